@@ -19,4 +19,13 @@ pub enum ContractError {
     EscrowNotFound = 8,
     InvalidAmount = 9,
     InvalidReleaseTime = 10,
+
+    // Arbiter Errors
+    ArbiterNotFound = 11,
+    ArbiterAlreadyRegistered = 12,
+    NotArbiter = 13,
+    InvalidArbiterFee = 14,
+
+    // Archival Errors
+    EscrowNotTerminal = 15,
 }
@@ -3,9 +3,15 @@ use soroban_sdk::{token, Address, Env, String};
 use crate::{
     error::ContractError,
     event,
-    storage::{self, Escrow, EscrowStatus},
+    storage::{self, Arbiter, Escrow, EscrowStatus, EscrowSummary},
 };
 
+/// Denominator for basis-point fee calculations (10_000 bps = 100%).
+const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Time an assigned arbiter has to resolve a dispute before the admin may step in.
+pub(crate) const ARBITER_RESPONSE_PERIOD: u64 = 14 * 24 * 60 * 60;
+
 /// Initializes the contract with an admin. Can only be called once.
 pub fn initialize(env: &Env, admin: Address) -> Result<(), ContractError> {
     if storage::has_admin(env) {
@@ -28,6 +34,117 @@ pub fn set_admin(env: &Env, admin: Address, new_admin: Address) -> Result<(), Co
     Ok(())
 }
 
+/// Registers a new arbiter in the pool with the given fee rate. Admin only.
+pub fn register_arbiter(
+    env: &Env,
+    admin: Address,
+    arbiter: Address,
+    fee_bps: u32,
+) -> Result<(), ContractError> {
+    admin.require_auth();
+    if !storage::is_admin(env, &admin) {
+        return Err(ContractError::NotAdmin);
+    }
+    if fee_bps > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidArbiterFee);
+    }
+
+    let mut pool = storage::get_arbiter_pool(env);
+    let record = match storage::get_arbiter(env, &arbiter) {
+        Ok(existing) if existing.active => return Err(ContractError::ArbiterAlreadyRegistered),
+        // Re-activating a removed arbiter keeps their case history.
+        Ok(existing) => Arbiter {
+            fee_bps,
+            active: true,
+            ..existing
+        },
+        Err(_) => Arbiter {
+            address: arbiter.clone(),
+            fee_bps,
+            active: true,
+            resolved_cases: 0,
+            total_fees_earned: 0,
+        },
+    };
+
+    pool.push_back(arbiter.clone());
+    storage::set_arbiter_pool(env, &pool);
+    storage::set_arbiter(env, &record);
+    event::arbiter_registered(env, &arbiter, fee_bps);
+    Ok(())
+}
+
+/// Removes an arbiter from the pool. Escrows already assigned to them are unaffected.
+pub fn remove_arbiter(env: &Env, admin: Address, arbiter: Address) -> Result<(), ContractError> {
+    admin.require_auth();
+    if !storage::is_admin(env, &admin) {
+        return Err(ContractError::NotAdmin);
+    }
+
+    let mut record = storage::get_arbiter(env, &arbiter)?;
+    if !record.active {
+        return Err(ContractError::ArbiterNotFound);
+    }
+
+    let mut pool = storage::get_arbiter_pool(env);
+    if let Some(index) = pool.first_index_of(&arbiter) {
+        pool.remove(index);
+    }
+    storage::set_arbiter_pool(env, &pool);
+
+    record.active = false;
+    storage::set_arbiter(env, &record);
+    event::arbiter_removed(env, &arbiter);
+    Ok(())
+}
+
+/// Updates the fee rate of a registered arbiter. Only applies to escrows created afterwards.
+pub fn set_arbiter_fee(
+    env: &Env,
+    admin: Address,
+    arbiter: Address,
+    fee_bps: u32,
+) -> Result<(), ContractError> {
+    admin.require_auth();
+    if !storage::is_admin(env, &admin) {
+        return Err(ContractError::NotAdmin);
+    }
+    if fee_bps > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidArbiterFee);
+    }
+
+    let mut record = storage::get_arbiter(env, &arbiter)?;
+    if !record.active {
+        return Err(ContractError::ArbiterNotFound);
+    }
+    record.fee_bps = fee_bps;
+    storage::set_arbiter(env, &record);
+    Ok(())
+}
+
+/// Picks the arbiter for a new escrow: the requested one if it is active,
+/// otherwise a pseudo-random member of the pool. Returns `None` if the pool is empty.
+fn select_arbiter(
+    env: &Env,
+    requested: Option<Address>,
+) -> Result<Option<Arbiter>, ContractError> {
+    if let Some(address) = requested {
+        let record = storage::get_arbiter(env, &address)?;
+        if !record.active {
+            return Err(ContractError::ArbiterNotFound);
+        }
+        return Ok(Some(record));
+    }
+
+    let pool = storage::get_arbiter_pool(env);
+    if pool.is_empty() {
+        return Ok(None);
+    }
+    let index: u64 = env.prng().gen_range(0..pool.len() as u64);
+    let address = pool.get(index as u32).unwrap();
+    Ok(Some(storage::get_arbiter(env, &address)?))
+}
+
 /// Creates a new escrow agreement and immediately locks the buyer's funds.
 pub fn create_escrow(
     env: &Env,
@@ -36,6 +153,7 @@ pub fn create_escrow(
     amount: i128,
     payment_token: Address,
     release_timestamp: u64,
+    arbiter: Option<Address>,
 ) -> Result<u64, ContractError> {
     buyer.require_auth();
 
@@ -46,6 +164,8 @@ pub fn create_escrow(
         return Err(ContractError::InvalidReleaseTime);
     }
 
+    let arbiter = select_arbiter(env, arbiter)?;
+
     // Lock the buyer's funds in the contract.
     let token_client = token::Client::new(env, &payment_token);
     token_client.transfer(&buyer, &env.current_contract_address(), &amount);
@@ -61,6 +181,9 @@ pub fn create_escrow(
         status: EscrowStatus::Active,
         dispute_reason: None,
         buyer_confirmed: false,
+        arbiter: arbiter.as_ref().map(|a| a.address.clone()),
+        arbiter_fee_bps: arbiter.as_ref().map_or(0, |a| a.fee_bps),
+        disputed_at: None,
     };
 
     storage::set_escrow(env, &escrow);
@@ -140,39 +263,119 @@ pub fn dispute_escrow(
 
     escrow.status = EscrowStatus::Disputed;
     escrow.dispute_reason = Some(reason.clone());
+    escrow.disputed_at = Some(env.ledger().timestamp());
     storage::set_escrow(env, &escrow);
     event::escrow_disputed(env, escrow_id, &buyer, reason);
 
     Ok(())
 }
 
-/// Allows a designated admin to resolve a dispute by refunding the buyer.
+/// Allows the escrow's arbiter (or the admin, if none was assigned) to resolve a
+/// dispute by refunding the buyer, less the arbiter's fee. The admin may also
+/// step in, without a fee, once the arbiter is removed or lets the response
+/// period lapse.
 pub fn resolve_dispute_and_refund(
     env: &Env,
-    admin: Address,
+    resolver: Address,
     escrow_id: u64,
 ) -> Result<(), ContractError> {
-    admin.require_auth();
-    if !storage::is_admin(env, &admin) {
-        return Err(ContractError::NotAdmin);
-    }
+    resolver.require_auth();
 
     let mut escrow = storage::get_escrow(env, escrow_id)?;
 
+    let arbitrated = escrow.arbiter.as_ref() == Some(&resolver);
+    if !arbitrated {
+        if !storage::is_admin(env, &resolver) {
+            return Err(match escrow.arbiter {
+                Some(_) => ContractError::NotArbiter,
+                None => ContractError::NotAdmin,
+            });
+        }
+        let may_step_in = match &escrow.arbiter {
+            Some(arbiter) => admin_may_step_in(env, &escrow, arbiter)?,
+            None => true,
+        };
+        if !may_step_in {
+            return Err(ContractError::NotArbiter);
+        }
+    }
     if escrow.status != EscrowStatus::Disputed {
         return Err(ContractError::EscrowNotDisputed);
     }
 
+    // Only the arbiter who actually resolved the dispute earns the fee.
+    let fee = if arbitrated {
+        escrow.amount * escrow.arbiter_fee_bps as i128 / BPS_DENOMINATOR as i128
+    } else {
+        0
+    };
+    let refund = escrow.amount - fee;
+
     let token_client = token::Client::new(env, &escrow.payment_token);
-    token_client.transfer(
-        &env.current_contract_address(),
-        &escrow.buyer,
-        &escrow.amount,
-    );
+    if fee > 0 {
+        token_client.transfer(&env.current_contract_address(), &resolver, &fee);
+    }
+    token_client.transfer(&env.current_contract_address(), &escrow.buyer, &refund);
+
+    if arbitrated {
+        let mut record = storage::get_arbiter(env, &resolver)?;
+        record.resolved_cases += 1;
+        record.total_fees_earned += fee;
+        storage::set_arbiter(env, &record);
+        event::arbiter_paid(env, escrow_id, &resolver, fee);
+    }
 
     escrow.status = EscrowStatus::Refunded;
     storage::set_escrow(env, &escrow);
-    event::funds_refunded(env, escrow_id, &escrow.buyer, escrow.amount);
+    event::funds_refunded(env, escrow_id, &escrow.buyer, refund);
+
+    Ok(())
+}
+
+/// Whether the admin may resolve a dispute in place of its assigned arbiter:
+/// the arbiter has been removed, or has not acted within the response period.
+fn admin_may_step_in(
+    env: &Env,
+    escrow: &Escrow,
+    arbiter: &Address,
+) -> Result<bool, ContractError> {
+    if !storage::get_arbiter(env, arbiter)?.active {
+        return Ok(true);
+    }
+    let disputed_at = escrow.disputed_at.unwrap_or(0);
+    Ok(env.ledger().timestamp() >= disputed_at.saturating_add(ARBITER_RESPONSE_PERIOD))
+}
+
+/// Extends the storage lifetime of an escrow. Anyone may call this to keep a
+/// long-running escrow from expiring out of the ledger state.
+pub fn extend_escrow_ttl(env: &Env, escrow_id: u64) -> Result<(), ContractError> {
+    if !storage::has_escrow(env, escrow_id) {
+        return Err(ContractError::EscrowNotFound);
+    }
+    storage::extend_escrow_ttl(env, escrow_id);
+    Ok(())
+}
+
+/// Replaces a Released/Refunded escrow with a compact summary record, freeing
+/// its full storage entry. Anyone may call this once the escrow is terminal.
+pub fn archive_escrow(env: &Env, escrow_id: u64) -> Result<(), ContractError> {
+    let escrow = storage::get_escrow(env, escrow_id)?;
+
+    if escrow.status != EscrowStatus::Released && escrow.status != EscrowStatus::Refunded {
+        return Err(ContractError::EscrowNotTerminal);
+    }
+
+    let summary = EscrowSummary {
+        id: escrow.id,
+        buyer: escrow.buyer,
+        seller: escrow.seller,
+        amount: escrow.amount,
+        status: escrow.status,
+        archived_at: env.ledger().timestamp(),
+    };
+    storage::set_archived_escrow(env, &summary);
+    storage::remove_escrow(env, escrow_id);
+    event::escrow_archived(env, escrow_id);
 
     Ok(())
 }
@@ -39,3 +39,28 @@ pub fn admin_changed(env: &Env, old_admin: &Address, new_admin: &Address) {
     let topics = (symbol_short!("set_admin"), old_admin.clone());
     env.events().publish(topics, new_admin.clone());
 }
+
+/// Emits an event when an arbiter joins the pool.
+pub fn arbiter_registered(env: &Env, arbiter: &Address, fee_bps: u32) {
+    let topics = (symbol_short!("arb_reg"), arbiter.clone());
+    env.events().publish(topics, fee_bps);
+}
+
+/// Emits an event when an arbiter is removed from the pool.
+pub fn arbiter_removed(env: &Env, arbiter: &Address) {
+    let topics = (symbol_short!("arb_rm"), arbiter.clone());
+    env.events().publish(topics, ());
+}
+
+/// Emits an event when an arbiter is paid their fee for resolving a dispute.
+pub fn arbiter_paid(env: &Env, escrow_id: u64, arbiter: &Address, fee: i128) {
+    let topics = (symbol_short!("arb_paid"), arbiter.clone());
+    let data = (escrow_id, fee);
+    env.events().publish(topics, data);
+}
+
+/// Emits an event when a terminal escrow is archived to a summary record.
+pub fn escrow_archived(env: &Env, escrow_id: u64) {
+    let topics = (symbol_short!("archived"),);
+    env.events().publish(topics, escrow_id);
+}
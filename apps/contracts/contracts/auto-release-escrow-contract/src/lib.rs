@@ -7,9 +7,12 @@ mod storage;
 #[cfg(test)]
 mod test;
 
-use soroban_sdk::{contract, contractimpl, Address, Env, String};
+use soroban_sdk::{contract, contractimpl, Address, Env, String, Vec};
 
-use crate::{error::ContractError, storage::Escrow};
+use crate::{
+    error::ContractError,
+    storage::{Arbiter, Escrow, EscrowSummary},
+};
 
 #[contract]
 pub struct AutoReleaseEscrowContract;
@@ -26,7 +29,33 @@ impl AutoReleaseEscrowContract {
         escrow_logic::set_admin(&env, admin, new_admin)
     }
 
+    /// Adds an arbiter to the pool with a fee rate in basis points. Admin only.
+    pub fn register_arbiter(
+        env: Env,
+        admin: Address,
+        arbiter: Address,
+        fee_bps: u32,
+    ) -> Result<(), ContractError> {
+        escrow_logic::register_arbiter(&env, admin, arbiter, fee_bps)
+    }
+
+    /// Removes an arbiter from the pool. Admin only.
+    pub fn remove_arbiter(env: Env, admin: Address, arbiter: Address) -> Result<(), ContractError> {
+        escrow_logic::remove_arbiter(&env, admin, arbiter)
+    }
+
+    /// Changes an arbiter's fee rate for escrows created from now on. Admin only.
+    pub fn set_arbiter_fee(
+        env: Env,
+        admin: Address,
+        arbiter: Address,
+        fee_bps: u32,
+    ) -> Result<(), ContractError> {
+        escrow_logic::set_arbiter_fee(&env, admin, arbiter, fee_bps)
+    }
+
     /// Creates a new escrow agreement and locks the buyer's funds.
+    /// If no arbiter is given, one is drawn from the pool.
     pub fn create_escrow(
         env: Env,
         buyer: Address,
@@ -34,6 +63,7 @@ impl AutoReleaseEscrowContract {
         amount: i128,
         payment_token: Address,
         release_timestamp: u64, // The time after which the funds can be released
+        arbiter: Option<Address>,
     ) -> Result<u64, ContractError> {
         escrow_logic::create_escrow(
             &env,
@@ -42,6 +72,7 @@ impl AutoReleaseEscrowContract {
             amount,
             payment_token,
             release_timestamp,
+            arbiter,
         )
     }
 
@@ -67,13 +98,26 @@ impl AutoReleaseEscrowContract {
         escrow_logic::dispute_escrow(&env, buyer, escrow_id, reason)
     }
 
-    /// Allows an admin/arbiter to resolve a dispute, refunding the buyer.
+    /// Allows the escrow's arbiter (or the admin, if none was assigned) to
+    /// resolve a dispute, refunding the buyer less the arbiter fee. The admin
+    /// can also resolve it, fee-free, once the arbiter is removed or the
+    /// response period has passed.
     pub fn resolve_dispute_and_refund(
         env: Env,
-        admin: Address,
+        resolver: Address,
         escrow_id: u64,
     ) -> Result<(), ContractError> {
-        escrow_logic::resolve_dispute_and_refund(&env, admin, escrow_id)
+        escrow_logic::resolve_dispute_and_refund(&env, resolver, escrow_id)
+    }
+
+    /// Extends the storage lifetime of an escrow. Callable by anyone.
+    pub fn extend_escrow_ttl(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        escrow_logic::extend_escrow_ttl(&env, escrow_id)
+    }
+
+    /// Archives a Released or Refunded escrow into a compact summary record.
+    pub fn archive_escrow(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        escrow_logic::archive_escrow(&env, escrow_id)
     }
 
     // --- Read-Only Functions ---
@@ -82,4 +126,19 @@ impl AutoReleaseEscrowContract {
     pub fn get_escrow(env: Env, escrow_id: u64) -> Result<Escrow, ContractError> {
         storage::get_escrow(&env, escrow_id)
     }
+
+    /// Retrieves the summary of an archived escrow.
+    pub fn get_archived_escrow(env: Env, escrow_id: u64) -> Result<EscrowSummary, ContractError> {
+        storage::get_archived_escrow(&env, escrow_id)
+    }
+
+    /// Retrieves an arbiter's fee rate and case statistics.
+    pub fn get_arbiter(env: Env, arbiter: Address) -> Result<Arbiter, ContractError> {
+        storage::get_arbiter(&env, &arbiter)
+    }
+
+    /// Lists the arbiters currently eligible for new escrows.
+    pub fn get_arbiter_pool(env: Env) -> Vec<Address> {
+        storage::get_arbiter_pool(&env)
+    }
 }
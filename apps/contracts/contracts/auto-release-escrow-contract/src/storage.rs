@@ -1,7 +1,20 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
 
 use crate::error::ContractError;
 
+pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const ESCROW_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const ESCROW_LIFETIME_THRESHOLD: u32 = ESCROW_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const ARBITER_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const ARBITER_LIFETIME_THRESHOLD: u32 = ARBITER_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const ARCHIVE_BUMP_AMOUNT: u32 = 90 * DAY_IN_LEDGERS;
+pub(crate) const ARCHIVE_LIFETIME_THRESHOLD: u32 = ARCHIVE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum EscrowStatus {
@@ -23,6 +36,31 @@ pub struct Escrow {
     pub status: EscrowStatus,
     pub dispute_reason: Option<String>,
     pub buyer_confirmed: bool,
+    pub arbiter: Option<Address>, // None falls back to the contract admin
+    pub arbiter_fee_bps: u32,     // Fee rate locked in at creation
+    pub disputed_at: Option<u64>, // Starts the arbiter's response period
+}
+
+/// Compact record kept for a Released/Refunded escrow after it has been archived.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowSummary {
+    pub id: u64,
+    pub buyer: Address,
+    pub seller: Address,
+    pub amount: i128,
+    pub status: EscrowStatus,
+    pub archived_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Arbiter {
+    pub address: Address,
+    pub fee_bps: u32, // Fee charged on the escrowed amount, in basis points
+    pub active: bool, // Inactive arbiters keep their cases but get no new ones
+    pub resolved_cases: u32,
+    pub total_fees_earned: i128,
 }
 
 #[contracttype]
@@ -31,6 +69,9 @@ pub enum DataKey {
     Admin,
     EscrowCounter,
     Escrow(u64),
+    ArchivedEscrow(u64),
+    Arbiter(Address),
+    ArbiterPool,
 }
 
 // --- Storage Helper Functions ---
@@ -64,15 +105,97 @@ pub fn get_next_escrow_id(env: &Env) -> u64 {
     next_id
 }
 
-pub fn get_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, ContractError> {
+pub fn extend_instance_ttl(env: &Env) {
     env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn has_escrow(env: &Env, escrow_id: u64) -> bool {
+    env.storage().persistent().has(&DataKey::Escrow(escrow_id))
+}
+
+pub fn extend_escrow_ttl(env: &Env, escrow_id: u64) {
+    env.storage().persistent().extend_ttl(
+        &DataKey::Escrow(escrow_id),
+        ESCROW_LIFETIME_THRESHOLD,
+        ESCROW_BUMP_AMOUNT,
+    );
+}
+
+pub fn get_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, ContractError> {
+    let escrow = env
+        .storage()
         .persistent()
         .get(&DataKey::Escrow(escrow_id))
-        .ok_or(ContractError::EscrowNotFound)
+        .ok_or(ContractError::EscrowNotFound)?;
+    extend_escrow_ttl(env, escrow_id);
+    Ok(escrow)
 }
 
 pub fn set_escrow(env: &Env, escrow: &Escrow) {
     env.storage()
         .persistent()
         .set(&DataKey::Escrow(escrow.id), escrow);
+    extend_escrow_ttl(env, escrow.id);
+    extend_instance_ttl(env);
+}
+
+pub fn remove_escrow(env: &Env, escrow_id: u64) {
+    env.storage().persistent().remove(&DataKey::Escrow(escrow_id));
+}
+
+pub fn get_archived_escrow(env: &Env, escrow_id: u64) -> Result<EscrowSummary, ContractError> {
+    let key = DataKey::ArchivedEscrow(escrow_id);
+    let summary = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::EscrowNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, ARCHIVE_LIFETIME_THRESHOLD, ARCHIVE_BUMP_AMOUNT);
+    Ok(summary)
+}
+
+pub fn set_archived_escrow(env: &Env, summary: &EscrowSummary) {
+    let key = DataKey::ArchivedEscrow(summary.id);
+    env.storage().persistent().set(&key, summary);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, ARCHIVE_LIFETIME_THRESHOLD, ARCHIVE_BUMP_AMOUNT);
+}
+
+pub fn get_arbiter(env: &Env, arbiter: &Address) -> Result<Arbiter, ContractError> {
+    let key = DataKey::Arbiter(arbiter.clone());
+    let record = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::ArbiterNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, ARBITER_LIFETIME_THRESHOLD, ARBITER_BUMP_AMOUNT);
+    Ok(record)
+}
+
+pub fn set_arbiter(env: &Env, arbiter: &Arbiter) {
+    let key = DataKey::Arbiter(arbiter.address.clone());
+    env.storage().persistent().set(&key, arbiter);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, ARBITER_LIFETIME_THRESHOLD, ARBITER_BUMP_AMOUNT);
+}
+
+/// Returns the addresses of all active arbiters eligible for new escrows.
+pub fn get_arbiter_pool(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::ArbiterPool)
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn set_arbiter_pool(env: &Env, pool: &Vec<Address>) {
+    env.storage().instance().set(&DataKey::ArbiterPool, pool);
+    extend_instance_ttl(env);
 }
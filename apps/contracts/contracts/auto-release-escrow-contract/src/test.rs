@@ -1,9 +1,19 @@
 #![cfg(test)]
 
 use super::*;
-use crate::{error::ContractError, storage::EscrowStatus};
+use crate::{
+    error::ContractError,
+    escrow_logic::ARBITER_RESPONSE_PERIOD,
+    storage::{
+        DataKey, EscrowStatus, ARBITER_BUMP_AMOUNT, DAY_IN_LEDGERS, ESCROW_BUMP_AMOUNT,
+        INSTANCE_BUMP_AMOUNT,
+    },
+};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger as _},
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Ledger as _,
+    },
     token, Address, Env, IntoVal, String,
 };
 use token::StellarAssetClient as TokenAdminClient;
@@ -89,6 +99,7 @@ fn test_set_admin() {
         &100,
         &test.token.address,
         &(test.env.ledger().timestamp() + 100),
+        &None,
     );
     test.contract
         .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
@@ -121,6 +132,7 @@ fn test_create_escrow_and_fund_locking() {
         &1000, // amount
         &test.token.address,
         &release_timestamp,
+        &None,
     );
 
     assert_eq!(escrow_id, 1);
@@ -147,6 +159,7 @@ fn test_release_funds_after_time_elapses() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
 
     // Advance time past the release timestamp
@@ -172,6 +185,7 @@ fn test_confirm_receipt_and_early_release() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
 
     // Buyer confirms receipt
@@ -198,6 +212,7 @@ fn test_dispute_and_admin_refund() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
 
     // Buyer disputes the escrow
@@ -231,6 +246,7 @@ fn test_release_fails_before_time() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
 
     let result = test.contract.try_release_funds(&escrow_id);
@@ -247,6 +263,7 @@ fn test_release_fails_if_disputed() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
     test.contract
         .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
@@ -265,6 +282,7 @@ fn test_dispute_fails_if_not_buyer() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
 
     let result =
@@ -283,6 +301,7 @@ fn test_dispute_fails_if_already_disputed() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
     test.contract
         .dispute_escrow(&test.buyer, &escrow_id, &"reason1".into_val(&test.env));
@@ -303,6 +322,7 @@ fn test_resolve_dispute_fails_if_not_admin() {
         &1000,
         &test.token.address,
         &release_timestamp,
+        &None,
     );
     test.contract
         .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
@@ -313,3 +333,375 @@ fn test_resolve_dispute_fails_if_not_admin() {
         .try_resolve_dispute_and_refund(&test.seller, &escrow_id);
     assert_eq!(result, Err(Ok(ContractError::NotAdmin)));
 }
+
+// --- Arbiter Tests ---
+
+#[test]
+fn test_register_arbiter() {
+    let test = EscrowTest::setup();
+    let arbiter = Address::generate(&test.env);
+
+    test.contract.register_arbiter(&test.admin, &arbiter, &250);
+
+    let record = test.contract.get_arbiter(&arbiter);
+    assert_eq!(record.fee_bps, 250);
+    assert!(record.active);
+    assert_eq!(record.resolved_cases, 0);
+    assert_eq!(test.contract.get_arbiter_pool().len(), 1);
+
+    // Registering twice should fail.
+    let result = test.contract.try_register_arbiter(&test.admin, &arbiter, &250);
+    assert_eq!(result, Err(Ok(ContractError::ArbiterAlreadyRegistered)));
+}
+
+#[test]
+fn test_register_arbiter_validation() {
+    let test = EscrowTest::setup();
+    let arbiter = Address::generate(&test.env);
+
+    let result = test.contract.try_register_arbiter(&test.seller, &arbiter, &250);
+    assert_eq!(result, Err(Ok(ContractError::NotAdmin)));
+
+    let result = test.contract.try_register_arbiter(&test.admin, &arbiter, &10_001);
+    assert_eq!(result, Err(Ok(ContractError::InvalidArbiterFee)));
+}
+
+#[test]
+fn test_create_escrow_with_chosen_arbiter() {
+    let test = EscrowTest::setup();
+    let arbiter1 = Address::generate(&test.env);
+    let arbiter2 = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter1, &100);
+    test.contract.register_arbiter(&test.admin, &arbiter2, &300);
+
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &1000,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &Some(arbiter2.clone()),
+    );
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.arbiter, Some(arbiter2));
+    assert_eq!(escrow.arbiter_fee_bps, 300);
+}
+
+#[test]
+fn test_create_escrow_with_unregistered_arbiter_fails() {
+    let test = EscrowTest::setup();
+    let stranger = Address::generate(&test.env);
+
+    let result = test.contract.try_create_escrow(
+        &test.buyer,
+        &test.seller,
+        &1000,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &Some(stranger),
+    );
+    assert_eq!(result, Err(Ok(ContractError::ArbiterNotFound)));
+}
+
+#[test]
+fn test_create_escrow_draws_arbiter_from_pool() {
+    let test = EscrowTest::setup();
+    let arbiter1 = Address::generate(&test.env);
+    let arbiter2 = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter1, &100);
+    test.contract.register_arbiter(&test.admin, &arbiter2, &100);
+
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &1000,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &None,
+    );
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    let assigned = escrow.arbiter.unwrap();
+    assert!(assigned == arbiter1 || assigned == arbiter2);
+}
+
+#[test]
+fn test_removed_arbiter_not_drawn() {
+    let test = EscrowTest::setup();
+    let arbiter1 = Address::generate(&test.env);
+    let arbiter2 = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter1, &100);
+    test.contract.register_arbiter(&test.admin, &arbiter2, &100);
+    test.contract.remove_arbiter(&test.admin, &arbiter1);
+
+    assert!(!test.contract.get_arbiter(&arbiter1).active);
+    assert_eq!(test.contract.get_arbiter_pool().len(), 1);
+
+    for _ in 0..5 {
+        let escrow_id = test.contract.create_escrow(
+            &test.buyer,
+            &test.seller,
+            &100,
+            &test.token.address,
+            &(test.env.ledger().timestamp() + 3600),
+            &None,
+        );
+        let escrow = test.contract.get_escrow(&escrow_id);
+        assert_eq!(escrow.arbiter, Some(arbiter2.clone()));
+    }
+
+    let result = test.contract.try_create_escrow(
+        &test.buyer,
+        &test.seller,
+        &100,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &Some(arbiter1.clone()),
+    );
+    assert_eq!(result, Err(Ok(ContractError::ArbiterNotFound)));
+
+    let result = test
+        .contract
+        .try_set_arbiter_fee(&test.admin, &arbiter1, &200);
+    assert_eq!(result, Err(Ok(ContractError::ArbiterNotFound)));
+}
+
+#[test]
+fn test_arbiter_resolves_dispute_and_earns_fee() {
+    let test = EscrowTest::setup();
+    let arbiter = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter, &500); // 5%
+
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &1000,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &Some(arbiter.clone()),
+    );
+    test.contract
+        .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
+
+    // The admin can no longer resolve an escrow with an assigned arbiter.
+    let result = test
+        .contract
+        .try_resolve_dispute_and_refund(&test.admin, &escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::NotArbiter)));
+
+    test.contract
+        .resolve_dispute_and_refund(&arbiter, &escrow_id);
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    assert_eq!(test.token.balance(&arbiter), 50);
+    assert_eq!(test.token.balance(&test.buyer), 9950);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+
+    let record = test.contract.get_arbiter(&arbiter);
+    assert_eq!(record.resolved_cases, 1);
+    assert_eq!(record.total_fees_earned, 50);
+}
+
+#[test]
+fn test_fee_change_does_not_affect_existing_escrow() {
+    let test = EscrowTest::setup();
+    let arbiter = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter, &100); // 1%
+
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &1000,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &Some(arbiter.clone()),
+    );
+    test.contract.set_arbiter_fee(&test.admin, &arbiter, &1000);
+    test.contract
+        .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
+    test.contract
+        .resolve_dispute_and_refund(&arbiter, &escrow_id);
+
+    assert_eq!(test.token.balance(&arbiter), 10);
+    assert_eq!(test.contract.get_arbiter(&arbiter).fee_bps, 1000);
+}
+
+#[test]
+fn test_admin_resolves_for_removed_arbiter() {
+    let test = EscrowTest::setup();
+    let arbiter = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter, &500);
+
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &1000,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &Some(arbiter.clone()),
+    );
+    test.contract
+        .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
+    test.contract.remove_arbiter(&test.admin, &arbiter);
+
+    // The admin steps in for the removed arbiter, and no fee is charged.
+    test.contract
+        .resolve_dispute_and_refund(&test.admin, &escrow_id);
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    assert_eq!(test.token.balance(&test.buyer), 10000);
+    assert_eq!(test.token.balance(&arbiter), 0);
+    assert_eq!(test.contract.get_arbiter(&arbiter).resolved_cases, 0);
+}
+
+#[test]
+fn test_admin_resolves_after_arbiter_timeout() {
+    let test = EscrowTest::setup();
+    let arbiter = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter, &500);
+
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &1000,
+        &test.token.address,
+        &(test.env.ledger().timestamp() + 3600),
+        &Some(arbiter.clone()),
+    );
+    test.contract
+        .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += ARBITER_RESPONSE_PERIOD - 1);
+    let result = test
+        .contract
+        .try_resolve_dispute_and_refund(&test.admin, &escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::NotArbiter)));
+
+    // Once the response period lapses, the admin may resolve it.
+    test.env.ledger().with_mut(|l| l.timestamp += 1);
+    test.contract
+        .resolve_dispute_and_refund(&test.admin, &escrow_id);
+
+    assert_eq!(test.token.balance(&test.buyer), 10000);
+    assert_eq!(test.token.balance(&arbiter), 0);
+
+    // Other callers are still turned away.
+    let result = test
+        .contract
+        .try_resolve_dispute_and_refund(&test.seller, &escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::NotArbiter)));
+}
+
+// --- Storage Lifetime Tests ---
+
+impl EscrowTest<'_> {
+    fn escrow_ttl(&self, escrow_id: u64) -> u32 {
+        self.env.as_contract(&self.contract.address, || {
+            self.env
+                .storage()
+                .persistent()
+                .get_ttl(&DataKey::Escrow(escrow_id))
+        })
+    }
+
+    fn create_default_escrow(&self) -> u64 {
+        self.contract.create_escrow(
+            &self.buyer,
+            &self.seller,
+            &1000,
+            &self.token.address,
+            &(self.env.ledger().timestamp() + 3600),
+            &None,
+        )
+    }
+}
+
+#[test]
+fn test_escrow_ttl_bumped_on_touch() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_default_escrow();
+    assert_eq!(test.escrow_ttl(escrow_id), ESCROW_BUMP_AMOUNT);
+
+    // Let two days' worth of ledgers pass, then touch the escrow.
+    test.env
+        .ledger()
+        .with_mut(|l| l.sequence_number += 2 * DAY_IN_LEDGERS);
+    assert_eq!(
+        test.escrow_ttl(escrow_id),
+        ESCROW_BUMP_AMOUNT - 2 * DAY_IN_LEDGERS
+    );
+
+    test.contract.confirm_receipt(&test.buyer, &escrow_id);
+    assert_eq!(test.escrow_ttl(escrow_id), ESCROW_BUMP_AMOUNT);
+}
+
+#[test]
+fn test_register_arbiter_extends_ttl() {
+    let test = EscrowTest::setup();
+    let arbiter = Address::generate(&test.env);
+    test.contract.register_arbiter(&test.admin, &arbiter, &100);
+
+    test.env.as_contract(&test.contract.address, || {
+        let storage = test.env.storage();
+        assert_eq!(
+            storage.persistent().get_ttl(&DataKey::Arbiter(arbiter.clone())),
+            ARBITER_BUMP_AMOUNT
+        );
+        assert_eq!(storage.instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+fn test_extend_escrow_ttl() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_default_escrow();
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.sequence_number += 2 * DAY_IN_LEDGERS);
+    test.contract.extend_escrow_ttl(&escrow_id);
+    assert_eq!(test.escrow_ttl(escrow_id), ESCROW_BUMP_AMOUNT);
+
+    let result = test.contract.try_extend_escrow_ttl(&99);
+    assert_eq!(result, Err(Ok(ContractError::EscrowNotFound)));
+}
+
+#[test]
+fn test_archive_released_escrow() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_default_escrow();
+    test.contract.confirm_receipt(&test.buyer, &escrow_id);
+    test.contract.release_funds(&escrow_id);
+
+    test.contract.archive_escrow(&escrow_id);
+
+    // The full record is gone, only the summary remains.
+    let result = test.contract.try_get_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::EscrowNotFound)));
+
+    let summary = test.contract.get_archived_escrow(&escrow_id);
+    assert_eq!(summary.id, escrow_id);
+    assert_eq!(summary.buyer, test.buyer);
+    assert_eq!(summary.seller, test.seller);
+    assert_eq!(summary.amount, 1000);
+    assert_eq!(summary.status, EscrowStatus::Released);
+}
+
+#[test]
+fn test_archive_active_escrow_fails() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_default_escrow();
+
+    let result = test.contract.try_archive_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::EscrowNotTerminal)));
+
+    test.contract
+        .dispute_escrow(&test.buyer, &escrow_id, &"reason".into_val(&test.env));
+    let result = test.contract.try_archive_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::EscrowNotTerminal)));
+}
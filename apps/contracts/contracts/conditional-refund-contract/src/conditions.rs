@@ -0,0 +1,58 @@
+use crate::error::ContractError;
+use crate::refund_storage::{ContractStatus, RefundCondition, RefundContract};
+use soroban_sdk::{Env, Vec};
+
+// Upper bound on clauses per contract, keeps evaluation cost predictable
+pub const MAX_REFUND_CONDITIONS: u32 = 10;
+
+pub fn validate_conditions(conditions: &Vec<RefundCondition>) -> Result<(), ContractError> {
+    if conditions.len() > MAX_REFUND_CONDITIONS {
+        return Err(ContractError::InvalidCondition);
+    }
+
+    for condition in conditions.iter() {
+        if let RefundCondition::RejectedWithin(window) = condition {
+            if window == 0 {
+                return Err(ContractError::InvalidCondition);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the index of the first refund condition that currently holds, if any.
+pub fn evaluate_conditions(env: &Env, contract: &RefundContract) -> Option<u32> {
+    let current_time = env.ledger().timestamp();
+
+    for (index, condition) in contract.refund_conditions.iter().enumerate() {
+        let fired = match condition {
+            RefundCondition::NotDeliveredBy(deadline) => {
+                let delivered_in_time = contract
+                    .delivered_at
+                    .is_some_and(|delivered_at| delivered_at <= deadline);
+                current_time > deadline && !delivered_in_time
+            }
+            RefundCondition::RejectedWithin(window) => {
+                // Buyer must have asked for the refund within the window after delivery
+                let rejected_by_buyer = contract.status == ContractStatus::RefundRequested
+                    && contract.refund_requester.as_ref() == Some(&contract.buyer);
+                match (contract.delivered_at, contract.refund_requested_at) {
+                    (Some(delivered_at), Some(requested_at)) => {
+                        rejected_by_buyer && requested_at <= delivered_at.saturating_add(window)
+                    }
+                    _ => false,
+                }
+            }
+            RefundCondition::OracleAttests(_, _) => {
+                contract.attested_conditions.contains(index as u32)
+            }
+        };
+
+        if fired {
+            return Some(index as u32);
+        }
+    }
+
+    None
+}
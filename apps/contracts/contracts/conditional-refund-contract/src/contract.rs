@@ -1,8 +1,11 @@
+use crate::conditions;
 use crate::error::ContractError;
 use crate::events::*;
 use crate::refund_storage::*;
 use crate::storage;
-use soroban_sdk::{token, Address, Env, String, Vec};
+use soroban_sdk::{token, Address, BytesN, Env, String, Vec};
+
+const BASIS_POINTS: u128 = 10_000;
 
 pub fn create_refund_contract(
     env: &Env,
@@ -10,14 +13,27 @@ pub fn create_refund_contract(
     seller: &Address,
     token: &Address,
     amount: u128,
-    refund_deadline: u64,
-    delivery_deadline: u64,
-    refund_conditions: String,
+    terms: RefundTerms,
 ) -> Result<u64, ContractError> {
+    let RefundTerms {
+        refund_deadline,
+        delivery_deadline,
+        refund_conditions,
+        refund_policy,
+    } = terms;
+
     if amount == 0 {
         return Err(ContractError::InvalidAmount);
     }
 
+    if refund_policy.restocking_fee_bps as u128 > BASIS_POINTS
+        || (refund_policy.requires_return && refund_policy.seller_response_window == 0)
+    {
+        return Err(ContractError::InvalidRefundPolicy);
+    }
+
+    conditions::validate_conditions(&refund_conditions)?;
+
     if buyer == seller {
         return Err(ContractError::InvalidInput);
     }
@@ -36,10 +52,14 @@ pub fn create_refund_contract(
         token: token.clone(),
         amount,
         escrowed_amount: 0,
+        refunded_amount: 0,
+        refund_policy,
         status: ContractStatus::Created,
         refund_deadline,
         delivery_deadline,
         refund_conditions,
+        attested_conditions: Vec::new(env),
+        triggered_condition: None,
         created_at: current_time,
         funded_at: None,
         delivered_at: None,
@@ -49,6 +69,10 @@ pub fn create_refund_contract(
         refund_processed_at: None,
         refund_reason: None,
         refund_requester: None,
+        return_tracking_hash: None,
+        return_shipped_at: None,
+        return_received_at: None,
+        return_disputed: false,
     };
 
     set_contract(env, &contract);
@@ -161,7 +185,7 @@ pub fn request_refund(
     contract_id: u64,
     requester: &Address,
     reason: String,
-) -> Result<(), ContractError> {
+) -> Result<Option<u32>, ContractError> {
     let mut contract = get_contract(env, contract_id)?;
 
     if contract.buyer != *requester && contract.seller != *requester {
@@ -175,7 +199,10 @@ pub fn request_refund(
         return Err(ContractError::OperationNotAllowed);
     }
 
-    if contract.status == ContractStatus::RefundRequested {
+    if contract.status == ContractStatus::RefundRequested
+        || contract.status == ContractStatus::ReturnShipped
+        || contract.status == ContractStatus::ReturnReceived
+    {
         return Err(ContractError::RefundAlreadyRequested);
     }
 
@@ -188,54 +215,102 @@ pub fn request_refund(
     contract.refund_requested_at = Some(current_time);
     contract.refund_reason = Some(reason.clone());
     contract.refund_requester = Some(requester.clone());
+    contract.triggered_condition = conditions::evaluate_conditions(env, &contract);
+
+    set_contract(env, &contract);
+
+    emit_refund_requested(
+        env,
+        contract_id,
+        requester.clone(),
+        reason,
+        contract.triggered_condition,
+    );
+
+    Ok(contract.triggered_condition)
+}
+
+pub fn attest_condition(
+    env: &Env,
+    contract_id: u64,
+    oracle: &Address,
+    claim: BytesN<32>,
+) -> Result<(), ContractError> {
+    let mut contract = get_contract(env, contract_id)?;
+
+    if contract.status == ContractStatus::Completed
+        || contract.status == ContractStatus::Cancelled
+        || contract.status == ContractStatus::RefundProcessed
+    {
+        return Err(ContractError::OperationNotAllowed);
+    }
+
+    let mut condition_index = None;
+    for (index, condition) in contract.refund_conditions.iter().enumerate() {
+        if condition == RefundCondition::OracleAttests(oracle.clone(), claim.clone()) {
+            condition_index = Some(index as u32);
+            break;
+        }
+    }
+    let condition_index = condition_index.ok_or(ContractError::Unauthorized)?;
 
+    if contract.attested_conditions.contains(condition_index) {
+        return Err(ContractError::ConditionAlreadyAttested);
+    }
+
+    contract.attested_conditions.push_back(condition_index);
     set_contract(env, &contract);
 
-    emit_refund_requested(env, contract_id, requester.clone(), reason);
+    emit_condition_attested(env, contract_id, oracle.clone(), claim, condition_index);
 
     Ok(())
 }
 
-pub fn process_automatic_refund(env: &Env, contract_id: u64) -> Result<(), ContractError> {
+pub fn process_automatic_refund(env: &Env, contract_id: u64) -> Result<u32, ContractError> {
     let mut contract = get_contract(env, contract_id)?;
 
     let current_time = env.ledger().timestamp();
 
-    // Check if automatic refund conditions are met
-    let can_auto_refund = match contract.status {
-        ContractStatus::Funded => {
-            // Auto-refund if delivery deadline passed and not delivered
-            current_time > contract.delivery_deadline
+    // A disputed return is settled by the admin, not by the clauses
+    if contract.return_disputed {
+        return Err(ContractError::ReturnDisputed);
+    }
+
+    // Only escrows that are still open can be refunded automatically
+    let triggered_condition = match contract.status {
+        ContractStatus::Funded | ContractStatus::Delivered | ContractStatus::RefundRequested => {
+            let fired = conditions::evaluate_conditions(env, &contract);
+            if fired.is_some() && awaiting_return(&contract) {
+                return Err(ContractError::ReturnRequired);
+            }
+            fired
         }
-        ContractStatus::RefundRequested => {
-            // Auto-refund if buyer requested and conditions met
-            contract.refund_requester.as_ref() == Some(&contract.buyer)
+        // Seller confirmed the return, or stayed silent past the response window
+        ContractStatus::ReturnReceived => contract.triggered_condition,
+        ContractStatus::ReturnShipped if seller_response_window_passed(env, &contract) => {
+            contract.triggered_condition
         }
-        _ => false,
+        _ => None,
     };
 
-    if !can_auto_refund {
-        return Err(ContractError::RefundConditionsNotMet);
-    }
+    let triggered_condition = match triggered_condition {
+        Some(index) => index,
+        None => return Err(ContractError::RefundConditionsNotMet),
+    };
 
     if contract.escrowed_amount == 0 {
         return Err(ContractError::RefundAlreadyProcessed);
     }
 
     // Process refund to buyer
-    let contract_address = env.current_contract_address();
-    let token_client = token::Client::new(env, &contract.token);
-
-    token_client.transfer(
-        &contract_address,
-        &contract.buyer,
-        &(contract.escrowed_amount as i128),
-    );
-
     let refunded_amount = contract.escrowed_amount;
+    let restocking_fee = transfer_refund(env, &contract, refunded_amount);
+
     contract.escrowed_amount = 0;
+    contract.refunded_amount += refunded_amount - restocking_fee;
     contract.status = ContractStatus::RefundProcessed;
     contract.refund_processed_at = Some(current_time);
+    contract.triggered_condition = Some(triggered_condition);
 
     set_contract(env, &contract);
 
@@ -244,17 +319,19 @@ pub fn process_automatic_refund(env: &Env, contract_id: u64) -> Result<(), Contr
         contract_id,
         contract.buyer.clone(),
         refunded_amount,
+        restocking_fee,
         true,
+        Some(triggered_condition),
     );
 
-    Ok(())
+    Ok(triggered_condition)
 }
 
 pub fn resolve_refund_dispute(
     env: &Env,
     contract_id: u64,
     admin: &Address,
-    approve_refund: bool,
+    refund_amount: u128,
 ) -> Result<(), ContractError> {
     if !storage::is_admin(env, admin) {
         return Err(ContractError::AdminOnly);
@@ -262,34 +339,63 @@ pub fn resolve_refund_dispute(
 
     let mut contract = get_contract(env, contract_id)?;
 
-    if contract.status != ContractStatus::RefundRequested {
+    if contract.status != ContractStatus::RefundRequested
+        && contract.status != ContractStatus::ReturnShipped
+        && contract.status != ContractStatus::ReturnReceived
+    {
         return Err(ContractError::RefundNotRequested);
     }
 
+    // The buyer gets nothing back until the goods are, or the seller let the
+    // response window lapse
+    if refund_amount > 0
+        && awaiting_return(&contract)
+        && !seller_response_window_passed(env, &contract)
+    {
+        return Err(ContractError::ReturnRequired);
+    }
+
+    if refund_amount > contract.escrowed_amount {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    let is_partial = refund_amount > 0 && refund_amount < contract.escrowed_amount;
+    if is_partial && !contract.refund_policy.allow_partial_refunds {
+        return Err(ContractError::PartialRefundNotAllowed);
+    }
+
     let current_time = env.ledger().timestamp();
-    let contract_address = env.current_contract_address();
-    let token_client = token::Client::new(env, &contract.token);
-    let amount_transferred = contract.escrowed_amount;
+    let mut restocking_fee = 0;
+    let amount_transferred;
 
-    if approve_refund {
-        // Refund to buyer
-        token_client.transfer(
-            &contract_address,
-            &contract.buyer,
-            &(contract.escrowed_amount as i128),
-        );
+    if refund_amount > 0 {
+        // Refund (part of) the escrow to buyer, less any restocking fee
+        restocking_fee = transfer_refund(env, &contract, refund_amount);
+        amount_transferred = refund_amount;
 
-        contract.escrowed_amount = 0;
-        contract.status = ContractStatus::RefundProcessed;
+        contract.escrowed_amount -= refund_amount;
+        contract.refunded_amount += refund_amount - restocking_fee;
         contract.refund_processed_at = Some(current_time);
+
+        // A partial refund reopens the order for the remaining escrow
+        contract.status = if contract.escrowed_amount == 0 {
+            ContractStatus::RefundProcessed
+        } else if contract.delivered_at.is_some() {
+            ContractStatus::Delivered
+        } else {
+            ContractStatus::Funded
+        };
     } else {
         // Release to seller
+        let contract_address = env.current_contract_address();
+        let token_client = token::Client::new(env, &contract.token);
         token_client.transfer(
             &contract_address,
             &contract.seller,
             &(contract.escrowed_amount as i128),
         );
 
+        amount_transferred = contract.escrowed_amount;
         contract.escrowed_amount = 0;
         contract.status = ContractStatus::Completed;
         contract.completed_at = Some(current_time);
@@ -301,13 +407,144 @@ pub fn resolve_refund_dispute(
         env,
         contract_id,
         admin.clone(),
-        approve_refund,
+        refund_amount > 0,
         amount_transferred,
+        restocking_fee,
+        contract.escrowed_amount,
     );
 
     Ok(())
 }
 
+pub fn ship_return(
+    env: &Env,
+    contract_id: u64,
+    buyer: &Address,
+    tracking_hash: BytesN<32>,
+) -> Result<(), ContractError> {
+    let mut contract = get_contract(env, contract_id)?;
+
+    if contract.buyer != *buyer {
+        return Err(ContractError::BuyerOnly);
+    }
+
+    if contract.status != ContractStatus::RefundRequested || !awaiting_return(&contract) {
+        return Err(ContractError::OperationNotAllowed);
+    }
+
+    contract.status = ContractStatus::ReturnShipped;
+    contract.return_tracking_hash = Some(tracking_hash.clone());
+    contract.return_shipped_at = Some(env.ledger().timestamp());
+
+    set_contract(env, &contract);
+
+    emit_return_shipped(env, contract_id, buyer.clone(), tracking_hash);
+
+    Ok(())
+}
+
+pub fn confirm_return_received(
+    env: &Env,
+    contract_id: u64,
+    seller: &Address,
+) -> Result<(), ContractError> {
+    let mut contract = get_contract(env, contract_id)?;
+
+    if contract.seller != *seller {
+        return Err(ContractError::SellerOnly);
+    }
+
+    if contract.status != ContractStatus::ReturnShipped {
+        return Err(ContractError::ReturnNotShipped);
+    }
+
+    contract.status = ContractStatus::ReturnReceived;
+    contract.return_received_at = Some(env.ledger().timestamp());
+
+    set_contract(env, &contract);
+
+    emit_return_received(env, contract_id, seller.clone());
+
+    Ok(())
+}
+
+pub fn dispute_return(
+    env: &Env,
+    contract_id: u64,
+    seller: &Address,
+    reason: String,
+) -> Result<(), ContractError> {
+    let mut contract = get_contract(env, contract_id)?;
+
+    if contract.seller != *seller {
+        return Err(ContractError::SellerOnly);
+    }
+
+    if contract.status != ContractStatus::ReturnShipped {
+        return Err(ContractError::ReturnNotShipped);
+    }
+
+    if seller_response_window_passed(env, &contract) {
+        return Err(ContractError::SellerResponseWindowPassed);
+    }
+
+    // Goods are back with the seller; the refund amount goes to admin resolution
+    let current_time = env.ledger().timestamp();
+    contract.status = ContractStatus::RefundRequested;
+    contract.return_received_at = Some(current_time);
+    contract.refund_requested_at = Some(current_time);
+    contract.refund_reason = Some(reason.clone());
+    contract.refund_requester = Some(seller.clone());
+    contract.return_disputed = true;
+
+    set_contract(env, &contract);
+
+    emit_return_disputed(env, contract_id, seller.clone(), reason);
+
+    Ok(())
+}
+
+// Delivered goods under a return policy must come back before any refund
+fn awaiting_return(contract: &RefundContract) -> bool {
+    contract.refund_policy.requires_return
+        && contract.delivered_at.is_some()
+        && contract.return_received_at.is_none()
+}
+
+fn seller_response_window_passed(env: &Env, contract: &RefundContract) -> bool {
+    match contract.return_shipped_at {
+        Some(shipped_at) => {
+            env.ledger().timestamp()
+                > shipped_at.saturating_add(contract.refund_policy.seller_response_window)
+        }
+        None => false,
+    }
+}
+
+/// Sends `refund_amount` out of escrow to the buyer, withholding the restocking
+/// fee for the seller when the goods were already delivered. Returns the fee.
+fn transfer_refund(env: &Env, contract: &RefundContract, refund_amount: u128) -> u128 {
+    let restocking_fee = if contract.delivered_at.is_some() {
+        refund_amount * contract.refund_policy.restocking_fee_bps as u128 / BASIS_POINTS
+    } else {
+        0
+    };
+
+    let contract_address = env.current_contract_address();
+    let token_client = token::Client::new(env, &contract.token);
+
+    token_client.transfer(
+        &contract_address,
+        &contract.buyer,
+        &((refund_amount - restocking_fee) as i128),
+    );
+    if restocking_fee > 0 {
+        token_client.transfer(&contract_address, &contract.seller, &(restocking_fee as i128));
+    }
+
+    restocking_fee
+}
+
 pub fn cancel_contract(
     env: &Env,
     contract_id: u64,
@@ -327,7 +564,7 @@ pub fn cancel_contract(
     }
 
     // Only allow cancellation before delivery is marked
-    if contract.status == ContractStatus::Delivered {
+    if contract.delivered_at.is_some() {
         return Err(ContractError::OperationNotAllowed);
     }
 
@@ -362,21 +599,98 @@ pub fn get_contract(env: &Env, contract_id: u64) -> Result<RefundContract, Contr
 pub fn get_user_contracts(
     env: &Env,
     user: &Address,
-    offset: u32,
+    filter: ContractFilter,
+    cursor: u32,
     limit: u32,
-) -> Result<Vec<u64>, ContractError> {
-    let all_contracts = crate::refund_storage::get_user_contracts(env, user);
-    let mut result = Vec::new(env);
+) -> Result<UserContractsPage, ContractError> {
+    let mut contract_ids = Vec::new(env);
+    let count = get_user_contract_count(env, user);
+
+    // Unfiltered listings read `limit` entries; filtered ones stop after
+    // loading MAX_FILTER_SCAN contracts even if fewer matched
+    let end = if filter == ContractFilter::All {
+        cursor.saturating_add(limit).min(count)
+    } else {
+        cursor.saturating_add(MAX_FILTER_SCAN).min(count)
+    };
 
-    let start = offset as usize;
-    let end = (offset + limit) as usize;
-    let contracts_len = all_contracts.len() as usize;
+    let mut position = cursor;
+    while position < end && contract_ids.len() < limit {
+        let contracts = get_user_contract_page(env, user, position / USER_CONTRACTS_PAGE_SIZE);
+        let mut slot = position % USER_CONTRACTS_PAGE_SIZE;
+        while slot < contracts.len() && position < end && contract_ids.len() < limit {
+            let contract_id = contracts.get(slot).unwrap();
+            let matches = match &filter {
+                ContractFilter::All => true,
+                ContractFilter::Status(status) => get_contract(env, contract_id)?.status == *status,
+                ContractFilter::AwaitingAction => {
+                    awaits_action(&get_contract(env, contract_id)?, user)
+                }
+            };
+            if matches {
+                contract_ids.push_back(contract_id);
+            }
+            slot += 1;
+            position += 1;
+        }
+    }
 
-    for i in start..end.min(contracts_len) {
-        if let Some(contract_id) = all_contracts.get(i as u32) {
-            result.push_back(contract_id);
+    Ok(UserContractsPage {
+        contract_ids,
+        next_cursor: if position < count {
+            Some(position)
+        } else {
+            None
+        },
+    })
+}
+
+pub fn migrate_user_contracts(
+    env: &Env,
+    admin: &Address,
+    user: &Address,
+    limit: u32,
+) -> Result<u32, ContractError> {
+    if !storage::is_admin(env, admin) {
+        return Err(ContractError::AdminOnly);
+    }
+
+    Ok(crate::refund_storage::migrate_user_contracts(env, user, limit))
+}
+
+// Whether the contract is blocked on the given user's next move
+fn awaits_action(contract: &RefundContract, user: &Address) -> bool {
+    if contract.buyer == *user {
+        match contract.status {
+            ContractStatus::Created | ContractStatus::Delivered => true,
+            ContractStatus::RefundRequested => awaiting_return(contract),
+            _ => false,
         }
+    } else if contract.seller == *user {
+        matches!(
+            contract.status,
+            ContractStatus::Funded | ContractStatus::ReturnShipped
+        )
+    } else {
+        false
     }
+}
 
-    Ok(result)
+pub fn remove_completed_contract(env: &Env, contract_id: u64) -> Result<(), ContractError> {
+    let contract = get_contract(env, contract_id)?;
+
+    if contract.status != ContractStatus::Completed
+        && contract.status != ContractStatus::Cancelled
+        && contract.status != ContractStatus::RefundProcessed
+    {
+        return Err(ContractError::OperationNotAllowed);
+    }
+
+    let removed_buyer = remove_user_contract(env, &contract.buyer, contract_id);
+    let removed_seller = remove_user_contract(env, &contract.seller, contract_id);
+    if !removed_buyer && !removed_seller {
+        return Err(ContractError::DataNotFound);
+    }
+
+    Ok(())
 }
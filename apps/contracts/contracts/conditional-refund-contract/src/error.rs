@@ -43,4 +43,18 @@ pub enum ContractError {
     DataNotFound = 26,
     OperationNotAllowed = 27,
     DeadlineInPast = 28,
+
+    // Condition errors
+    InvalidCondition = 29,
+    ConditionAlreadyAttested = 30,
+
+    // Refund policy errors
+    InvalidRefundPolicy = 31,
+    PartialRefundNotAllowed = 32,
+
+    // Return shipping errors
+    ReturnRequired = 33,
+    ReturnNotShipped = 34,
+    SellerResponseWindowPassed = 35,
+    ReturnDisputed = 36,
 }
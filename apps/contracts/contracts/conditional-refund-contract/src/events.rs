@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use soroban_sdk::{contracttype, Address, BytesN, Env, String};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -41,6 +41,7 @@ pub struct RefundRequestedEvent {
     pub contract_id: u64,
     pub requester: Address,
     pub reason: String,
+    pub triggered_condition: Option<u32>,
 }
 
 #[contracttype]
@@ -49,7 +50,41 @@ pub struct RefundProcessedEvent {
     pub contract_id: u64,
     pub recipient: Address,
     pub amount: u128,
+    pub restocking_fee: u128,
     pub automatic: bool,
+    pub triggered_condition: Option<u32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConditionAttestedEvent {
+    pub contract_id: u64,
+    pub oracle: Address,
+    pub claim: BytesN<32>,
+    pub condition_index: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReturnShippedEvent {
+    pub contract_id: u64,
+    pub buyer: Address,
+    pub tracking_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReturnReceivedEvent {
+    pub contract_id: u64,
+    pub seller: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReturnDisputedEvent {
+    pub contract_id: u64,
+    pub seller: Address,
+    pub reason: String,
 }
 
 #[contracttype]
@@ -59,6 +94,8 @@ pub struct RefundDisputeResolvedEvent {
     pub admin: Address,
     pub approved: bool,
     pub amount: u128,
+    pub restocking_fee: u128,
+    pub remaining_escrow: u128,
 }
 
 #[contracttype]
@@ -118,11 +155,18 @@ pub fn emit_delivery_confirmed(env: &Env, contract_id: u64, buyer: Address, amou
     env.events().publish(("delivery_confirmed",), event);
 }
 
-pub fn emit_refund_requested(env: &Env, contract_id: u64, requester: Address, reason: String) {
+pub fn emit_refund_requested(
+    env: &Env,
+    contract_id: u64,
+    requester: Address,
+    reason: String,
+    triggered_condition: Option<u32>,
+) {
     let event = RefundRequestedEvent {
         contract_id,
         requester,
         reason,
+        triggered_condition,
     };
     env.events().publish(("refund_requested",), event);
 }
@@ -132,29 +176,84 @@ pub fn emit_refund_processed(
     contract_id: u64,
     recipient: Address,
     amount: u128,
+    restocking_fee: u128,
     automatic: bool,
+    triggered_condition: Option<u32>,
 ) {
     let event = RefundProcessedEvent {
         contract_id,
         recipient,
         amount,
+        restocking_fee,
         automatic,
+        triggered_condition,
     };
     env.events().publish(("refund_processed",), event);
 }
 
+pub fn emit_condition_attested(
+    env: &Env,
+    contract_id: u64,
+    oracle: Address,
+    claim: BytesN<32>,
+    condition_index: u32,
+) {
+    let event = ConditionAttestedEvent {
+        contract_id,
+        oracle,
+        claim,
+        condition_index,
+    };
+    env.events().publish(("condition_attested",), event);
+}
+
+pub fn emit_return_shipped(
+    env: &Env,
+    contract_id: u64,
+    buyer: Address,
+    tracking_hash: BytesN<32>,
+) {
+    let event = ReturnShippedEvent {
+        contract_id,
+        buyer,
+        tracking_hash,
+    };
+    env.events().publish(("return_shipped",), event);
+}
+
+pub fn emit_return_received(env: &Env, contract_id: u64, seller: Address) {
+    let event = ReturnReceivedEvent {
+        contract_id,
+        seller,
+    };
+    env.events().publish(("return_received",), event);
+}
+
+pub fn emit_return_disputed(env: &Env, contract_id: u64, seller: Address, reason: String) {
+    let event = ReturnDisputedEvent {
+        contract_id,
+        seller,
+        reason,
+    };
+    env.events().publish(("return_disputed",), event);
+}
+
 pub fn emit_refund_dispute_resolved(
     env: &Env,
     contract_id: u64,
     admin: Address,
     approved: bool,
     amount: u128,
+    restocking_fee: u128,
+    remaining_escrow: u128,
 ) {
     let event = RefundDisputeResolvedEvent {
         contract_id,
         admin,
         approved,
         amount,
+        restocking_fee,
+        remaining_escrow,
     };
     env.events().publish(("refund_dispute_resolved",), event);
 }
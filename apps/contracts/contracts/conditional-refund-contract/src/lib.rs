@@ -1,5 +1,6 @@
 #![no_std]
 
+mod conditions;
 mod contract;
 mod error;
 mod events;
@@ -7,11 +8,14 @@ mod refund_storage;
 mod storage;
 mod test;
 
-use soroban_sdk::{contract, contractimpl, Address, Env, String};
+use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, String};
 
 pub use contract::*;
 pub use error::*;
 pub use events::*;
+pub use refund_storage::{
+    ContractFilter, RefundCondition, RefundPolicy, RefundTerms, UserContractsPage,
+};
 
 #[contract]
 pub struct ConditionalRefundContract;
@@ -28,28 +32,18 @@ impl ConditionalRefundContract {
         Ok(())
     }
 
-    /// Create a new refund contract
+    /// Create a new refund contract with machine-checkable refund conditions
+    /// and a return policy
     pub fn create_refund_contract(
         env: Env,
         buyer: Address,
         seller: Address,
         token: Address,
         amount: u128,
-        refund_deadline: u64,
-        delivery_deadline: u64,
-        refund_conditions: String,
+        terms: RefundTerms,
     ) -> Result<u64, ContractError> {
         buyer.require_auth();
-        contract::create_refund_contract(
-            &env,
-            &buyer,
-            &seller,
-            &token,
-            amount,
-            refund_deadline,
-            delivery_deadline,
-            refund_conditions,
-        )
+        contract::create_refund_contract(&env, &buyer, &seller, &token, amount, terms)
     }
 
     /// Fund the contract by locking tokens in escrow
@@ -78,31 +72,75 @@ impl ConditionalRefundContract {
         contract::confirm_delivery(&env, contract_id, &buyer)
     }
 
-    /// Request refund based on conditions
+    /// Request refund; returns the index of the refund condition that fired, if any
     pub fn request_refund(
         env: Env,
         contract_id: u64,
         requester: Address,
         reason: String,
-    ) -> Result<(), ContractError> {
+    ) -> Result<Option<u32>, ContractError> {
         requester.require_auth();
         contract::request_refund(&env, contract_id, &requester, reason)
     }
 
-    /// Process automatic refund if conditions are met
-    pub fn process_automatic_refund(env: Env, contract_id: u64) -> Result<(), ContractError> {
+    /// Record an oracle attestation for an OracleAttests refund condition
+    pub fn attest_condition(
+        env: Env,
+        contract_id: u64,
+        oracle: Address,
+        claim: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        oracle.require_auth();
+        contract::attest_condition(&env, contract_id, &oracle, claim)
+    }
+
+    /// Process automatic refund if a refund condition holds; returns its index
+    pub fn process_automatic_refund(env: Env, contract_id: u64) -> Result<u32, ContractError> {
         contract::process_automatic_refund(&env, contract_id)
     }
 
-    /// Resolve refund dispute (admin only)
+    /// Post the return tracking hash for goods shipped back (buyer action)
+    pub fn ship_return(
+        env: Env,
+        contract_id: u64,
+        buyer: Address,
+        tracking_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        buyer.require_auth();
+        contract::ship_return(&env, contract_id, &buyer, tracking_hash)
+    }
+
+    /// Confirm the returned goods arrived in order (seller action)
+    pub fn confirm_return_received(
+        env: Env,
+        contract_id: u64,
+        seller: Address,
+    ) -> Result<(), ContractError> {
+        seller.require_auth();
+        contract::confirm_return_received(&env, contract_id, &seller)
+    }
+
+    /// Dispute the condition of returned goods, escalating to admin (seller action)
+    pub fn dispute_return(
+        env: Env,
+        contract_id: u64,
+        seller: Address,
+        reason: String,
+    ) -> Result<(), ContractError> {
+        seller.require_auth();
+        contract::dispute_return(&env, contract_id, &seller, reason)
+    }
+
+    /// Resolve refund dispute (admin only). A zero `refund_amount` releases the
+    /// escrow to the seller; a partial amount keeps the rest escrowed.
     pub fn resolve_refund_dispute(
         env: Env,
         contract_id: u64,
         admin: Address,
-        approve_refund: bool,
+        refund_amount: u128,
     ) -> Result<(), ContractError> {
         admin.require_auth();
-        contract::resolve_refund_dispute(&env, contract_id, &admin, approve_refund)
+        contract::resolve_refund_dispute(&env, contract_id, &admin, refund_amount)
     }
 
     /// Cancel contract (before delivery)
@@ -123,13 +161,37 @@ impl ConditionalRefundContract {
         contract::get_contract(&env, contract_id)
     }
 
-    /// Get user contracts
+    /// Get up to `limit` of the user's active contracts matching the filter,
+    /// scanning the index from `cursor`. Pass `next_cursor` back to continue.
     pub fn get_user_contracts(
         env: Env,
         user: Address,
-        offset: u32,
+        filter: ContractFilter,
+        cursor: u32,
         limit: u32,
-    ) -> Result<soroban_sdk::Vec<u64>, ContractError> {
-        contract::get_user_contracts(&env, &user, offset, limit)
+    ) -> Result<UserContractsPage, ContractError> {
+        contract::get_user_contracts(&env, &user, filter, cursor, limit)
+    }
+
+    /// Move part of a user's pre-paging contract index into the paged layout
+    /// (admin only). Returns how many entries are left to move.
+    pub fn migrate_user_contracts(
+        env: Env,
+        admin: Address,
+        user: Address,
+        limit: u32,
+    ) -> Result<u32, ContractError> {
+        admin.require_auth();
+        contract::migrate_user_contracts(&env, &admin, &user, limit)
+    }
+
+    /// Get the number of contracts in the user's active index
+    pub fn get_user_contract_count(env: Env, user: Address) -> u32 {
+        refund_storage::get_user_contract_count(&env, &user)
+    }
+
+    /// Remove a finished contract from both participants' active indexes
+    pub fn remove_completed_contract(env: Env, contract_id: u64) -> Result<(), ContractError> {
+        contract::remove_completed_contract(&env, contract_id)
     }
 }
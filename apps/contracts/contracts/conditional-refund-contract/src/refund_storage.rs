@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -9,9 +9,47 @@ pub enum ContractStatus {
     Completed,
     Cancelled,
     RefundRequested,
+    ReturnShipped,
+    ReturnReceived,
     RefundProcessed,
 }
 
+// Machine-checkable refund clauses; the refund is due as soon as any one holds
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RefundCondition {
+    // Seller has not marked delivery by the given timestamp
+    NotDeliveredBy(u64),
+    // Buyer requests a refund within the given number of seconds after delivery
+    RejectedWithin(u64),
+    // The given oracle attests the claim identified by the hash
+    OracleAttests(Address, BytesN<32>),
+}
+
+// Merchant return policy applied whenever funds go back to the buyer
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundPolicy {
+    // Share of a post-delivery refund kept by the seller, in basis points
+    pub restocking_fee_bps: u32,
+    // Whether disputes may be settled by refunding only part of the escrow
+    pub allow_partial_refunds: bool,
+    // Whether delivered goods must be shipped back before a refund releases
+    pub requires_return: bool,
+    // Seconds the seller has to confirm or dispute a shipped return
+    pub seller_response_window: u64,
+}
+
+// Deadlines, refund conditions and return policy agreed at creation
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundTerms {
+    pub refund_deadline: u64,
+    pub delivery_deadline: u64,
+    pub refund_conditions: Vec<RefundCondition>,
+    pub refund_policy: RefundPolicy,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RefundContract {
@@ -21,10 +59,14 @@ pub struct RefundContract {
     pub token: Address,
     pub amount: u128,
     pub escrowed_amount: u128,
+    pub refunded_amount: u128,
+    pub refund_policy: RefundPolicy,
     pub status: ContractStatus,
     pub refund_deadline: u64,
     pub delivery_deadline: u64,
-    pub refund_conditions: String,
+    pub refund_conditions: Vec<RefundCondition>,
+    pub attested_conditions: Vec<u32>,
+    pub triggered_condition: Option<u32>,
     pub created_at: u64,
     pub funded_at: Option<u64>,
     pub delivered_at: Option<u64>,
@@ -34,6 +76,11 @@ pub struct RefundContract {
     pub refund_processed_at: Option<u64>,
     pub refund_reason: Option<String>,
     pub refund_requester: Option<Address>,
+    pub return_tracking_hash: Option<BytesN<32>>,
+    pub return_shipped_at: Option<u64>,
+    pub return_received_at: Option<u64>,
+    // Seller objected to the returned goods; only the admin can refund now
+    pub return_disputed: bool,
 }
 
 // Storage key types
@@ -41,13 +88,43 @@ pub struct RefundContract {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum StorageKey {
     Contract(u64),
-    UserContracts(Address),
     ContractCounter,
+    // Active contract index per user: a dense list split into fixed-size pages
+    UserContractCount(Address),
+    UserContractPage(Address, u32),
+    UserContractPosition(Address, u64),
+    // Unpaged index from before the paged layout; only read by the migration
+    UserContracts(Address),
+}
+
+// Filters for listing a user's active contracts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ContractFilter {
+    All,
+    Status(ContractStatus),
+    // Contracts waiting on the queried user to take the next step
+    AwaitingAction,
+}
+
+// One page of a user's contract listing, with where to resume scanning
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UserContractsPage {
+    pub contract_ids: Vec<u64>,
+    // Index position to pass as the next cursor; None once the index is exhausted
+    pub next_cursor: Option<u32>,
 }
 
 // Storage key constants
 const CONTRACT_COUNTER: Symbol = symbol_short!("CTR_CNT");
 
+// Number of contract IDs kept in a single user index page
+pub const USER_CONTRACTS_PAGE_SIZE: u32 = 20;
+
+// Most index entries a filtered listing loads in one call
+pub const MAX_FILTER_SCAN: u32 = 2 * USER_CONTRACTS_PAGE_SIZE;
+
 // Contract ID generation
 pub fn get_next_contract_id(env: &Env) -> u64 {
     let current = env
@@ -72,17 +149,109 @@ pub fn set_contract(env: &Env, contract: &RefundContract) {
 }
 
 // User contracts storage functions
-pub fn get_user_contracts(env: &Env, user: &Address) -> Vec<u64> {
-    let key = StorageKey::UserContracts(user.clone());
+pub fn get_user_contract_count(env: &Env, user: &Address) -> u32 {
+    let key = StorageKey::UserContractCount(user.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+fn set_user_contract_count(env: &Env, user: &Address, count: u32) {
+    let key = StorageKey::UserContractCount(user.clone());
+    env.storage().persistent().set(&key, &count);
+}
+
+pub fn get_user_contract_page(env: &Env, user: &Address, page: u32) -> Vec<u64> {
+    let key = StorageKey::UserContractPage(user.clone(), page);
     env.storage()
         .persistent()
         .get(&key)
         .unwrap_or(Vec::new(env))
 }
 
+fn set_user_contract_page(env: &Env, user: &Address, page: u32, contracts: &Vec<u64>) {
+    let key = StorageKey::UserContractPage(user.clone(), page);
+    if contracts.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, contracts);
+    }
+}
+
+fn set_user_contract_position(env: &Env, user: &Address, contract_id: u64, position: u32) {
+    let key = StorageKey::UserContractPosition(user.clone(), contract_id);
+    env.storage().persistent().set(&key, &position);
+}
+
 pub fn add_user_contract(env: &Env, user: &Address, contract_id: u64) {
-    let key = StorageKey::UserContracts(user.clone());
-    let mut contracts = get_user_contracts(env, user);
+    let position = get_user_contract_count(env, user);
+    let page = position / USER_CONTRACTS_PAGE_SIZE;
+
+    let mut contracts = get_user_contract_page(env, user, page);
     contracts.push_back(contract_id);
-    env.storage().persistent().set(&key, &contracts);
+    set_user_contract_page(env, user, page, &contracts);
+
+    set_user_contract_position(env, user, contract_id, position);
+    set_user_contract_count(env, user, position + 1);
+}
+
+// Swap-removes the contract from the user's index; returns false if it was not indexed
+pub fn remove_user_contract(env: &Env, user: &Address, contract_id: u64) -> bool {
+    let position_key = StorageKey::UserContractPosition(user.clone(), contract_id);
+    let position: u32 = match env.storage().persistent().get(&position_key) {
+        Some(position) => position,
+        None => return false,
+    };
+
+    let last_position = get_user_contract_count(env, user) - 1;
+    let last_page = last_position / USER_CONTRACTS_PAGE_SIZE;
+    let mut last_contracts = get_user_contract_page(env, user, last_page);
+    let last_contract_id = last_contracts.pop_back().unwrap();
+
+    if position != last_position {
+        // Move the last entry into the freed slot
+        let page = position / USER_CONTRACTS_PAGE_SIZE;
+        let slot = position % USER_CONTRACTS_PAGE_SIZE;
+        if page == last_page {
+            last_contracts.set(slot, last_contract_id);
+        } else {
+            let mut contracts = get_user_contract_page(env, user, page);
+            contracts.set(slot, last_contract_id);
+            set_user_contract_page(env, user, page, &contracts);
+        }
+        set_user_contract_position(env, user, last_contract_id, position);
+    }
+
+    set_user_contract_page(env, user, last_page, &last_contracts);
+    env.storage().persistent().remove(&position_key);
+    set_user_contract_count(env, user, last_position);
+    true
+}
+
+// Moves up to `limit` entries of the user's legacy unpaged index into the
+// paged layout, oldest first. Returns how many entries are left to move.
+pub fn migrate_user_contracts(env: &Env, user: &Address, limit: u32) -> u32 {
+    let key = StorageKey::UserContracts(user.clone());
+    let mut legacy: Vec<u64> = match env.storage().persistent().get(&key) {
+        Some(legacy) => legacy,
+        None => return 0,
+    };
+
+    let mut moved = 0;
+    while moved < limit {
+        let contract_id = match legacy.pop_front() {
+            Some(contract_id) => contract_id,
+            None => break,
+        };
+        let position_key = StorageKey::UserContractPosition(user.clone(), contract_id);
+        if !env.storage().persistent().has(&position_key) {
+            add_user_contract(env, user, contract_id);
+        }
+        moved += 1;
+    }
+
+    if legacy.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &legacy);
+    }
+    legacy.len()
 }
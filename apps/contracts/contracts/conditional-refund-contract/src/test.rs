@@ -2,15 +2,22 @@
 extern crate std;
 
 use crate::error::ContractError;
-use crate::refund_storage::ContractStatus;
+use crate::refund_storage::{
+    ContractFilter, ContractStatus, RefundCondition, RefundPolicy, RefundTerms, StorageKey,
+    MAX_FILTER_SCAN, USER_CONTRACTS_PAGE_SIZE,
+};
 use crate::{ConditionalRefundContract, ConditionalRefundContractClient};
-use soroban_sdk::{testutils::Address as _, testutils::Ledger, token, Address, Env, String};
+use soroban_sdk::{
+    testutils::Address as _, testutils::Ledger, token, vec, Address, BytesN, Env, String,
+};
 use token::Client as TokenClient;
 use token::StellarAssetClient as TokenAdminClient;
 
 const REFUND_AMOUNT: u128 = 1000;
 const REFUND_DEADLINE: u64 = 1000;
 const DELIVERY_DEADLINE: u64 = 800;
+const REJECTION_WINDOW: u64 = 100;
+const SELLER_RESPONSE_WINDOW: u64 = 200;
 
 fn create_token_contract<'a>(
     env: &Env,
@@ -35,7 +42,7 @@ struct RefundTest<'a> {
     seller: Address,
     token: TokenClient<'a>,
     contract: ConditionalRefundContractClient<'a>,
-    refund_conditions: String,
+    terms: RefundTerms,
 }
 
 impl<'a> RefundTest<'a> {
@@ -54,7 +61,17 @@ impl<'a> RefundTest<'a> {
         let contract = create_refund_contract(&env);
         let _ = contract.initialize(&admin);
 
-        let refund_conditions = String::from_str(&env, "Defective product or not as described");
+        let refund_conditions = vec![
+            &env,
+            RefundCondition::NotDeliveredBy(DELIVERY_DEADLINE),
+            RefundCondition::RejectedWithin(REJECTION_WINDOW),
+        ];
+        let refund_policy = RefundPolicy {
+            restocking_fee_bps: 0,
+            allow_partial_refunds: true,
+            requires_return: false,
+            seller_response_window: 0,
+        };
 
         RefundTest {
             env,
@@ -63,7 +80,12 @@ impl<'a> RefundTest<'a> {
             seller,
             token,
             contract,
-            refund_conditions,
+            terms: RefundTerms {
+                refund_deadline: REFUND_DEADLINE,
+                delivery_deadline: DELIVERY_DEADLINE,
+                refund_conditions,
+                refund_policy,
+            },
         }
     }
 
@@ -73,9 +95,20 @@ impl<'a> RefundTest<'a> {
             &self.seller,
             &self.token.address,
             &REFUND_AMOUNT,
-            &REFUND_DEADLINE,
-            &DELIVERY_DEADLINE,
-            &self.refund_conditions,
+            &self.terms,
+        )
+    }
+
+    fn create_refund_contract_with_policy(&self, refund_policy: &RefundPolicy) -> u64 {
+        self.contract.create_refund_contract(
+            &self.buyer,
+            &self.seller,
+            &self.token.address,
+            &REFUND_AMOUNT,
+            &RefundTerms {
+                refund_policy: refund_policy.clone(),
+                ..self.terms.clone()
+            },
         )
     }
 
@@ -89,6 +122,27 @@ impl<'a> RefundTest<'a> {
         test.fund_contract(contract_id);
         (test, contract_id)
     }
+
+    // Delivered contract under a return policy, with a rejection that fired
+    fn setup_with_return_requested() -> (Self, u64) {
+        let test = Self::setup();
+        let policy = RefundPolicy {
+            restocking_fee_bps: 0,
+            allow_partial_refunds: true,
+            requires_return: true,
+            seller_response_window: SELLER_RESPONSE_WINDOW,
+        };
+        let contract_id = test.create_refund_contract_with_policy(&policy);
+        test.fund_contract(contract_id);
+        test.contract.mark_delivered(&contract_id, &test.seller);
+
+        let refund_reason = String::from_str(&test.env, "Wrong size");
+        let triggered = test
+            .contract
+            .request_refund(&contract_id, &test.buyer, &refund_reason);
+        assert_eq!(triggered, Some(1));
+        (test, contract_id)
+    }
 }
 
 // Initialization tests
@@ -133,9 +187,7 @@ fn test_create_refund_contract_zero_amount() {
         &test.seller,
         &test.token.address,
         &0,
-        &REFUND_DEADLINE,
-        &DELIVERY_DEADLINE,
-        &test.refund_conditions,
+        &test.terms,
     );
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAmount));
@@ -149,9 +201,7 @@ fn test_create_refund_contract_same_buyer_seller() {
         &test.buyer,
         &test.token.address,
         &REFUND_AMOUNT,
-        &REFUND_DEADLINE,
-        &DELIVERY_DEADLINE,
-        &test.refund_conditions,
+        &test.terms,
     );
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidInput));
@@ -167,9 +217,10 @@ fn test_create_refund_contract_deadline_in_past() {
         &test.seller,
         &test.token.address,
         &REFUND_AMOUNT,
-        &past_deadline,
-        &DELIVERY_DEADLINE,
-        &test.refund_conditions,
+        &RefundTerms {
+            refund_deadline: past_deadline,
+            ..test.terms.clone()
+        },
     );
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::DeadlineInPast));
@@ -416,7 +467,8 @@ fn test_process_automatic_refund_delivery_deadline_passed() {
 
     let buyer_balance_before = test.token.balance(&test.buyer);
 
-    test.contract.process_automatic_refund(&contract_id);
+    let fired = test.contract.process_automatic_refund(&contract_id);
+    assert_eq!(fired, 0);
 
     assert_eq!(
         test.token.balance(&test.buyer),
@@ -426,19 +478,25 @@ fn test_process_automatic_refund_delivery_deadline_passed() {
     let contract_data = test.contract.get_contract(&contract_id);
     assert_eq!(contract_data.status, ContractStatus::RefundProcessed);
     assert_eq!(contract_data.escrowed_amount, 0);
+    assert_eq!(contract_data.triggered_condition, Some(0));
 }
 
 #[test]
 fn test_process_automatic_refund_buyer_requested() {
     let (test, contract_id) = RefundTest::setup_with_funded_contract();
 
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
     let refund_reason = String::from_str(&test.env, "Product defective");
-    test.contract
+    let triggered = test
+        .contract
         .request_refund(&contract_id, &test.buyer, &refund_reason);
+    assert_eq!(triggered, Some(1));
 
     let buyer_balance_before = test.token.balance(&test.buyer);
 
-    test.contract.process_automatic_refund(&contract_id);
+    let fired = test.contract.process_automatic_refund(&contract_id);
+    assert_eq!(fired, 1);
 
     assert_eq!(
         test.token.balance(&test.buyer),
@@ -476,6 +534,179 @@ fn test_process_automatic_refund_already_processed() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_request_refund_without_condition_goes_to_dispute() {
+    let (test, contract_id) = RefundTest::setup_with_funded_contract();
+
+    // Not delivered yet and delivery deadline not reached: no clause holds
+    let refund_reason = String::from_str(&test.env, "Changed my mind");
+    let triggered = test
+        .contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    assert_eq!(triggered, None);
+
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::RefundConditionsNotMet)
+    );
+
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::RefundRequested);
+    assert_eq!(contract_data.triggered_condition, None);
+}
+
+#[test]
+fn test_rejection_after_window_does_not_fire() {
+    let (test, contract_id) = RefundTest::setup_with_funded_contract();
+
+    test.contract.mark_delivered(&contract_id, &test.seller);
+    test.env.ledger().with_mut(|li| {
+        li.timestamp += REJECTION_WINDOW + 1;
+    });
+
+    let refund_reason = String::from_str(&test.env, "Late rejection");
+    let triggered = test
+        .contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    assert_eq!(triggered, None);
+
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::RefundConditionsNotMet)
+    );
+}
+
+#[test]
+fn test_rejection_window_does_not_overflow() {
+    let mut test = RefundTest::setup();
+    test.terms.refund_conditions = vec![&test.env, RefundCondition::RejectedWithin(u64::MAX)];
+    let contract_id = test.create_refund_contract();
+    test.fund_contract(contract_id);
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
+    let refund_reason = String::from_str(&test.env, "Wrong colour");
+    let triggered = test
+        .contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    assert_eq!(triggered, Some(0));
+}
+
+#[test]
+fn test_seller_request_does_not_fire_rejection() {
+    let (test, contract_id) = RefundTest::setup_with_funded_contract();
+
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
+    let refund_reason = String::from_str(&test.env, "Seller offers refund");
+    let triggered = test
+        .contract
+        .request_refund(&contract_id, &test.seller, &refund_reason);
+    assert_eq!(triggered, None);
+}
+
+#[test]
+fn test_oracle_attestation_fires_condition() {
+    let test = RefundTest::setup();
+    let oracle = Address::generate(&test.env);
+    let claim = BytesN::from_array(&test.env, &[7u8; 32]);
+
+    let conditions = vec![
+        &test.env,
+        RefundCondition::NotDeliveredBy(DELIVERY_DEADLINE),
+        RefundCondition::OracleAttests(oracle.clone(), claim.clone()),
+    ];
+    let contract_id = test.contract.create_refund_contract(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &REFUND_AMOUNT,
+        &RefundTerms {
+            refund_conditions: conditions,
+            ..test.terms.clone()
+        },
+    );
+    test.fund_contract(contract_id);
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::RefundConditionsNotMet)
+    );
+
+    test.contract.attest_condition(&contract_id, &oracle, &claim);
+
+    let buyer_balance_before = test.token.balance(&test.buyer);
+    let fired = test.contract.process_automatic_refund(&contract_id);
+    assert_eq!(fired, 1);
+    assert_eq!(
+        test.token.balance(&test.buyer),
+        buyer_balance_before + REFUND_AMOUNT as i128
+    );
+}
+
+#[test]
+fn test_attest_condition_wrong_oracle_or_claim() {
+    let test = RefundTest::setup();
+    let oracle = Address::generate(&test.env);
+    let impostor = Address::generate(&test.env);
+    let claim = BytesN::from_array(&test.env, &[7u8; 32]);
+    let other_claim = BytesN::from_array(&test.env, &[8u8; 32]);
+
+    let conditions = vec![
+        &test.env,
+        RefundCondition::OracleAttests(oracle.clone(), claim.clone()),
+    ];
+    let contract_id = test.contract.create_refund_contract(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &REFUND_AMOUNT,
+        &RefundTerms {
+            refund_conditions: conditions,
+            ..test.terms.clone()
+        },
+    );
+
+    let result = test
+        .contract
+        .try_attest_condition(&contract_id, &impostor, &claim);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::Unauthorized));
+
+    let result = test
+        .contract
+        .try_attest_condition(&contract_id, &oracle, &other_claim);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::Unauthorized));
+
+    test.contract.attest_condition(&contract_id, &oracle, &claim);
+    let result = test
+        .contract
+        .try_attest_condition(&contract_id, &oracle, &claim);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::ConditionAlreadyAttested)
+    );
+}
+
+#[test]
+fn test_create_refund_contract_invalid_condition() {
+    let test = RefundTest::setup();
+    let conditions = vec![&test.env, RefundCondition::RejectedWithin(0)];
+    let result = test.contract.try_create_refund_contract(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &REFUND_AMOUNT,
+        &RefundTerms {
+            refund_conditions: conditions,
+            ..test.terms.clone()
+        },
+    );
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidCondition));
+}
+
 // Dispute resolution tests
 #[test]
 fn test_resolve_refund_dispute_approve() {
@@ -488,7 +719,7 @@ fn test_resolve_refund_dispute_approve() {
     let buyer_balance_before = test.token.balance(&test.buyer);
 
     test.contract
-        .resolve_refund_dispute(&contract_id, &test.admin, &true);
+        .resolve_refund_dispute(&contract_id, &test.admin, &REFUND_AMOUNT);
 
     assert_eq!(
         test.token.balance(&test.buyer),
@@ -510,7 +741,7 @@ fn test_resolve_refund_dispute_reject() {
     let seller_balance_before = test.token.balance(&test.seller);
 
     test.contract
-        .resolve_refund_dispute(&contract_id, &test.admin, &false);
+        .resolve_refund_dispute(&contract_id, &test.admin, &0);
 
     assert_eq!(
         test.token.balance(&test.seller),
@@ -531,7 +762,7 @@ fn test_resolve_refund_dispute_non_admin() {
 
     let result = test
         .contract
-        .try_resolve_refund_dispute(&contract_id, &test.buyer, &true);
+        .try_resolve_refund_dispute(&contract_id, &test.buyer, &REFUND_AMOUNT);
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::AdminOnly));
 }
@@ -542,11 +773,397 @@ fn test_resolve_refund_dispute_not_requested() {
 
     let result = test
         .contract
-        .try_resolve_refund_dispute(&contract_id, &test.admin, &true);
+        .try_resolve_refund_dispute(&contract_id, &test.admin, &REFUND_AMOUNT);
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::RefundNotRequested));
 }
 
+#[test]
+fn test_resolve_refund_dispute_with_restocking_fee() {
+    let test = RefundTest::setup();
+    let policy = RefundPolicy {
+        restocking_fee_bps: 1_500, // 15%
+        allow_partial_refunds: true,
+        requires_return: false,
+        seller_response_window: 0,
+    };
+    let contract_id = test.create_refund_contract_with_policy(&policy);
+    test.fund_contract(contract_id);
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
+    let refund_reason = String::from_str(&test.env, "Returned unopened");
+    test.contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    test.contract
+        .resolve_refund_dispute(&contract_id, &test.admin, &REFUND_AMOUNT);
+
+    assert_eq!(test.token.balance(&test.buyer), 850);
+    assert_eq!(test.token.balance(&test.seller), 150);
+
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::RefundProcessed);
+    assert_eq!(contract_data.escrowed_amount, 0);
+    assert_eq!(contract_data.refunded_amount, 850);
+}
+
+#[test]
+fn test_no_restocking_fee_before_delivery() {
+    let test = RefundTest::setup();
+    let policy = RefundPolicy {
+        restocking_fee_bps: 1_500,
+        allow_partial_refunds: true,
+        requires_return: false,
+        seller_response_window: 0,
+    };
+    let contract_id = test.create_refund_contract_with_policy(&policy);
+    test.fund_contract(contract_id);
+
+    test.env.ledger().with_mut(|li| {
+        li.timestamp = DELIVERY_DEADLINE + 100;
+    });
+    test.contract.process_automatic_refund(&contract_id);
+
+    assert_eq!(test.token.balance(&test.buyer), REFUND_AMOUNT as i128);
+    assert_eq!(test.token.balance(&test.seller), 0);
+}
+
+#[test]
+fn test_multiple_partial_refunds() {
+    let (test, contract_id) = RefundTest::setup_with_funded_contract();
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
+    // First partial refund: one item of the order was missing
+    let refund_reason = String::from_str(&test.env, "Item missing");
+    test.contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    test.contract
+        .resolve_refund_dispute(&contract_id, &test.admin, &300);
+
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::Delivered);
+    assert_eq!(contract_data.escrowed_amount, 700);
+    assert_eq!(contract_data.refunded_amount, 300);
+
+    // Second partial refund: another item arrived damaged
+    let refund_reason = String::from_str(&test.env, "Item damaged");
+    test.contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    test.contract
+        .resolve_refund_dispute(&contract_id, &test.admin, &200);
+
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.escrowed_amount, 500);
+    assert_eq!(contract_data.refunded_amount, 500);
+
+    // Buyer accepts the rest of the order
+    test.contract.confirm_delivery(&contract_id, &test.buyer);
+
+    assert_eq!(test.token.balance(&test.buyer), 500);
+    assert_eq!(test.token.balance(&test.seller), 500);
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::Completed);
+    assert_eq!(contract_data.escrowed_amount, 0);
+}
+
+#[test]
+fn test_partial_refund_not_allowed_by_policy() {
+    let test = RefundTest::setup();
+    let policy = RefundPolicy {
+        restocking_fee_bps: 0,
+        allow_partial_refunds: false,
+        requires_return: false,
+        seller_response_window: 0,
+    };
+    let contract_id = test.create_refund_contract_with_policy(&policy);
+    test.fund_contract(contract_id);
+
+    let refund_reason = String::from_str(&test.env, "Partial issue");
+    test.contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+
+    let result = test
+        .contract
+        .try_resolve_refund_dispute(&contract_id, &test.admin, &300);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::PartialRefundNotAllowed)
+    );
+}
+
+#[test]
+fn test_resolve_refund_dispute_amount_exceeds_escrow() {
+    let (test, contract_id) = RefundTest::setup_with_funded_contract();
+
+    let refund_reason = String::from_str(&test.env, "Dispute reason");
+    test.contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+
+    let result = test
+        .contract
+        .try_resolve_refund_dispute(&contract_id, &test.admin, &(REFUND_AMOUNT + 1));
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAmount));
+}
+
+#[test]
+fn test_create_refund_contract_invalid_policy() {
+    let test = RefundTest::setup();
+    let policy = RefundPolicy {
+        restocking_fee_bps: 10_001,
+        allow_partial_refunds: true,
+        requires_return: false,
+        seller_response_window: 0,
+    };
+    let result = test.contract.try_create_refund_contract(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &REFUND_AMOUNT,
+        &RefundTerms {
+            refund_policy: policy,
+            ..test.terms.clone()
+        },
+    );
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidRefundPolicy));
+}
+
+// Return shipping tests
+#[test]
+fn test_refund_requires_return_first() {
+    let (test, contract_id) = RefundTest::setup_with_return_requested();
+
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ReturnRequired));
+}
+
+#[test]
+fn test_return_confirmed_by_seller_releases_refund() {
+    let (test, contract_id) = RefundTest::setup_with_return_requested();
+    let tracking_hash = BytesN::from_array(&test.env, &[1u8; 32]);
+
+    test.contract
+        .ship_return(&contract_id, &test.buyer, &tracking_hash);
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::ReturnShipped);
+    assert_eq!(contract_data.return_tracking_hash, Some(tracking_hash));
+
+    // Still in transit: nothing releases yet
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::RefundConditionsNotMet)
+    );
+
+    test.contract
+        .confirm_return_received(&contract_id, &test.seller);
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::ReturnReceived);
+    assert!(contract_data.return_received_at.is_some());
+
+    let fired = test.contract.process_automatic_refund(&contract_id);
+    assert_eq!(fired, 1);
+    assert_eq!(test.token.balance(&test.buyer), REFUND_AMOUNT as i128);
+
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::RefundProcessed);
+}
+
+#[test]
+fn test_return_refund_after_seller_silence() {
+    let (test, contract_id) = RefundTest::setup_with_return_requested();
+    let tracking_hash = BytesN::from_array(&test.env, &[1u8; 32]);
+    test.contract
+        .ship_return(&contract_id, &test.buyer, &tracking_hash);
+
+    test.env.ledger().with_mut(|li| {
+        li.timestamp += SELLER_RESPONSE_WINDOW + 1;
+    });
+
+    // Seller can no longer object once the window has passed
+    let reason = String::from_str(&test.env, "Too late");
+    let result = test
+        .contract
+        .try_dispute_return(&contract_id, &test.seller, &reason);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::SellerResponseWindowPassed)
+    );
+
+    test.contract.process_automatic_refund(&contract_id);
+    assert_eq!(test.token.balance(&test.buyer), REFUND_AMOUNT as i128);
+}
+
+#[test]
+fn test_seller_disputes_returned_goods() {
+    let (test, contract_id) = RefundTest::setup_with_return_requested();
+    let tracking_hash = BytesN::from_array(&test.env, &[1u8; 32]);
+    test.contract
+        .ship_return(&contract_id, &test.buyer, &tracking_hash);
+
+    let reason = String::from_str(&test.env, "Returned item damaged");
+    test.contract
+        .dispute_return(&contract_id, &test.seller, &reason);
+
+    let contract_data = test.contract.get_contract(&contract_id);
+    assert_eq!(contract_data.status, ContractStatus::RefundRequested);
+    assert_eq!(contract_data.refund_requester, Some(test.seller.clone()));
+    assert_eq!(contract_data.refund_reason, Some(reason));
+    assert!(contract_data.return_disputed);
+
+    // The rejection clause still holds, but the dispute overrides it
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ReturnDisputed));
+
+    // Silence timeout no longer applies; admin settles the amount
+    test.env.ledger().with_mut(|li| {
+        li.timestamp += SELLER_RESPONSE_WINDOW + 1;
+    });
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ReturnDisputed));
+
+    test.contract
+        .resolve_refund_dispute(&contract_id, &test.admin, &600);
+    assert_eq!(test.token.balance(&test.buyer), 600);
+
+    // The remainder stays with the admin even after a partial refund
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ReturnDisputed));
+}
+
+#[test]
+fn test_admin_refund_waits_for_return() {
+    let (test, contract_id) = RefundTest::setup_with_return_requested();
+    let tracking_hash = BytesN::from_array(&test.env, &[1u8; 32]);
+
+    let result = test
+        .contract
+        .try_resolve_refund_dispute(&contract_id, &test.admin, &REFUND_AMOUNT);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ReturnRequired));
+
+    test.contract
+        .ship_return(&contract_id, &test.buyer, &tracking_hash);
+    let result = test
+        .contract
+        .try_resolve_refund_dispute(&contract_id, &test.admin, &REFUND_AMOUNT);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ReturnRequired));
+
+    test.contract
+        .confirm_return_received(&contract_id, &test.seller);
+    test.contract
+        .resolve_refund_dispute(&contract_id, &test.admin, &REFUND_AMOUNT);
+    assert_eq!(test.token.balance(&test.buyer), REFUND_AMOUNT as i128);
+}
+
+#[test]
+fn test_unconditional_request_ships_return_before_admin_refund() {
+    let test = RefundTest::setup();
+    let policy = RefundPolicy {
+        restocking_fee_bps: 0,
+        allow_partial_refunds: true,
+        requires_return: true,
+        seller_response_window: SELLER_RESPONSE_WINDOW,
+    };
+    let contract_id = test.create_refund_contract_with_policy(&policy);
+    test.fund_contract(contract_id);
+    test.contract.mark_delivered(&contract_id, &test.seller);
+    test.env.ledger().with_mut(|li| {
+        li.timestamp += REJECTION_WINDOW + 1;
+    });
+
+    let refund_reason = String::from_str(&test.env, "Changed my mind");
+    let triggered = test
+        .contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    assert_eq!(triggered, None);
+
+    let tracking_hash = BytesN::from_array(&test.env, &[2u8; 32]);
+    test.contract
+        .ship_return(&contract_id, &test.buyer, &tracking_hash);
+    test.env.ledger().with_mut(|li| {
+        li.timestamp += SELLER_RESPONSE_WINDOW + 1;
+    });
+
+    // No clause fired, so the admin settles once the seller stays silent
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::RefundConditionsNotMet)
+    );
+    test.contract
+        .resolve_refund_dispute(&contract_id, &test.admin, &500);
+    assert_eq!(test.token.balance(&test.buyer), 500);
+}
+
+#[test]
+fn test_huge_seller_response_window_does_not_overflow() {
+    let test = RefundTest::setup();
+    let policy = RefundPolicy {
+        restocking_fee_bps: 0,
+        allow_partial_refunds: true,
+        requires_return: true,
+        seller_response_window: u64::MAX,
+    };
+    let contract_id = test.create_refund_contract_with_policy(&policy);
+    test.fund_contract(contract_id);
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
+    let refund_reason = String::from_str(&test.env, "Wrong size");
+    test.contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+    let tracking_hash = BytesN::from_array(&test.env, &[1u8; 32]);
+    test.contract
+        .ship_return(&contract_id, &test.buyer, &tracking_hash);
+
+    let result = test.contract.try_process_automatic_refund(&contract_id);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::RefundConditionsNotMet)
+    );
+}
+
+#[test]
+fn test_return_actions_authorization() {
+    let (test, contract_id) = RefundTest::setup_with_return_requested();
+    let tracking_hash = BytesN::from_array(&test.env, &[1u8; 32]);
+
+    let result = test
+        .contract
+        .try_confirm_return_received(&contract_id, &test.seller);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ReturnNotShipped));
+
+    let result = test
+        .contract
+        .try_ship_return(&contract_id, &test.seller, &tracking_hash);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::BuyerOnly));
+
+    test.contract
+        .ship_return(&contract_id, &test.buyer, &tracking_hash);
+
+    let result = test
+        .contract
+        .try_confirm_return_received(&contract_id, &test.buyer);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::SellerOnly));
+
+    // Buyer cannot skip the return leg by cancelling
+    let result = test.contract.try_cancel_contract(&contract_id, &test.buyer);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::OperationNotAllowed));
+}
+
+#[test]
+fn test_ship_return_not_needed_without_policy() {
+    let (test, contract_id) = RefundTest::setup_with_funded_contract();
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
+    let refund_reason = String::from_str(&test.env, "Wrong size");
+    test.contract
+        .request_refund(&contract_id, &test.buyer, &refund_reason);
+
+    let tracking_hash = BytesN::from_array(&test.env, &[1u8; 32]);
+    let result = test
+        .contract
+        .try_ship_return(&contract_id, &test.buyer, &tracking_hash);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::OperationNotAllowed));
+}
+
 // Contract cancellation tests
 #[test]
 fn test_cancel_contract_success() {
@@ -604,6 +1221,8 @@ fn test_cancel_contract_after_delivery_marked() {
 fn test_multiple_refund_attempts() {
     let (test, contract_id) = RefundTest::setup_with_funded_contract();
 
+    test.contract.mark_delivered(&contract_id, &test.seller);
+
     let refund_reason = String::from_str(&test.env, "First refund");
     test.contract
         .request_refund(&contract_id, &test.buyer, &refund_reason);
@@ -672,13 +1291,228 @@ fn test_get_user_contracts_pagination() {
     }
 
     // Test pagination
-    let first_page = test.contract.get_user_contracts(&test.buyer, &0, &3);
+    let first_page = test
+        .contract
+        .get_user_contracts(&test.buyer, &ContractFilter::All, &0, &3)
+        .contract_ids;
     assert_eq!(first_page.len(), 3);
 
-    let second_page = test.contract.get_user_contracts(&test.buyer, &3, &3);
+    let second_page = test
+        .contract
+        .get_user_contracts(&test.buyer, &ContractFilter::All, &3, &3)
+        .contract_ids;
     assert_eq!(second_page.len(), 2);
 }
 
+#[test]
+fn test_get_user_contracts_across_pages() {
+    let test = RefundTest::setup();
+
+    let total = USER_CONTRACTS_PAGE_SIZE + 5;
+    for _ in 0..total {
+        test.create_refund_contract();
+    }
+    assert_eq!(test.contract.get_user_contract_count(&test.buyer), total);
+
+    // A window straddling the page boundary
+    let window = test
+        .contract
+        .get_user_contracts(
+            &test.buyer,
+            &ContractFilter::All,
+            &(USER_CONTRACTS_PAGE_SIZE - 2),
+            &4,
+        )
+        .contract_ids;
+    let start = USER_CONTRACTS_PAGE_SIZE as u64 - 1;
+    assert_eq!(
+        window,
+        vec![&test.env, start, start + 1, start + 2, start + 3]
+    );
+
+    let tail = test
+        .contract
+        .get_user_contracts(&test.buyer, &ContractFilter::All, &total, &10)
+        .contract_ids;
+    assert_eq!(tail.len(), 0);
+}
+
+#[test]
+fn test_get_user_contracts_status_filter() {
+    let test = RefundTest::setup();
+    let created_id = test.create_refund_contract();
+    let funded_id = test.create_refund_contract();
+    test.fund_contract(funded_id);
+
+    let funded = test
+        .contract
+        .get_user_contracts(
+            &test.buyer,
+            &ContractFilter::Status(ContractStatus::Funded),
+            &0,
+            &10,
+        )
+        .contract_ids;
+    assert_eq!(funded, vec![&test.env, funded_id]);
+
+    let created = test
+        .contract
+        .get_user_contracts(
+            &test.buyer,
+            &ContractFilter::Status(ContractStatus::Created),
+            &0,
+            &10,
+        )
+        .contract_ids;
+    assert_eq!(created, vec![&test.env, created_id]);
+}
+
+#[test]
+fn test_get_user_contracts_awaiting_action() {
+    let test = RefundTest::setup();
+    let unfunded_id = test.create_refund_contract();
+    let funded_id = test.create_refund_contract();
+    test.fund_contract(funded_id);
+
+    // Buyer must fund the first contract; seller must deliver the second
+    let buyer_todo = test
+        .contract
+        .get_user_contracts(&test.buyer, &ContractFilter::AwaitingAction, &0, &10)
+        .contract_ids;
+    assert_eq!(buyer_todo, vec![&test.env, unfunded_id]);
+
+    let seller_todo = test
+        .contract
+        .get_user_contracts(&test.seller, &ContractFilter::AwaitingAction, &0, &10)
+        .contract_ids;
+    assert_eq!(seller_todo, vec![&test.env, funded_id]);
+
+    // Once delivered, the ball is in the buyer's court
+    test.contract.mark_delivered(&funded_id, &test.seller);
+    let buyer_todo = test
+        .contract
+        .get_user_contracts(&test.buyer, &ContractFilter::AwaitingAction, &1, &10)
+        .contract_ids;
+    assert_eq!(buyer_todo, vec![&test.env, funded_id]);
+    let seller_todo = test
+        .contract
+        .get_user_contracts(&test.seller, &ContractFilter::AwaitingAction, &0, &10)
+        .contract_ids;
+    assert_eq!(seller_todo.len(), 0);
+}
+
+#[test]
+fn test_remove_completed_contract() {
+    let test = RefundTest::setup();
+    let mut contract_ids = std::vec![];
+    for _ in 0..USER_CONTRACTS_PAGE_SIZE + 2 {
+        contract_ids.push(test.create_refund_contract());
+    }
+    let first_id = contract_ids[0];
+    test.fund_contract(first_id);
+
+    // Still open: cannot be removed
+    let result = test.contract.try_remove_completed_contract(&first_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::OperationNotAllowed));
+
+    test.contract.mark_delivered(&first_id, &test.seller);
+    test.contract.confirm_delivery(&first_id, &test.buyer);
+    test.contract.remove_completed_contract(&first_id);
+
+    let remaining = USER_CONTRACTS_PAGE_SIZE + 1;
+    assert_eq!(test.contract.get_user_contract_count(&test.buyer), remaining);
+    assert_eq!(test.contract.get_user_contract_count(&test.seller), remaining);
+
+    // The last contract filled the freed slot
+    let all = test
+        .contract
+        .get_user_contracts(&test.buyer, &ContractFilter::All, &0, &100)
+        .contract_ids;
+    assert_eq!(all.len(), remaining);
+    assert!(!all.contains(first_id));
+    assert_eq!(all.get(0), contract_ids.last().copied());
+
+    // The contract itself stays queryable
+    let contract_data = test.contract.get_contract(&first_id);
+    assert_eq!(contract_data.status, ContractStatus::Completed);
+
+    let result = test.contract.try_remove_completed_contract(&first_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::DataNotFound));
+}
+
+#[test]
+fn test_filtered_listing_returns_cursor() {
+    let test = RefundTest::setup();
+    let total = MAX_FILTER_SCAN + 5;
+    let mut last_id = 0;
+    for _ in 0..total {
+        last_id = test.create_refund_contract();
+    }
+    test.fund_contract(last_id);
+
+    // The only match sits past the scan bound
+    let filter = ContractFilter::Status(ContractStatus::Funded);
+    let first = test
+        .contract
+        .get_user_contracts(&test.buyer, &filter, &0, &10);
+    assert_eq!(first.contract_ids.len(), 0);
+    assert_eq!(first.next_cursor, Some(MAX_FILTER_SCAN));
+
+    let second = test
+        .contract
+        .get_user_contracts(&test.buyer, &filter, &MAX_FILTER_SCAN, &10);
+    assert_eq!(second.contract_ids, vec![&test.env, last_id]);
+    assert_eq!(second.next_cursor, None);
+}
+
+#[test]
+fn test_migrate_legacy_user_contracts() {
+    let test = RefundTest::setup();
+    let first_id = test.create_refund_contract();
+    let second_id = test.create_refund_contract();
+
+    // Index left behind by the unpaged layout, overlapping the paged one
+    let legacy = vec![&test.env, first_id, second_id, 100u64, 101, 102];
+    test.env.as_contract(&test.contract.address, || {
+        test.env
+            .storage()
+            .persistent()
+            .set(&StorageKey::UserContracts(test.buyer.clone()), &legacy);
+    });
+
+    let left = test
+        .contract
+        .migrate_user_contracts(&test.admin, &test.buyer, &3);
+    assert_eq!(left, 2);
+    assert_eq!(test.contract.get_user_contract_count(&test.buyer), 3);
+
+    let left = test
+        .contract
+        .migrate_user_contracts(&test.admin, &test.buyer, &10);
+    assert_eq!(left, 0);
+    assert_eq!(test.contract.get_user_contract_count(&test.buyer), 5);
+
+    let all = test
+        .contract
+        .get_user_contracts(&test.buyer, &ContractFilter::All, &0, &10)
+        .contract_ids;
+    assert_eq!(all, vec![&test.env, first_id, second_id, 100, 101, 102]);
+
+    test.env.as_contract(&test.contract.address, || {
+        assert!(!test
+            .env
+            .storage()
+            .persistent()
+            .has(&StorageKey::UserContracts(test.buyer.clone())));
+    });
+
+    // Only the admin may run it
+    let result = test
+        .contract
+        .try_migrate_user_contracts(&test.buyer, &test.buyer, &10);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AdminOnly));
+}
+
 #[test]
 fn test_fund_contract_insufficient_balance() {
     let test = RefundTest::setup();
@@ -691,9 +1525,7 @@ fn test_fund_contract_insufficient_balance() {
         &test.seller,
         &test.token.address,
         &REFUND_AMOUNT,
-        &REFUND_DEADLINE,
-        &DELIVERY_DEADLINE,
-        &test.refund_conditions,
+        &test.terms,
     );
 
     // This should fail due to insufficient balance
@@ -2,10 +2,13 @@ use crate::{
     error::ContractError,
     events,
     storage::{self, add_user_transaction, get_transaction, increment_transaction_counter, save_transaction},
-    types::{ConsensusRule, ConsensusTransaction, TransactionStatus},
+    types::{
+        Agreement, Amendment, ConsensusRule, ConsensusTransaction, Contribution, Payee, PendingAmendment,
+        TransactionStatus,
+    },
     utils,
 };
-use soroban_sdk::{token, Address, Env, String, Vec};
+use soroban_sdk::{token, Address, Env, Map, String, Vec};
 
 pub fn create_transaction(
     env: &Env,
@@ -42,6 +45,10 @@ pub fn create_transaction(
         return Err(ContractError::ArbitratorRequired);
     }
 
+    if let ConsensusRule::Weighted(ref rule) = consensus_rule {
+        utils::validate_weighted_rule(rule)?;
+    }
+
     let current_time = env.ledger().timestamp();
     let deadline = current_time + deadline_duration;
 
@@ -75,6 +82,9 @@ pub fn create_transaction(
     if let Some(ref arb) = arbitrator {
         add_user_transaction(env, arb, transaction_id)?;
     }
+    for party in transaction.required_parties.iter() {
+        index_user_transaction(env, &party, transaction_id)?;
+    }
 
     // Emit event
     let event = events::TransactionCreatedEvent {
@@ -93,13 +103,47 @@ pub fn create_transaction(
 }
 
 pub fn fund_transaction(env: &Env, buyer: &Address, transaction_id: u64) -> Result<(), ContractError> {
-    let mut transaction = get_transaction(env, transaction_id)?;
+    let transaction = get_transaction(env, transaction_id)?;
 
     // Verify caller is the buyer
     if buyer != &transaction.buyer {
         return Err(ContractError::NotAuthorized);
     }
 
+    // Buyer covers whatever other contributors have not
+    let remaining = transaction.amount - transaction.funded_amount;
+    deposit(env, buyer, transaction, remaining)
+}
+
+pub fn contribute(
+    env: &Env,
+    contributor: &Address,
+    transaction_id: u64,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let transaction = get_transaction(env, transaction_id)?;
+
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    if amount > transaction.amount - transaction.funded_amount {
+        return Err(ContractError::ContributionExceedsRemaining);
+    }
+
+    deposit(env, contributor, transaction, amount)
+}
+
+/// Moves `amount` from the contributor into escrow, marking the transaction
+/// Funded once contributions cover the full amount.
+fn deposit(
+    env: &Env,
+    contributor: &Address,
+    mut transaction: ConsensusTransaction,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let transaction_id = transaction.transaction_id;
+
     // Check transaction status
     if transaction.status != TransactionStatus::Created {
         return Err(ContractError::TransactionAlreadyFunded);
@@ -121,9 +165,37 @@ pub fn fund_transaction(env: &Env, buyer: &Address, transaction_id: u64) -> Resu
         return Err(ContractError::TransactionExpired);
     }
 
+    let first_contribution = !transaction.contributions.contains_key(contributor.clone());
+    if first_contribution && *contributor != transaction.buyer {
+        let others = transaction.contributions.len()
+            - transaction.contributions.contains_key(transaction.buyer.clone()) as u32;
+        if others >= utils::MAX_CONTRIBUTORS {
+            return Err(ContractError::TooManyContributors);
+        }
+    }
+
     // Transfer funds to contract
     let token_client = token::Client::new(env, &transaction.token);
-    token_client.transfer(buyer, &env.current_contract_address(), &transaction.amount);
+    token_client.transfer(contributor, &env.current_contract_address(), &amount);
+
+    utils::record_contribution(&mut transaction, contributor, amount);
+    if first_contribution {
+        index_user_transaction(env, contributor, transaction_id)?;
+    }
+
+    let contribution_event = events::ContributionReceivedEvent {
+        transaction_id,
+        contributor: contributor.clone(),
+        amount,
+        funded_amount: transaction.funded_amount,
+        timestamp: current_time,
+    };
+    events::emit_contribution_received(env, contribution_event);
+
+    if transaction.funded_amount < transaction.amount {
+        save_transaction(env, &transaction)?;
+        return Ok(());
+    }
 
     // Update transaction status
     let old_status = transaction.status.clone();
@@ -133,7 +205,7 @@ pub fn fund_transaction(env: &Env, buyer: &Address, transaction_id: u64) -> Resu
     // Emit events
     let funded_event = events::TransactionFundedEvent {
         transaction_id,
-        buyer: buyer.clone(),
+        buyer: transaction.buyer.clone(),
         amount: transaction.amount,
         timestamp: current_time,
     };
@@ -150,6 +222,34 @@ pub fn fund_transaction(env: &Env, buyer: &Address, transaction_id: u64) -> Resu
     Ok(())
 }
 
+/// Adds the transaction to a user's list unless it is already there.
+fn index_user_transaction(env: &Env, user: &Address, transaction_id: u64) -> Result<(), ContractError> {
+    if storage::get_user_transactions(env, user).contains(transaction_id) {
+        return Ok(());
+    }
+    add_user_transaction(env, user, transaction_id)
+}
+
+/// Returns `refund_amount` of the escrow to contributors pro rata.
+fn refund_contributors(env: &Env, transaction: &mut ConsensusTransaction, refund_amount: i128) {
+    let token_client = token::Client::new(env, &transaction.token);
+    for (contributor, share) in utils::pro_rata_refunds(env, transaction, refund_amount).iter() {
+        token_client.transfer(&env.current_contract_address(), &contributor, &share);
+
+        let mut contribution = transaction.contributions.get(contributor.clone()).unwrap();
+        contribution.refunded += share;
+        transaction.contributions.set(contributor.clone(), contribution);
+
+        let event = events::ContributionRefundedEvent {
+            transaction_id: transaction.transaction_id,
+            contributor,
+            amount: share,
+        };
+        events::emit_contribution_refunded(env, event);
+    }
+    transaction.funded_amount -= refund_amount;
+}
+
 pub fn submit_agreement(
     env: &Env,
     party: &Address,
@@ -180,22 +280,25 @@ pub fn submit_agreement(
         return Err(ContractError::NotAuthorizedParty);
     }
 
-    // Check if already agreed
-    if transaction.agreements.contains_key(party.clone()) {
-        return Err(ContractError::AgreementAlreadySubmitted);
+    // A party may change their vote, but not resubmit the same one
+    if let Some(existing) = transaction.agreements.get(party.clone()) {
+        if existing.agreed == agreed {
+            return Err(ContractError::AgreementAlreadySubmitted);
+        }
     }
 
-    // Add agreement
+    // Add or replace agreement
     utils::add_agreement(&mut transaction, party.clone(), agreed, reason.clone(), current_time);
+    transaction.tally = utils::compute_tally(&transaction);
 
-    // Check for rejection
-    if !agreed {
+    // Refund once the rejections make consensus unreachable
+    if !utils::can_reach_consensus(&transaction) {
         transaction.status = TransactionStatus::Refunded;
-        save_transaction(env, &transaction)?;
 
-        // Refund buyer
-        let token_client = token::Client::new(env, &transaction.token);
-        token_client.transfer(&env.current_contract_address(), &transaction.buyer, &transaction.amount);
+        // Refund every contributor their share
+        let refund_amount = transaction.funded_amount;
+        refund_contributors(env, &mut transaction, refund_amount);
+        save_transaction(env, &transaction)?;
 
         // Emit events
         let rejection_event = events::ConsensusRejectedEvent {
@@ -208,7 +311,7 @@ pub fn submit_agreement(
         let refund_event = events::FundsRefundedEvent {
             transaction_id,
             buyer: transaction.buyer.clone(),
-            amount: transaction.amount,
+            amount: refund_amount,
             reason: reason.unwrap_or_else(|| String::from_str(env, "Party rejected agreement")),
             timestamp: current_time,
         };
@@ -245,6 +348,306 @@ pub fn submit_agreement(
     Ok(())
 }
 
+pub fn revoke_agreement(env: &Env, party: &Address, transaction_id: u64) -> Result<(), ContractError> {
+    let mut transaction = get_transaction(env, transaction_id)?;
+    let current_time = env.ledger().timestamp();
+
+    if transaction.status != TransactionStatus::Funded {
+        return Err(ContractError::InvalidTransactionStatus);
+    }
+
+    if utils::is_expired(&transaction, current_time) {
+        return Err(ContractError::TransactionExpired);
+    }
+
+    if !transaction.agreements.contains_key(party.clone()) {
+        return Err(ContractError::AgreementNotFound);
+    }
+
+    transaction.agreements.remove(party.clone());
+    transaction.tally = utils::compute_tally(&transaction);
+    save_transaction(env, &transaction)?;
+
+    let event = events::AgreementRevokedEvent {
+        transaction_id,
+        party: party.clone(),
+        timestamp: current_time,
+    };
+    events::emit_agreement_revoked(env, event);
+
+    Ok(())
+}
+
+/// Records an amendment as pending, with the proposer's approval. It replaces
+/// any earlier pending amendment and only takes effect once the approvals
+/// reach consensus under the transaction's rule.
+pub fn propose_amendment(
+    env: &Env,
+    proposer: &Address,
+    transaction_id: u64,
+    amendment: Amendment,
+) -> Result<(), ContractError> {
+    let transaction = get_transaction(env, transaction_id)?;
+    let current_time = env.ledger().timestamp();
+
+    if !transaction.required_parties.iter().any(|p| p == *proposer) {
+        return Err(ContractError::NotAuthorizedParty);
+    }
+
+    if !utils::is_negotiable(&transaction) {
+        return Err(ContractError::InvalidTransactionStatus);
+    }
+
+    if utils::is_expired(&transaction, current_time) {
+        return Err(ContractError::TransactionExpired);
+    }
+
+    validate_amendment(&transaction, &amendment, current_time)?;
+
+    let mut pending = PendingAmendment {
+        proposer: proposer.clone(),
+        amendment: amendment.clone(),
+        base_version: transaction.version,
+        approvals: Map::new(env),
+    };
+    pending.approvals.set(
+        proposer.clone(),
+        Agreement {
+            party: proposer.clone(),
+            agreed: true,
+            timestamp: current_time,
+            reason: None,
+            version: transaction.version,
+        },
+    );
+
+    let event = events::AmendmentProposedEvent {
+        transaction_id,
+        proposer: proposer.clone(),
+        version: transaction.version,
+        amendment,
+        timestamp: current_time,
+    };
+    events::emit_amendment_proposed(env, event);
+
+    // The proposer's weight alone may already carry it
+    if utils::has_consensus(&utils::amendment_ballot(&transaction, &pending.approvals)) {
+        storage::remove_pending_amendment(env, transaction_id);
+        return apply_amendment(env, proposer, transaction, pending.amendment);
+    }
+
+    storage::save_pending_amendment(env, transaction_id, &pending);
+    Ok(())
+}
+
+/// Approves or rejects the pending amendment. It is applied once approvals
+/// reach consensus, and dropped once rejections make that impossible.
+pub fn vote_amendment(
+    env: &Env,
+    party: &Address,
+    transaction_id: u64,
+    approve: bool,
+) -> Result<(), ContractError> {
+    let transaction = get_transaction(env, transaction_id)?;
+    let current_time = env.ledger().timestamp();
+
+    if !transaction.required_parties.iter().any(|p| p == *party) {
+        return Err(ContractError::NotAuthorizedParty);
+    }
+
+    if !utils::is_negotiable(&transaction) {
+        return Err(ContractError::InvalidTransactionStatus);
+    }
+
+    if utils::is_expired(&transaction, current_time) {
+        return Err(ContractError::TransactionExpired);
+    }
+
+    let mut pending = pending_amendment(env, &transaction).ok_or(ContractError::AmendmentNotFound)?;
+
+    // As with the terms, a party may change their vote but not repeat it
+    if let Some(existing) = pending.approvals.get(party.clone()) {
+        if existing.agreed == approve {
+            return Err(ContractError::AgreementAlreadySubmitted);
+        }
+    }
+    pending.approvals.set(
+        party.clone(),
+        Agreement {
+            party: party.clone(),
+            agreed: approve,
+            timestamp: current_time,
+            reason: None,
+            version: transaction.version,
+        },
+    );
+
+    let event = events::AmendmentVotedEvent {
+        transaction_id,
+        party: party.clone(),
+        approved: approve,
+        timestamp: current_time,
+    };
+    events::emit_amendment_voted(env, event);
+
+    let ballot = utils::amendment_ballot(&transaction, &pending.approvals);
+    if utils::has_consensus(&ballot) {
+        storage::remove_pending_amendment(env, transaction_id);
+        return apply_amendment(env, party, transaction, pending.amendment);
+    }
+
+    if !utils::can_reach_consensus(&ballot) {
+        storage::remove_pending_amendment(env, transaction_id);
+
+        let event = events::AmendmentRejectedEvent {
+            transaction_id,
+            rejecting_party: party.clone(),
+            timestamp: current_time,
+        };
+        events::emit_amendment_rejected(env, event);
+        return Ok(());
+    }
+
+    storage::save_pending_amendment(env, transaction_id, &pending);
+    Ok(())
+}
+
+/// The pending amendment, unless the terms it was proposed against have
+/// since changed.
+fn pending_amendment(env: &Env, transaction: &ConsensusTransaction) -> Option<PendingAmendment> {
+    storage::get_pending_amendment(env, transaction.transaction_id)
+        .filter(|pending| pending.base_version == transaction.version)
+}
+
+/// Checks that the amendment changes something and leaves valid terms.
+fn validate_amendment(
+    transaction: &ConsensusTransaction,
+    amendment: &Amendment,
+    current_time: u64,
+) -> Result<(), ContractError> {
+    if amendment.deadline.is_none() && amendment.amount.is_none() && amendment.payees.is_none() {
+        return Err(ContractError::InvalidAmendment);
+    }
+
+    if let Some(deadline) = amendment.deadline {
+        if deadline <= current_time {
+            return Err(ContractError::InvalidDeadline);
+        }
+    }
+
+    if let Some(amount) = amendment.amount {
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+    }
+
+    // A new amount must still cover the split, whether or not the split changed
+    let amount = amendment.amount.unwrap_or(transaction.amount);
+    let payees = amendment.payees.clone().unwrap_or(transaction.payees.clone());
+    if !payees.is_empty() {
+        utils::validate_payout_split(&payees, amount)?;
+    }
+    Ok(())
+}
+
+/// Applies an agreed amendment, bumping the terms version and clearing votes.
+fn apply_amendment(
+    env: &Env,
+    caller: &Address,
+    mut transaction: ConsensusTransaction,
+    amendment: Amendment,
+) -> Result<(), ContractError> {
+    let transaction_id = transaction.transaction_id;
+    let current_time = env.ledger().timestamp();
+
+    // Time has passed since the proposal, so the deadline is checked again
+    validate_amendment(&transaction, &amendment, current_time)?;
+
+    if let Some(deadline) = amendment.deadline {
+        transaction.deadline = deadline;
+    }
+
+    if let Some(amount) = amendment.amount {
+        // A funded escrow is topped up by the buyer to match a higher amount
+        if transaction.status == TransactionStatus::Funded && amount > transaction.amount {
+            if *caller != transaction.buyer {
+                transaction.buyer.require_auth();
+            }
+            let top_up = amount - transaction.amount;
+            let token_client = token::Client::new(env, &transaction.token);
+            token_client.transfer(&transaction.buyer, &env.current_contract_address(), &top_up);
+            let buyer = transaction.buyer.clone();
+            utils::record_contribution(&mut transaction, &buyer, top_up);
+        }
+
+        // Deposits above the new amount go back to contributors pro rata
+        if amount < transaction.funded_amount {
+            let excess = transaction.funded_amount - amount;
+            refund_contributors(env, &mut transaction, excess);
+        }
+        transaction.amount = amount;
+
+        // Lowering the amount can complete funding of a partly funded transaction
+        if transaction.status == TransactionStatus::Created && transaction.funded_amount == amount {
+            transaction.status = TransactionStatus::Funded;
+        }
+    }
+
+    if let Some(ref payees) = amendment.payees {
+        transaction.payees = payees.clone();
+    }
+
+    utils::bump_version(env, &mut transaction);
+    save_transaction(env, &transaction)?;
+
+    let event = events::AmendmentAppliedEvent {
+        transaction_id,
+        version: transaction.version,
+        amendment,
+        timestamp: current_time,
+    };
+    events::emit_amendment_applied(env, event);
+
+    Ok(())
+}
+
+/// Proposes a new payout split as a pending amendment. Like any other change
+/// to the terms, it only replaces the payees once the other parties approve.
+pub fn propose_payout_split(
+    env: &Env,
+    proposer: &Address,
+    transaction_id: u64,
+    payees: Vec<Payee>,
+) -> Result<(), ContractError> {
+    let transaction = get_transaction(env, transaction_id)?;
+    let current_time = env.ledger().timestamp();
+
+    if !transaction.required_parties.iter().any(|p| p == *proposer) {
+        return Err(ContractError::NotAuthorizedParty);
+    }
+
+    // Unlike a general amendment, a proposed split may not be empty
+    utils::validate_payout_split(&payees, transaction.amount)?;
+
+    let amendment = Amendment {
+        deadline: None,
+        amount: None,
+        payees: Some(payees.clone()),
+    };
+    propose_amendment(env, proposer, transaction_id, amendment)?;
+
+    let event = events::PayoutSplitProposedEvent {
+        transaction_id,
+        proposer: proposer.clone(),
+        payees,
+        version: transaction.version,
+        timestamp: current_time,
+    };
+    events::emit_payout_split_proposed(env, event);
+
+    Ok(())
+}
+
 pub fn release_funds(env: &Env, caller: Address, transaction_id: u64) -> Result<(), ContractError> {
     let mut transaction = get_transaction(env, transaction_id)?;
     let current_time = env.ledger().timestamp();
@@ -279,9 +682,18 @@ pub fn release_funds(env: &Env, caller: Address, transaction_id: u64) -> Result<
         return Err(ContractError::ConsensusNotReached);
     }
 
-    // Transfer funds to seller
+    // Pay out the approved split, with any remainder going to the seller
     let token_client = token::Client::new(env, &transaction.token);
-    token_client.transfer(&env.current_contract_address(), &transaction.seller, &transaction.amount);
+    for (recipient, amount) in utils::compute_payouts(env, &transaction).iter() {
+        token_client.transfer(&env.current_contract_address(), &recipient, &amount);
+
+        let payout_event = events::PayoutDistributedEvent {
+            transaction_id,
+            recipient,
+            amount,
+        };
+        events::emit_payout_distributed(env, payout_event);
+    }
 
     // Update transaction status
     let old_status = transaction.status.clone();
@@ -328,18 +740,17 @@ pub fn handle_expiration(env: &Env, transaction_id: u64) -> Result<(), ContractE
     let old_status = transaction.status.clone();
     transaction.status = TransactionStatus::Expired;
 
-    // If funds were deposited, refund to buyer
-    if old_status == TransactionStatus::Funded || 
-       old_status == TransactionStatus::ConsensusReached {
+    // If funds were deposited, refund every contributor their share
+    if transaction.funded_amount > 0 {
         transaction.status = TransactionStatus::Refunded;
-        
-        let token_client = token::Client::new(env, &transaction.token);
-        token_client.transfer(&env.current_contract_address(), &transaction.buyer, &transaction.amount);
+
+        let refund_amount = transaction.funded_amount;
+        refund_contributors(env, &mut transaction, refund_amount);
 
         let refund_event = events::FundsRefundedEvent {
             transaction_id,
             buyer: transaction.buyer.clone(),
-            amount: transaction.amount,
+            amount: refund_amount,
             reason: String::from_str(env, "Transaction expired"),
             timestamp: current_time,
         };
@@ -371,6 +782,16 @@ pub fn get_transaction_details(env: &Env, transaction_id: u64) -> Result<Consens
     get_transaction(env, transaction_id)
 }
 
+pub fn get_contribution(env: &Env, transaction_id: u64, contributor: &Address) -> Result<Option<Contribution>, ContractError> {
+    let transaction = get_transaction(env, transaction_id)?;
+    Ok(transaction.contributions.get(contributor.clone()))
+}
+
+pub fn get_pending_amendment(env: &Env, transaction_id: u64) -> Result<Option<PendingAmendment>, ContractError> {
+    let transaction = get_transaction(env, transaction_id)?;
+    Ok(pending_amendment(env, &transaction))
+}
+
 pub fn get_user_transactions(env: &Env, user: &Address) -> Vec<u64> {
     storage::get_user_transactions(env, user)
 }
\ No newline at end of file
@@ -16,6 +16,8 @@ pub enum ContractError {
     InvalidConsensusRule = 13,
     DuplicateParties = 14,
     ArbitratorRequired = 15,
+    InvalidPayoutSplit = 16,
+    InvalidAmendment = 17,
 
     // Status Errors
     TransactionNotFunded = 20,
@@ -30,10 +32,14 @@ pub enum ContractError {
     AgreementAlreadySubmitted = 31,
     ConsensusNotReached = 32,
     ConsensusRejected = 33,
+    AgreementNotFound = 34,
+    AmendmentNotFound = 35,
 
     // Fund Management Errors
     InsufficientFunds = 40,
     TransferFailed = 41,
+    ContributionExceedsRemaining = 42,
+    TooManyContributors = 43,
 
     // System Errors
     InvalidTimestamp = 50,
@@ -1,5 +1,5 @@
-use soroban_sdk::{Address, Env, String, contracttype};
-use crate::types::{ConsensusRule, TransactionStatus};
+use soroban_sdk::{Address, Env, String, Vec, contracttype};
+use crate::types::{Amendment, ConsensusRule, Payee, TransactionStatus};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -23,6 +23,24 @@ pub struct TransactionFundedEvent {
     pub timestamp: u64,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContributionReceivedEvent {
+    pub transaction_id: u64,
+    pub contributor: Address,
+    pub amount: i128,
+    pub funded_amount: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContributionRefundedEvent {
+    pub transaction_id: u64,
+    pub contributor: Address,
+    pub amount: i128,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AgreementSubmittedEvent {
@@ -33,6 +51,50 @@ pub struct AgreementSubmittedEvent {
     pub timestamp: u64,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgreementRevokedEvent {
+    pub transaction_id: u64,
+    pub party: Address,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmendmentProposedEvent {
+    pub transaction_id: u64,
+    pub proposer: Address,
+    pub version: u32, // Terms version being amended
+    pub amendment: Amendment,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmendmentVotedEvent {
+    pub transaction_id: u64,
+    pub party: Address,
+    pub approved: bool,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmendmentAppliedEvent {
+    pub transaction_id: u64,
+    pub version: u32, // New terms version
+    pub amendment: Amendment,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmendmentRejectedEvent {
+    pub transaction_id: u64,
+    pub rejecting_party: Address,
+    pub timestamp: u64,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ConsensusReachedEvent {
@@ -58,6 +120,24 @@ pub struct FundsReleasedEvent {
     pub timestamp: u64,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutSplitProposedEvent {
+    pub transaction_id: u64,
+    pub proposer: Address,
+    pub payees: Vec<Payee>,
+    pub version: u32,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutDistributedEvent {
+    pub transaction_id: u64,
+    pub recipient: Address,
+    pub amount: i128,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FundsRefundedEvent {
@@ -94,10 +174,38 @@ pub fn emit_transaction_funded(env: &Env, event: TransactionFundedEvent) {
     env.events().publish(("transaction_funded",), event);
 }
 
+pub fn emit_contribution_received(env: &Env, event: ContributionReceivedEvent) {
+    env.events().publish(("contribution_received",), event);
+}
+
+pub fn emit_contribution_refunded(env: &Env, event: ContributionRefundedEvent) {
+    env.events().publish(("contribution_refunded",), event);
+}
+
 pub fn emit_agreement_submitted(env: &Env, event: AgreementSubmittedEvent) {
     env.events().publish(("agreement_submitted",), event);
 }
 
+pub fn emit_agreement_revoked(env: &Env, event: AgreementRevokedEvent) {
+    env.events().publish(("agreement_revoked",), event);
+}
+
+pub fn emit_amendment_proposed(env: &Env, event: AmendmentProposedEvent) {
+    env.events().publish(("amendment_proposed",), event);
+}
+
+pub fn emit_amendment_voted(env: &Env, event: AmendmentVotedEvent) {
+    env.events().publish(("amendment_voted",), event);
+}
+
+pub fn emit_amendment_applied(env: &Env, event: AmendmentAppliedEvent) {
+    env.events().publish(("amendment_applied",), event);
+}
+
+pub fn emit_amendment_rejected(env: &Env, event: AmendmentRejectedEvent) {
+    env.events().publish(("amendment_rejected",), event);
+}
+
 pub fn emit_consensus_reached(env: &Env, event: ConsensusReachedEvent) {
     env.events().publish(("consensus_reached",), event);
 }
@@ -110,6 +218,14 @@ pub fn emit_funds_released(env: &Env, event: FundsReleasedEvent) {
     env.events().publish(("funds_released",), event);
 }
 
+pub fn emit_payout_split_proposed(env: &Env, event: PayoutSplitProposedEvent) {
+    env.events().publish(("payout_split_proposed",), event);
+}
+
+pub fn emit_payout_distributed(env: &Env, event: PayoutDistributedEvent) {
+    env.events().publish(("payout_distributed",), event);
+}
+
 pub fn emit_funds_refunded(env: &Env, event: FundsRefundedEvent) {
     env.events().publish(("funds_refunded",), event);
 }
@@ -13,7 +13,7 @@ mod test;
 use contract::*;
 use error::ContractError;
 use soroban_sdk::{contract, contractimpl, Address, Env, String, Vec};
-use types::{ConsensusRule, ConsensusTransaction};
+use types::{Amendment, ConsensusRule, ConsensusTransaction, Contribution, Payee, PendingAmendment};
 
 #[contract]
 pub struct ConsensusReleaseContract;
@@ -67,7 +67,8 @@ impl ConsensusReleaseContract {
     }
 
     /// Fund a transaction by transferring tokens to escrow
-    /// Can only be called by the buyer of the transaction
+    /// Can only be called by the buyer of the transaction.
+    /// The buyer deposits whatever other contributors have not yet covered
     pub fn fund_transaction(
         env: Env,
         buyer: Address,
@@ -77,8 +78,22 @@ impl ConsensusReleaseContract {
         fund_transaction(&env, &buyer, transaction_id)
     }
 
+    /// Deposit part of the transaction amount from any contributor
+    /// The transaction becomes Funded once contributions cover the full amount.
+    /// Refunds return each contributor's share pro rata
+    pub fn contribute(
+        env: Env,
+        contributor: Address,
+        transaction_id: u64,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        contributor.require_auth();
+        contribute(&env, &contributor, transaction_id, amount)
+    }
+
     /// Submit agreement or rejection for a transaction
-    /// Can be called by any authorized party (buyer, seller, arbitrator)
+    /// Can be called by any authorized party (buyer, seller, arbitrator).
+    /// A party may call again with the opposite decision to change their vote
     /// 
     /// # Arguments
     /// * `party` - Address of the party submitting the agreement
@@ -96,7 +111,58 @@ impl ConsensusReleaseContract {
         submit_agreement(&env, &party, transaction_id, agreed, reason)
     }
 
-    /// Release funds to seller after consensus is reached
+    /// Withdraw a previously submitted agreement before consensus is reached
+    pub fn revoke_agreement(
+        env: Env,
+        party: Address,
+        transaction_id: u64,
+    ) -> Result<(), ContractError> {
+        party.require_auth();
+        revoke_agreement(&env, &party, transaction_id)
+    }
+
+    /// Propose amending the deadline, amount or payout split of a transaction
+    /// Can be called by any required party before consensus is reached.
+    /// The amendment stays pending, replacing any earlier one, until the
+    /// parties approve it under the transaction's consensus rule.
+    pub fn propose_amendment(
+        env: Env,
+        proposer: Address,
+        transaction_id: u64,
+        amendment: Amendment,
+    ) -> Result<(), ContractError> {
+        proposer.require_auth();
+        propose_amendment(&env, &proposer, transaction_id, amendment)
+    }
+
+    /// Approve or reject the pending amendment
+    /// Once approved it bumps the terms version and clears all agreements.
+    /// Changing the amount of a funded transaction tops up from or refunds the buyer.
+    pub fn vote_amendment(
+        env: Env,
+        party: Address,
+        transaction_id: u64,
+        approve: bool,
+    ) -> Result<(), ContractError> {
+        party.require_auth();
+        vote_amendment(&env, &party, transaction_id, approve)
+    }
+
+    /// Propose how released funds are split across payees
+    /// Can be called by any required party before consensus is reached.
+    /// The split is a pending amendment and applies once the parties approve it,
+    /// which bumps the terms version and clears all agreements.
+    pub fn propose_payout_split(
+        env: Env,
+        proposer: Address,
+        transaction_id: u64,
+        payees: Vec<Payee>,
+    ) -> Result<(), ContractError> {
+        proposer.require_auth();
+        propose_payout_split(&env, &proposer, transaction_id, payees)
+    }
+
+    /// Release funds after consensus is reached, following the approved payout split
     /// Can be called by any authorized party once consensus is achieved
     pub fn release_funds(
         env: Env,
@@ -118,6 +184,23 @@ impl ConsensusReleaseContract {
         get_transaction_details(&env, transaction_id)
     }
 
+    /// Get a contributor's deposited and refunded amounts for a transaction
+    pub fn get_contribution(
+        env: Env,
+        transaction_id: u64,
+        contributor: Address,
+    ) -> Result<Option<Contribution>, ContractError> {
+        get_contribution(&env, transaction_id, &contributor)
+    }
+
+    /// Get the amendment awaiting approval, if any
+    pub fn get_pending_amendment(
+        env: Env,
+        transaction_id: u64,
+    ) -> Result<Option<PendingAmendment>, ContractError> {
+        get_pending_amendment(&env, transaction_id)
+    }
+
     /// Get all transaction IDs associated with a user
     pub fn get_user_transactions(env: Env, user: Address) -> Vec<u64> {
         get_user_transactions(&env, &user)
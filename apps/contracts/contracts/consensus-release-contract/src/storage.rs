@@ -1,4 +1,4 @@
-use crate::types::{ConsensusTransaction, DataKey};
+use crate::types::{ConsensusTransaction, DataKey, PendingAmendment};
 use soroban_sdk::{Address, Env, Vec};
 
 const INSTANCE_LIFETIME_THRESHOLD: u32 = 518400; // 30 days
@@ -21,6 +21,26 @@ pub fn save_transaction(env: &Env, transaction: &ConsensusTransaction) -> Result
     Ok(())
 }
 
+pub fn get_pending_amendment(env: &Env, transaction_id: u64) -> Option<PendingAmendment> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PendingAmendment(transaction_id))
+}
+
+pub fn save_pending_amendment(env: &Env, transaction_id: u64, pending: &PendingAmendment) {
+    let key = DataKey::PendingAmendment(transaction_id);
+    env.storage().persistent().set(&key, pending);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_pending_amendment(env: &Env, transaction_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PendingAmendment(transaction_id));
+}
+
 pub fn get_transaction_counter(env: &Env) -> u64 {
     env.storage()
         .persistent()
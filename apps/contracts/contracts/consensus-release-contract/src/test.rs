@@ -2,12 +2,16 @@
 
 use crate::{
     error::ContractError,
-    types::{ConsensusRule, TransactionStatus},
+    types::{
+        Amendment, ConsensusRule, Contribution, Payee, PayoutShare, TransactionStatus, VoteTally, WeightedParty,
+        WeightedRule,
+    },
+    utils::MAX_CONTRIBUTORS,
     ConsensusReleaseContract, ConsensusReleaseContractClient,
 };
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
-    token, Address, Env, String,
+    token, vec, Address, Env, String, Vec,
 };
 
 fn create_token_contract<'a>(env: &Env, admin: &Address) -> (token::Client<'a>, token::StellarAssetClient<'a>) {
@@ -590,4 +594,764 @@ fn test_edge_case_same_timestamp() {
 
     let transaction = ctx.contract.get_transaction(&transaction_id);
     assert_eq!(transaction.status, TransactionStatus::ConsensusReached);
-}
\ No newline at end of file
+}
+
+fn weighted_rule(env: &Env, parties: &[(Address, u32)], threshold_bps: u32) -> ConsensusRule {
+    let mut weighted = vec![env];
+    for (party, weight) in parties {
+        weighted.push_back(WeightedParty {
+            party: party.clone(),
+            weight: *weight,
+        });
+    }
+    ConsensusRule::Weighted(WeightedRule {
+        parties: weighted,
+        threshold_bps,
+    })
+}
+
+#[test]
+fn test_weighted_consensus_threshold() {
+    let ctx = TestContext::new();
+    let investor_a = Address::generate(&ctx.env);
+    let investor_b = Address::generate(&ctx.env);
+    let investor_c = Address::generate(&ctx.env);
+
+    let rule = weighted_rule(
+        &ctx.env,
+        &[(investor_a.clone(), 50), (investor_b.clone(), 30), (investor_c.clone(), 20)],
+        6000,
+    );
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Syndicated purchase"),
+        &rule,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.required_parties.len(), 3);
+    assert_eq!(
+        transaction.tally,
+        VoteTally { total_weight: 100, agreed_weight: 0, rejected_weight: 0 }
+    );
+
+    // 50% of weight is below the 60% threshold
+    ctx.contract.submit_agreement(&investor_a, &transaction_id, &true, &None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Funded);
+    assert_eq!(transaction.tally.agreed_weight, 50);
+
+    // A rejection that leaves the threshold reachable does not refund
+    ctx.contract.submit_agreement(&investor_b, &transaction_id, &false, &None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Funded);
+    assert_eq!(transaction.tally.rejected_weight, 30);
+
+    // 70% agreed reaches consensus
+    ctx.contract.submit_agreement(&investor_c, &transaction_id, &true, &None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::ConsensusReached);
+    assert_eq!(
+        transaction.tally,
+        VoteTally { total_weight: 100, agreed_weight: 70, rejected_weight: 30 }
+    );
+
+    ctx.contract.release_funds(&investor_a, &transaction_id);
+    assert_eq!(ctx.token.balance(&ctx.seller), 100);
+}
+
+#[test]
+fn test_weighted_rejection_refunds_when_unreachable() {
+    let ctx = TestContext::new();
+    let investor_a = Address::generate(&ctx.env);
+    let investor_b = Address::generate(&ctx.env);
+
+    let rule = weighted_rule(&ctx.env, &[(investor_a.clone(), 60), (investor_b.clone(), 40)], 6000);
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Syndicated purchase"),
+        &rule,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    // Only 40% of weight remains, so the 60% threshold can no longer be met
+    ctx.contract.submit_agreement(&investor_a, &transaction_id, &false, &None);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Refunded);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 1000);
+
+    // Parties outside the weighted list cannot vote
+    let result = ctx.contract.try_submit_agreement(&ctx.seller, &transaction_id, &true, &None);
+    assert_eq!(result, Err(Ok(ContractError::NotAuthorizedParty)));
+}
+
+#[test]
+fn test_weighted_rule_validation() {
+    let ctx = TestContext::new();
+    let investor = Address::generate(&ctx.env);
+
+    let invalid_rules = [
+        (weighted_rule(&ctx.env, &[], 6000), ContractError::InvalidConsensusRule),
+        (weighted_rule(&ctx.env, &[(investor.clone(), 10)], 0), ContractError::InvalidConsensusRule),
+        (weighted_rule(&ctx.env, &[(investor.clone(), 10)], 10_001), ContractError::InvalidConsensusRule),
+        (weighted_rule(&ctx.env, &[(investor.clone(), 0)], 6000), ContractError::InvalidConsensusRule),
+        (
+            weighted_rule(&ctx.env, &[(investor.clone(), 10), (investor.clone(), 20)], 6000),
+            ContractError::DuplicateParties,
+        ),
+        (
+            weighted_rule(&ctx.env, &[(investor.clone(), u32::MAX), (ctx.buyer.clone(), 1)], 6000),
+            ContractError::InvalidConsensusRule,
+        ),
+    ];
+
+    for (rule, expected) in invalid_rules {
+        let result = ctx.contract.try_create_transaction(
+            &ctx.buyer,
+            &ctx.seller,
+            &None,
+            &ctx.token.address,
+            &100,
+            &String::from_str(&ctx.env, "Test"),
+            &rule,
+            &3600,
+        );
+        assert_eq!(result, Err(Ok(expected)));
+    }
+}
+
+#[test]
+fn test_payout_split_release() {
+    let ctx = TestContext::new();
+    let broker = Address::generate(&ctx.env);
+    let partner = Address::generate(&ctx.env);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &1000,
+        &String::from_str(&ctx.env, "Group deal"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    let payees = vec![
+        &ctx.env,
+        Payee { recipient: broker.clone(), share: PayoutShare::Fixed(50) },
+        Payee { recipient: partner.clone(), share: PayoutShare::Bps(2_500) },
+    ];
+    ctx.contract.propose_payout_split(&ctx.seller, &transaction_id, &payees);
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &true);
+
+    ctx.contract.submit_agreement(&ctx.buyer, &transaction_id, &true, &None);
+    ctx.contract.submit_agreement(&ctx.seller, &transaction_id, &true, &None);
+    ctx.contract.release_funds(&ctx.buyer, &transaction_id);
+
+    // 50 fixed, 25% of 1000, and the remaining 700 to the seller
+    assert_eq!(ctx.token.balance(&broker), 50);
+    assert_eq!(ctx.token.balance(&partner), 250);
+    assert_eq!(ctx.token.balance(&ctx.seller), 700);
+    assert_eq!(ctx.token.balance(&ctx.contract.address), 0);
+}
+
+#[test]
+fn test_payout_split_change_resets_approvals() {
+    let ctx = TestContext::new();
+    let partner = Address::generate(&ctx.env);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &Some(ctx.arbitrator.clone()),
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Group deal"),
+        &ConsensusRule::WithArbitrator,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    ctx.contract.submit_agreement(&ctx.buyer, &transaction_id, &true, &None);
+    ctx.contract.submit_agreement(&ctx.seller, &transaction_id, &true, &None);
+
+    let payees = vec![
+        &ctx.env,
+        Payee { recipient: partner.clone(), share: PayoutShare::Bps(10_000) },
+    ];
+    ctx.contract.propose_payout_split(&ctx.arbitrator, &transaction_id, &payees);
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &true);
+    ctx.contract.vote_amendment(&ctx.seller, &transaction_id, &true);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.payees, payees);
+    assert!(transaction.agreements.is_empty());
+    assert_eq!(transaction.tally.agreed_weight, 0);
+
+    // Earlier approvals are gone, so the arbitrator's vote alone is not enough
+    ctx.contract.submit_agreement(&ctx.arbitrator, &transaction_id, &true, &None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Funded);
+
+    ctx.contract.submit_agreement(&ctx.buyer, &transaction_id, &true, &None);
+    ctx.contract.submit_agreement(&ctx.seller, &transaction_id, &true, &None);
+
+    // The split is locked once consensus is reached
+    let result = ctx.contract.try_propose_payout_split(&ctx.seller, &transaction_id, &payees);
+    assert_eq!(result, Err(Ok(ContractError::InvalidTransactionStatus)));
+
+    ctx.contract.release_funds(&ctx.seller, &transaction_id);
+    assert_eq!(ctx.token.balance(&partner), 100);
+    assert_eq!(ctx.token.balance(&ctx.seller), 0);
+}
+
+#[test]
+fn test_payout_split_validation() {
+    let ctx = TestContext::new();
+    let partner = Address::generate(&ctx.env);
+    let outsider = Address::generate(&ctx.env);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Group deal"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+
+    let valid = vec![
+        &ctx.env,
+        Payee { recipient: partner.clone(), share: PayoutShare::Fixed(10) },
+    ];
+    let result = ctx.contract.try_propose_payout_split(&outsider, &transaction_id, &valid);
+    assert_eq!(result, Err(Ok(ContractError::NotAuthorizedParty)));
+
+    let invalid_splits = [
+        vec![&ctx.env],
+        vec![&ctx.env, Payee { recipient: partner.clone(), share: PayoutShare::Fixed(0) }],
+        vec![&ctx.env, Payee { recipient: partner.clone(), share: PayoutShare::Bps(0) }],
+        vec![&ctx.env, Payee { recipient: partner.clone(), share: PayoutShare::Fixed(101) }],
+        vec![
+            &ctx.env,
+            Payee { recipient: partner.clone(), share: PayoutShare::Fixed(60) },
+            Payee { recipient: ctx.seller.clone(), share: PayoutShare::Bps(5_000) },
+        ],
+    ];
+    for payees in invalid_splits {
+        let result = ctx.contract.try_propose_payout_split(&ctx.buyer, &transaction_id, &payees);
+        assert_eq!(result, Err(Ok(ContractError::InvalidPayoutSplit)));
+    }
+}
+
+#[test]
+fn test_change_and_revoke_vote() {
+    let ctx = TestContext::new();
+    let investor_a = Address::generate(&ctx.env);
+    let investor_b = Address::generate(&ctx.env);
+    let investor_c = Address::generate(&ctx.env);
+
+    let rule = weighted_rule(
+        &ctx.env,
+        &[(investor_a.clone(), 40), (investor_b.clone(), 30), (investor_c.clone(), 30)],
+        6000,
+    );
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Syndicated purchase"),
+        &rule,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    // Reject, then change the vote to an approval
+    ctx.contract.submit_agreement(&investor_b, &transaction_id, &false, &None);
+    ctx.contract.submit_agreement(&investor_b, &transaction_id, &true, &None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.tally.agreed_weight, 30);
+    assert_eq!(transaction.tally.rejected_weight, 0);
+
+    // Revoking removes the vote from the tally
+    ctx.contract.revoke_agreement(&investor_b, &transaction_id);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert!(!transaction.agreements.contains_key(investor_b.clone()));
+    assert_eq!(transaction.tally.agreed_weight, 0);
+
+    let result = ctx.contract.try_revoke_agreement(&investor_b, &transaction_id);
+    assert_eq!(result, Err(Ok(ContractError::AgreementNotFound)));
+
+    ctx.contract.submit_agreement(&investor_a, &transaction_id, &true, &None);
+    ctx.contract.submit_agreement(&investor_c, &transaction_id, &true, &None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::ConsensusReached);
+
+    // Votes are final once consensus is reached
+    let result = ctx.contract.try_revoke_agreement(&investor_a, &transaction_id);
+    assert_eq!(result, Err(Ok(ContractError::InvalidTransactionStatus)));
+    let result = ctx.contract.try_submit_agreement(&investor_a, &transaction_id, &false, &None);
+    assert_eq!(result, Err(Ok(ContractError::NotAuthorizedParty)));
+}
+
+#[test]
+fn test_amendment_bumps_version_and_resets_votes() {
+    let ctx = TestContext::new();
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Test transaction"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+    ctx.contract.submit_agreement(&ctx.buyer, &transaction_id, &true, &None);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.version, 1);
+    assert_eq!(transaction.agreements.get(ctx.buyer.clone()).unwrap().version, 1);
+
+    // Seller asks for more money and more time
+    let new_deadline = ctx.env.ledger().timestamp() + 7200;
+    let amendment = Amendment {
+        deadline: Some(new_deadline),
+        amount: Some(150),
+        payees: None,
+    };
+    ctx.contract.propose_amendment(&ctx.seller, &transaction_id, &amendment);
+
+    // Nothing changes until the buyer approves too
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.version, 1);
+    assert_eq!(transaction.amount, 100);
+    assert_eq!(transaction.agreements.len(), 1);
+    let pending = ctx.contract.get_pending_amendment(&transaction_id).unwrap();
+    assert_eq!(pending.amendment, amendment);
+    assert_eq!(pending.base_version, 1);
+
+    let result = ctx.contract.try_vote_amendment(&ctx.seller, &transaction_id, &true);
+    assert_eq!(result, Err(Ok(ContractError::AgreementAlreadySubmitted)));
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &true);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.version, 2);
+    assert_eq!(ctx.contract.get_pending_amendment(&transaction_id), None);
+    assert_eq!(transaction.amount, 150);
+    assert_eq!(transaction.deadline, new_deadline);
+    assert!(transaction.agreements.is_empty());
+    assert_eq!(ctx.token.balance(&ctx.contract.address), 150); // Buyer topped up
+
+    // Both parties approve the new terms
+    ctx.contract.submit_agreement(&ctx.buyer, &transaction_id, &true, &None);
+    ctx.contract.submit_agreement(&ctx.seller, &transaction_id, &true, &None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::ConsensusReached);
+    assert_eq!(transaction.agreements.get(ctx.seller.clone()).unwrap().version, 2);
+
+    ctx.contract.release_funds(&ctx.seller, &transaction_id);
+    assert_eq!(ctx.token.balance(&ctx.seller), 150);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 850);
+}
+
+#[test]
+fn test_amendment_validation() {
+    let ctx = TestContext::new();
+    let partner = Address::generate(&ctx.env);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Test transaction"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+    ctx.contract.propose_payout_split(
+        &ctx.seller,
+        &transaction_id,
+        &vec![&ctx.env, Payee { recipient: partner.clone(), share: PayoutShare::Fixed(80) }],
+    );
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &true);
+
+    let empty = Amendment { deadline: None, amount: None, payees: None };
+    let result = ctx.contract.try_propose_amendment(&ctx.buyer, &transaction_id, &empty);
+    assert_eq!(result, Err(Ok(ContractError::InvalidAmendment)));
+
+    let past_deadline = Amendment {
+        deadline: Some(ctx.env.ledger().timestamp()),
+        amount: None,
+        payees: None,
+    };
+    let result = ctx.contract.try_propose_amendment(&ctx.buyer, &transaction_id, &past_deadline);
+    assert_eq!(result, Err(Ok(ContractError::InvalidDeadline)));
+
+    // Cutting the amount below the existing split is rejected
+    let too_small = Amendment { deadline: None, amount: Some(50), payees: None };
+    let result = ctx.contract.try_propose_amendment(&ctx.buyer, &transaction_id, &too_small);
+    assert_eq!(result, Err(Ok(ContractError::InvalidPayoutSplit)));
+
+    // Lowering the amount together with the split refunds the difference
+    let lower = Amendment {
+        deadline: None,
+        amount: Some(60),
+        payees: Some(vec![
+            &ctx.env,
+            Payee { recipient: partner.clone(), share: PayoutShare::Fixed(40) },
+        ]),
+    };
+    ctx.contract.propose_amendment(&ctx.buyer, &transaction_id, &lower);
+    ctx.contract.vote_amendment(&ctx.seller, &transaction_id, &true);
+    assert_eq!(ctx.contract.get_transaction(&transaction_id).version, 3);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 940);
+    assert_eq!(ctx.token.balance(&ctx.contract.address), 60);
+
+    let outsider = Address::generate(&ctx.env);
+    let result = ctx.contract.try_propose_amendment(&outsider, &transaction_id, &lower);
+    assert_eq!(result, Err(Ok(ContractError::NotAuthorizedParty)));
+}
+
+#[test]
+fn test_weighted_amendment_needs_threshold() {
+    let ctx = TestContext::new();
+    let investor_a = Address::generate(&ctx.env);
+    let investor_b = Address::generate(&ctx.env);
+    let investor_c = Address::generate(&ctx.env);
+
+    let rule = weighted_rule(
+        &ctx.env,
+        &[(investor_a.clone(), 50), (investor_b.clone(), 30), (investor_c.clone(), 20)],
+        6000,
+    );
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Syndicated purchase"),
+        &rule,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    let new_deadline = ctx.env.ledger().timestamp() + 7200;
+    let amendment = Amendment { deadline: Some(new_deadline), amount: None, payees: None };
+
+    // 50% of weight is below the 60% threshold
+    ctx.contract.propose_amendment(&investor_a, &transaction_id, &amendment);
+    assert_eq!(ctx.contract.get_transaction(&transaction_id).version, 1);
+
+    ctx.contract.vote_amendment(&investor_c, &transaction_id, &true);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.version, 2);
+    assert_eq!(transaction.deadline, new_deadline);
+
+    // Rejections that leave less than 60% drop the amendment
+    let amendment = Amendment { deadline: None, amount: Some(80), payees: None };
+    ctx.contract.propose_amendment(&investor_c, &transaction_id, &amendment);
+    ctx.contract.vote_amendment(&investor_a, &transaction_id, &false);
+    assert_eq!(ctx.contract.get_pending_amendment(&transaction_id), None);
+    let result = ctx.contract.try_vote_amendment(&investor_b, &transaction_id, &true);
+    assert_eq!(result, Err(Ok(ContractError::AmendmentNotFound)));
+    assert_eq!(ctx.contract.get_transaction(&transaction_id).amount, 100);
+}
+
+#[test]
+fn test_payout_split_replaces_pending_amendment() {
+    let ctx = TestContext::new();
+    let partner = Address::generate(&ctx.env);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Test transaction"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    let amendment = Amendment { deadline: None, amount: Some(120), payees: None };
+    ctx.contract.propose_amendment(&ctx.seller, &transaction_id, &amendment);
+
+    // A split proposal replaces the pending amendment, so only the split applies
+    let payees = vec![&ctx.env, Payee { recipient: partner, share: PayoutShare::Fixed(50) }];
+    ctx.contract.propose_payout_split(&ctx.seller, &transaction_id, &payees);
+    let pending = ctx.contract.get_pending_amendment(&transaction_id).unwrap();
+    assert_eq!(pending.amendment.payees, Some(payees.clone()));
+    assert_eq!(pending.amendment.amount, None);
+
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &true);
+    assert_eq!(ctx.contract.get_pending_amendment(&transaction_id), None);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.payees, payees);
+    assert_eq!(transaction.amount, 100);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 900);
+}
+
+#[test]
+fn test_single_party_cannot_replace_payees() {
+    let ctx = TestContext::new();
+    let accomplice = Address::generate(&ctx.env);
+    let partner = Address::generate(&ctx.env);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &100,
+        &String::from_str(&ctx.env, "Test transaction"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    let agreed = vec![&ctx.env, Payee { recipient: partner.clone(), share: PayoutShare::Fixed(20) }];
+    ctx.contract.propose_payout_split(&ctx.buyer, &transaction_id, &agreed);
+    ctx.contract.vote_amendment(&ctx.seller, &transaction_id, &true);
+    ctx.contract.submit_agreement(&ctx.buyer, &transaction_id, &true, &None);
+
+    // The seller's own proposal neither changes the payees nor wipes the buyer's vote
+    let hijack = vec![&ctx.env, Payee { recipient: accomplice.clone(), share: PayoutShare::Bps(10_000) }];
+    ctx.contract.propose_payout_split(&ctx.seller, &transaction_id, &hijack);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.payees, agreed);
+    assert!(transaction.agreements.get(ctx.buyer.clone()).unwrap().agreed);
+
+    // The buyer turns it down, and the agreed split is released
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &false);
+    assert_eq!(ctx.contract.get_pending_amendment(&transaction_id), None);
+    ctx.contract.submit_agreement(&ctx.seller, &transaction_id, &true, &None);
+    ctx.contract.release_funds(&ctx.seller, &transaction_id);
+    assert_eq!(ctx.token.balance(&partner), 20);
+    assert_eq!(ctx.token.balance(&ctx.seller), 80);
+    assert_eq!(ctx.token.balance(&accomplice), 0);
+}
+
+#[test]
+fn test_crowdfunded_transaction() {
+    let ctx = TestContext::new();
+    let backer_a = Address::generate(&ctx.env);
+    let backer_b = Address::generate(&ctx.env);
+    ctx.token_admin.mint(&backer_a, &500);
+    ctx.token_admin.mint(&backer_b, &500);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &1000,
+        &String::from_str(&ctx.env, "Crowdfunded purchase"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+
+    ctx.contract.contribute(&backer_a, &transaction_id, &300);
+    ctx.contract.contribute(&backer_b, &transaction_id, &200);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Created);
+    assert_eq!(transaction.funded_amount, 500);
+    assert!(ctx.contract.get_user_transactions(&backer_a).contains(transaction_id));
+
+    let result = ctx.contract.try_contribute(&backer_b, &transaction_id, &501);
+    assert_eq!(result, Err(Ok(ContractError::ContributionExceedsRemaining)));
+    let result = ctx.contract.try_contribute(&backer_b, &transaction_id, &0);
+    assert_eq!(result, Err(Ok(ContractError::InvalidAmount)));
+
+    // The buyer covers the rest
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Funded);
+    assert_eq!(transaction.funded_amount, 1000);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 500);
+    assert_eq!(
+        ctx.contract.get_contribution(&transaction_id, &backer_a),
+        Some(Contribution { contributor: backer_a.clone(), amount: 300, refunded: 0 })
+    );
+
+    let result = ctx.contract.try_contribute(&backer_b, &transaction_id, &1);
+    assert_eq!(result, Err(Ok(ContractError::ContributionExceedsRemaining)));
+
+    // A rejection returns every contribution
+    ctx.contract.submit_agreement(&ctx.seller, &transaction_id, &false, &None);
+    assert_eq!(ctx.token.balance(&backer_a), 500);
+    assert_eq!(ctx.token.balance(&backer_b), 500);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 1000);
+    assert_eq!(ctx.token.balance(&ctx.contract.address), 0);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.funded_amount, 0);
+    assert_eq!(transaction.contributions.get(backer_b.clone()).unwrap().refunded, 200);
+}
+
+#[test]
+fn test_contributor_cap() {
+    let ctx = TestContext::new();
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &1000,
+        &String::from_str(&ctx.env, "Crowdfunded purchase"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+
+    let mut backers = Vec::new(&ctx.env);
+    for _ in 0..MAX_CONTRIBUTORS {
+        let backer = Address::generate(&ctx.env);
+        ctx.token_admin.mint(&backer, &10);
+        ctx.contract.contribute(&backer, &transaction_id, &5);
+        backers.push_back(backer);
+    }
+
+    // A new backer is turned away, existing backers can still top up
+    let latecomer = Address::generate(&ctx.env);
+    ctx.token_admin.mint(&latecomer, &10);
+    let result = ctx.contract.try_contribute(&latecomer, &transaction_id, &5);
+    assert_eq!(result, Err(Ok(ContractError::TooManyContributors)));
+    ctx.contract.contribute(&backers.get(0).unwrap(), &transaction_id, &5);
+
+    // The buyer never counts against the cap
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Funded);
+    assert_eq!(transaction.contributions.len(), MAX_CONTRIBUTORS + 1);
+}
+
+#[test]
+fn test_partial_funding_refunded_on_expiration() {
+    let ctx = TestContext::new();
+    let backer = Address::generate(&ctx.env);
+    ctx.token_admin.mint(&backer, &100);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &500,
+        &String::from_str(&ctx.env, "Crowdfunded purchase"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.contribute(&backer, &transaction_id, &100);
+
+    ctx.advance_time(3601);
+    ctx.contract.handle_expiration(&transaction_id);
+
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Refunded);
+    assert_eq!(ctx.token.balance(&backer), 100);
+    assert_eq!(ctx.token.balance(&ctx.contract.address), 0);
+}
+
+#[test]
+fn test_amount_reduction_refunds_pro_rata() {
+    let ctx = TestContext::new();
+    let backer = Address::generate(&ctx.env);
+    ctx.token_admin.mint(&backer, &300);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &400,
+        &String::from_str(&ctx.env, "Crowdfunded purchase"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.contribute(&backer, &transaction_id, &300);
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    // Price drops by 100: backer held 75% and gets 75 back, buyer gets 25
+    let amendment = Amendment { deadline: None, amount: Some(300), payees: None };
+    ctx.contract.propose_amendment(&ctx.seller, &transaction_id, &amendment);
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &true);
+
+    assert_eq!(ctx.token.balance(&backer), 75);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 925);
+    let transaction = ctx.contract.get_transaction(&transaction_id);
+    assert_eq!(transaction.funded_amount, 300);
+    assert_eq!(transaction.status, TransactionStatus::Funded);
+
+    // Expiry refunds what remains in the same proportions
+    ctx.advance_time(3601);
+    ctx.contract.handle_expiration(&transaction_id);
+    assert_eq!(ctx.token.balance(&backer), 300);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 1000);
+}
+
+#[test]
+fn test_refund_remainder_goes_to_largest_fraction() {
+    let ctx = TestContext::new();
+    let backer_a = Address::generate(&ctx.env);
+    let backer_b = Address::generate(&ctx.env);
+    ctx.token_admin.mint(&backer_a, &3);
+    ctx.token_admin.mint(&backer_b, &3);
+
+    let transaction_id = ctx.contract.create_transaction(
+        &ctx.buyer,
+        &ctx.seller,
+        &None,
+        &ctx.token.address,
+        &10,
+        &String::from_str(&ctx.env, "Crowdfunded purchase"),
+        &ConsensusRule::BuyerSellerOnly,
+        &3600,
+    );
+    ctx.contract.contribute(&backer_a, &transaction_id, &3);
+    ctx.contract.contribute(&backer_b, &transaction_id, &3);
+    ctx.contract.fund_transaction(&ctx.buyer, &transaction_id);
+
+    // Refunding 7 of 10: exact shares are 2.1, 2.1 and 2.8, so the buyer
+    // gets the leftover unit whatever the contributors' order
+    let amendment = Amendment { deadline: None, amount: Some(3), payees: None };
+    ctx.contract.propose_amendment(&ctx.seller, &transaction_id, &amendment);
+    ctx.contract.vote_amendment(&ctx.buyer, &transaction_id, &true);
+
+    assert_eq!(ctx.token.balance(&backer_a), 2);
+    assert_eq!(ctx.token.balance(&backer_b), 2);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 999);
+    assert_eq!(ctx.token.balance(&ctx.contract.address), 3);
+    assert_eq!(ctx.contract.get_transaction(&transaction_id).funded_amount, 3);
+
+    ctx.advance_time(3601);
+    ctx.contract.handle_expiration(&transaction_id);
+    assert_eq!(ctx.token.balance(&backer_a), 3);
+    assert_eq!(ctx.token.balance(&backer_b), 3);
+    assert_eq!(ctx.token.balance(&ctx.buyer), 1000);
+    assert_eq!(ctx.token.balance(&ctx.contract.address), 0);
+}
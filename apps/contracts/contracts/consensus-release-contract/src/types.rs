@@ -16,6 +16,19 @@ pub struct ConsensusTransaction {
     pub created_at: u64,
     pub agreements: Map<Address, Agreement>,
     pub required_parties: Vec<Address>,
+    pub tally: VoteTally,
+    pub payees: Vec<Payee>, // Empty pays the whole amount to the seller
+    pub version: u32,       // Bumped whenever the terms change
+    pub contributions: Map<Address, Contribution>,
+    pub funded_amount: i128, // Currently held in escrow across all contributors
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Contribution {
+    pub contributor: Address,
+    pub amount: i128,   // Total deposited
+    pub refunded: i128, // Total returned through refunds
 }
 
 #[contracttype]
@@ -36,6 +49,44 @@ pub enum ConsensusRule {
     Majority,           // More than 50% must agree
     BuyerSellerOnly,    // Only buyer and seller need to agree (ignores arbitrator)
     WithArbitrator,     // Buyer, seller, and arbitrator must all agree
+    Weighted(WeightedRule), // Listed parties vote by weight against a threshold
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeightedParty {
+    pub party: Address,
+    pub weight: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeightedRule {
+    pub parties: Vec<WeightedParty>,
+    pub threshold_bps: u32, // Share of total weight needed, e.g. 6000 = 60%
+}
+
+/// Running vote totals. Non-weighted rules count every required party as weight 1.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoteTally {
+    pub total_weight: u32,
+    pub agreed_weight: u32,
+    pub rejected_weight: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PayoutShare {
+    Fixed(i128), // Exact token amount
+    Bps(u32),    // Basis points of the transaction amount
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Payee {
+    pub recipient: Address,
+    pub share: PayoutShare,
 }
 
 #[contracttype]
@@ -45,12 +96,34 @@ pub struct Agreement {
     pub agreed: bool,
     pub timestamp: u64,
     pub reason: Option<soroban_sdk::String>,
+    pub version: u32, // Terms version the vote was cast on
+}
+
+/// Changed terms proposed by a party; fields left as `None` stay as they are.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Amendment {
+    pub deadline: Option<u64>,
+    pub amount: Option<i128>,
+    pub payees: Option<Vec<Payee>>,
+}
+
+/// An amendment awaiting approval. It takes effect once the approvals reach
+/// consensus under the transaction's rule.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAmendment {
+    pub proposer: Address,
+    pub amendment: Amendment,
+    pub base_version: u32, // Terms version it amends; stale once that changes
+    pub approvals: Map<Address, Agreement>,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
     Transaction(u64),
+    PendingAmendment(u64),
     TransactionCounter,
     UserTransactions(Address),
     Admin,
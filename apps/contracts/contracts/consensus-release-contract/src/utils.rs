@@ -1,5 +1,15 @@
-use crate::types::{Agreement, ConsensusRule, ConsensusTransaction, TransactionStatus};
-use soroban_sdk::{Address, Env, Map};
+use crate::error::ContractError;
+use crate::types::{
+    Agreement, ConsensusRule, ConsensusTransaction, Contribution, Payee, PayoutShare,
+    TransactionStatus, VoteTally, WeightedRule,
+};
+use soroban_sdk::{Address, Env, Map, Vec};
+
+pub const BASIS_POINTS: u32 = 10_000;
+
+/// Most contributors besides the buyer on one transaction, which keeps the
+/// stored transaction and the refund loop bounded. The buyer can always fund.
+pub const MAX_CONTRIBUTORS: u32 = 20;
 
 pub fn create_consensus_transaction(
     env: &Env,
@@ -15,12 +25,22 @@ pub fn create_consensus_transaction(
 ) -> ConsensusTransaction {
     let mut required_parties = soroban_sdk::vec![env, buyer.clone(), seller.clone()];
     
-    if let Some(ref arb) = arbitrator {
+    if let ConsensusRule::Weighted(ref rule) = consensus_rule {
+        required_parties = soroban_sdk::Vec::new(env);
+        for weighted in rule.parties.iter() {
+            required_parties.push_back(weighted.party);
+        }
+    } else if let Some(ref arb) = arbitrator {
         if consensus_rule == ConsensusRule::WithArbitrator || consensus_rule == ConsensusRule::Unanimous {
             required_parties.push_back(arb.clone());
         }
     }
 
+    let total_weight = required_parties
+        .iter()
+        .map(|party| party_weight(&consensus_rule, &party))
+        .sum();
+
     ConsensusTransaction {
         transaction_id,
         buyer,
@@ -35,6 +55,205 @@ pub fn create_consensus_transaction(
         created_at: env.ledger().timestamp(),
         agreements: Map::new(env),
         required_parties,
+        tally: VoteTally {
+            total_weight,
+            agreed_weight: 0,
+            rejected_weight: 0,
+        },
+        payees: Vec::new(env),
+        version: 1,
+        contributions: Map::new(env),
+        funded_amount: 0,
+    }
+}
+
+pub fn record_contribution(transaction: &mut ConsensusTransaction, contributor: &Address, amount: i128) {
+    let mut contribution = transaction.contributions.get(contributor.clone()).unwrap_or(Contribution {
+        contributor: contributor.clone(),
+        amount: 0,
+        refunded: 0,
+    });
+    contribution.amount += amount;
+    transaction.contributions.set(contributor.clone(), contribution);
+    transaction.funded_amount += amount;
+}
+
+/// Splits `refund_amount` across contributors in proportion to what each still
+/// has in escrow. Shares are rounded down and the units left over go one each
+/// to the contributors with the largest fractional parts, so the shares sum
+/// exactly to `refund_amount` without favouring any position in the map.
+pub fn pro_rata_refunds(env: &Env, transaction: &ConsensusTransaction, refund_amount: i128) -> Vec<(Address, i128)> {
+    let mut shares: Vec<(Address, i128, i128)> = Vec::new(env);
+    let mut allocated = 0;
+    for (contributor, contribution) in transaction.contributions.iter() {
+        let scaled = refund_amount * (contribution.amount - contribution.refunded);
+        let share = scaled / transaction.funded_amount;
+        allocated += share;
+        shares.push_back((contributor, share, scaled % transaction.funded_amount));
+    }
+
+    // Fewer units are left over than there are contributors
+    for _ in 0..refund_amount - allocated {
+        let mut largest = 0;
+        for (i, (_, _, fraction)) in shares.iter().enumerate() {
+            if fraction > shares.get_unchecked(largest as u32).2 {
+                largest = i;
+            }
+        }
+        let (contributor, share, _) = shares.get_unchecked(largest as u32);
+        shares.set(largest as u32, (contributor, share + 1, -1));
+    }
+
+    let mut refunds = Vec::new(env);
+    for (contributor, share, _) in shares.iter() {
+        if share > 0 {
+            refunds.push_back((contributor, share));
+        }
+    }
+    refunds
+}
+
+fn payout_amount(share: &PayoutShare, total: i128) -> i128 {
+    match share {
+        PayoutShare::Fixed(amount) => *amount,
+        PayoutShare::Bps(bps) => total * *bps as i128 / BASIS_POINTS as i128,
+    }
+}
+
+/// Checks that every share is positive and that the split never pays out more
+/// than `amount`. Whatever the split leaves unallocated goes to the seller.
+pub fn validate_payout_split(payees: &Vec<Payee>, amount: i128) -> Result<(), ContractError> {
+    if payees.is_empty() {
+        return Err(ContractError::InvalidPayoutSplit);
+    }
+
+    let mut allocated: i128 = 0;
+    for payee in payees.iter() {
+        let valid = match payee.share {
+            PayoutShare::Fixed(value) => value > 0,
+            PayoutShare::Bps(bps) => bps > 0 && bps <= BASIS_POINTS,
+        };
+        if !valid {
+            return Err(ContractError::InvalidPayoutSplit);
+        }
+        allocated = allocated
+            .checked_add(payout_amount(&payee.share, amount))
+            .ok_or(ContractError::InvalidPayoutSplit)?;
+    }
+
+    if allocated > amount {
+        return Err(ContractError::InvalidPayoutSplit);
+    }
+    Ok(())
+}
+
+/// Resolves the payout split into per-recipient amounts, with the unallocated
+/// remainder (including bps rounding dust) paid to the seller.
+pub fn compute_payouts(env: &Env, transaction: &ConsensusTransaction) -> Vec<(Address, i128)> {
+    let mut payouts = Vec::new(env);
+    let mut remainder = transaction.amount;
+    for payee in transaction.payees.iter() {
+        let amount = payout_amount(&payee.share, transaction.amount);
+        remainder -= amount;
+        payouts.push_back((payee.recipient, amount));
+    }
+    if remainder > 0 {
+        payouts.push_back((transaction.seller.clone(), remainder));
+    }
+    payouts
+}
+
+/// Moves the transaction to a new terms version. Votes cast on the previous
+/// version no longer apply, so they are cleared.
+pub fn bump_version(env: &Env, transaction: &mut ConsensusTransaction) {
+    transaction.version += 1;
+    transaction.agreements = Map::new(env);
+    transaction.tally = compute_tally(transaction);
+}
+
+/// The transaction as it would stand with `approvals` as its votes, so votes
+/// on an amendment are counted under the same rule as votes on the terms.
+pub fn amendment_ballot(
+    transaction: &ConsensusTransaction,
+    approvals: &Map<Address, Agreement>,
+) -> ConsensusTransaction {
+    let mut ballot = transaction.clone();
+    ballot.agreements = approvals.clone();
+    ballot.tally = compute_tally(&ballot);
+    ballot
+}
+
+/// Whether the terms are still open for votes and amendments.
+pub fn is_negotiable(transaction: &ConsensusTransaction) -> bool {
+    transaction.status == TransactionStatus::Created || transaction.status == TransactionStatus::Funded
+}
+
+/// Checks a weighted rule: at least one party, positive weights, no repeats,
+/// a threshold within (0, 100%] and a total weight that fits in a `u32`.
+pub fn validate_weighted_rule(rule: &WeightedRule) -> Result<(), ContractError> {
+    if rule.parties.is_empty() || rule.threshold_bps == 0 || rule.threshold_bps > BASIS_POINTS {
+        return Err(ContractError::InvalidConsensusRule);
+    }
+
+    let mut total: u32 = 0;
+    for (i, weighted) in rule.parties.iter().enumerate() {
+        if weighted.weight == 0 {
+            return Err(ContractError::InvalidConsensusRule);
+        }
+        if rule.parties.iter().skip(i + 1).any(|other| other.party == weighted.party) {
+            return Err(ContractError::DuplicateParties);
+        }
+        total = total
+            .checked_add(weighted.weight)
+            .ok_or(ContractError::InvalidConsensusRule)?;
+    }
+    Ok(())
+}
+
+/// Voting weight of a required party; 1 under every rule except `Weighted`.
+pub fn party_weight(rule: &ConsensusRule, party: &Address) -> u32 {
+    match rule {
+        ConsensusRule::Weighted(rule) => rule
+            .parties
+            .iter()
+            .find(|weighted| weighted.party == *party)
+            .map_or(0, |weighted| weighted.weight),
+        _ => 1,
+    }
+}
+
+pub fn compute_tally(transaction: &ConsensusTransaction) -> VoteTally {
+    let mut tally = VoteTally {
+        total_weight: transaction.tally.total_weight,
+        agreed_weight: 0,
+        rejected_weight: 0,
+    };
+    for party in transaction.required_parties.iter() {
+        if let Some(agreement) = transaction.agreements.get(party.clone()) {
+            let weight = party_weight(&transaction.consensus_rule, &party);
+            if agreement.agreed {
+                tally.agreed_weight += weight;
+            } else {
+                tally.rejected_weight += weight;
+            }
+        }
+    }
+    tally
+}
+
+fn meets_threshold(weight: u32, total_weight: u32, threshold_bps: u32) -> bool {
+    weight as u64 * BASIS_POINTS as u64 >= threshold_bps as u64 * total_weight as u64
+}
+
+/// Whether the weight not yet rejected could still reach the threshold.
+pub fn can_reach_consensus(transaction: &ConsensusTransaction) -> bool {
+    match transaction.consensus_rule {
+        ConsensusRule::Weighted(ref rule) => meets_threshold(
+            transaction.tally.total_weight - transaction.tally.rejected_weight,
+            transaction.tally.total_weight,
+            rule.threshold_bps,
+        ),
+        _ => transaction.tally.rejected_weight == 0,
     }
 }
 
@@ -50,6 +269,7 @@ pub fn add_agreement(
         agreed,
         timestamp,
         reason,
+        version: transaction.version,
     };
     transaction.agreements.set(party, agreement);
 }
@@ -83,6 +303,11 @@ pub fn has_consensus(transaction: &ConsensusTransaction) -> bool {
                 false // Can't have WithArbitrator rule without an arbitrator
             }
         }
+        ConsensusRule::Weighted(ref rule) => meets_threshold(
+            transaction.tally.agreed_weight,
+            transaction.tally.total_weight,
+            rule.threshold_bps,
+        ),
     }
 }
 
@@ -1,15 +1,37 @@
+use crate::error::Error;
 use crate::types::DataKey;
 use soroban_sdk::{Address, Env};
 
-pub fn initialize(env: Env, admin: Address) {
+pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+    if env.storage().instance().has(&DataKey::Admin) {
+        return Err(Error::AlreadyInitialized);
+    }
     admin.require_auth();
     env.storage().instance().set(&DataKey::Admin, &admin);
     env.storage()
         .instance()
         .set(&DataKey::TotalTransactions, &0u128);
+    Ok(())
 }
 
-pub fn is_admin(env: &Env, address: &Address) -> bool {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-    admin == *address
+/// Fails unless `address` is the configured admin.
+pub fn require_admin(env: &Env, address: &Address) -> Result<(), Error> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)?;
+    if admin != *address {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
+
+pub fn set_token_contract(env: Env, admin: Address, token_contract: Address) -> Result<(), Error> {
+    admin.require_auth();
+    require_admin(&env, &admin)?;
+    env.storage()
+        .instance()
+        .set(&DataKey::TokenContract, &token_contract);
+    Ok(())
 }
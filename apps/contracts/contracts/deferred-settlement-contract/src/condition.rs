@@ -0,0 +1,121 @@
+use crate::error::Error;
+use crate::oracle::has_oracle_quorum;
+use crate::types::{ConditionNode, DeferredTransaction, SettlementCondition};
+use soroban_sdk::{Address, Env, Vec};
+
+pub const MAX_CONDITION_NODES: u32 = 16;
+
+/// Checks that the expression is a well-formed tree rooted at node 0. Every
+/// child index must point further down the list, which rules out cycles.
+pub fn validate_condition(condition: &Vec<ConditionNode>) -> Result<(), Error> {
+    if condition.is_empty() || condition.len() > MAX_CONDITION_NODES {
+        return Err(Error::InvalidCondition);
+    }
+
+    for (index, node) in condition.iter().enumerate() {
+        match node {
+            ConditionNode::Leaf(_) => {}
+            ConditionNode::And(children) | ConditionNode::Or(children) => {
+                if children.is_empty() {
+                    return Err(Error::InvalidCondition);
+                }
+                for child in children.iter() {
+                    if child as usize <= index || child >= condition.len() {
+                        return Err(Error::InvalidCondition);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn leaf_holds(
+    env: &Env,
+    transaction: &DeferredTransaction,
+    leaf: &SettlementCondition,
+    caller: &Address,
+) -> bool {
+    let now = env.ledger().timestamp();
+    match leaf {
+        SettlementCondition::TimeBased => now >= transaction.deadline,
+        SettlementCondition::TimeElapsed(delay) => {
+            now >= transaction.created_at.saturating_add(*delay)
+        }
+        SettlementCondition::BuyerApproval => *caller == transaction.buyer,
+        SettlementCondition::OracleConfirmation => has_oracle_quorum(env, transaction.id),
+    }
+}
+
+/// Records every leaf that holds for this call and returns the indices that
+/// were newly satisfied. Satisfied leaves stay satisfied, so a buyer approval
+/// given today still counts once a later time leaf comes due.
+pub fn record_satisfied_leaves(
+    env: &Env,
+    transaction: &mut DeferredTransaction,
+    caller: &Address,
+) -> Vec<u32> {
+    let mut newly_satisfied = Vec::new(env);
+    for (index, node) in transaction.condition.iter().enumerate() {
+        let index = index as u32;
+        if let ConditionNode::Leaf(leaf) = node {
+            if !transaction.satisfied_leaves.contains(index)
+                && leaf_holds(env, transaction, &leaf, caller)
+            {
+                newly_satisfied.push_back(index);
+            }
+        }
+    }
+    for index in newly_satisfied.iter() {
+        transaction.satisfied_leaves.push_back(index);
+    }
+    newly_satisfied
+}
+
+/// Evaluates the whole expression against the recorded leaves, bottom-up.
+pub fn is_satisfied(env: &Env, transaction: &DeferredTransaction) -> bool {
+    let len = transaction.condition.len();
+    let mut values: Vec<bool> = Vec::new(env);
+    for _ in 0..len {
+        values.push_back(false);
+    }
+
+    for index in (0..len).rev() {
+        let value = match transaction.condition.get_unchecked(index) {
+            ConditionNode::Leaf(_) => transaction.satisfied_leaves.contains(index),
+            ConditionNode::And(children) => children.iter().all(|c| values.get_unchecked(c)),
+            ConditionNode::Or(children) => children.iter().any(|c| values.get_unchecked(c)),
+        };
+        values.set(index, value);
+    }
+    values.get_unchecked(0)
+}
+
+/// Whether a permissionless `verify_condition` call could settle the
+/// transaction right now, ignoring leaves that need the buyer's signature.
+pub fn can_settle_now(env: &Env, transaction: &DeferredTransaction) -> bool {
+    let mut probe = transaction.clone();
+    record_satisfied_leaves(env, &mut probe, &env.current_contract_address());
+    is_satisfied(env, &probe)
+}
+
+/// Whether the transaction settles on its recorded leaves plus the time
+/// leaves that have come due. Makes no oracle calls, so views can use it.
+pub fn settles_on_time(env: &Env, transaction: &DeferredTransaction) -> bool {
+    let mut probe = transaction.clone();
+    let caller = env.current_contract_address();
+    for (index, node) in transaction.condition.iter().enumerate() {
+        let index = index as u32;
+        if let ConditionNode::Leaf(
+            leaf @ (SettlementCondition::TimeBased | SettlementCondition::TimeElapsed(_)),
+        ) = node
+        {
+            if !probe.satisfied_leaves.contains(index)
+                && leaf_holds(env, transaction, &leaf, &caller)
+            {
+                probe.satisfied_leaves.push_back(index);
+            }
+        }
+    }
+    is_satisfied(env, &probe)
+}
@@ -1,14 +1,13 @@
-use crate::types::{DataKey, DeferredTransaction, Error, TransactionEvent, TransactionStatus};
+use crate::error::Error;
+use crate::index;
+use crate::storage;
+use crate::types::{TransactionEvent, TransactionStatus};
 use soroban_sdk::{token, Address, Env, Symbol};
 
 pub fn initiate_dispute(env: Env, caller: Address, transaction_id: u128) -> Result<(), Error> {
     caller.require_auth();
 
-    let mut transaction: DeferredTransaction = env
-        .storage()
-        .instance()
-        .get(&DataKey::Transaction(transaction_id))
-        .ok_or(Error::TransactionNotFound)?;
+    let mut transaction = storage::get_transaction(&env, transaction_id)?;
 
     if transaction.status != TransactionStatus::Pending {
         return Err(Error::NotPending);
@@ -19,9 +18,8 @@ pub fn initiate_dispute(env: Env, caller: Address, transaction_id: u128) -> Resu
     }
 
     transaction.status = TransactionStatus::Disputed;
-    env.storage()
-        .instance()
-        .set(&DataKey::Transaction(transaction_id), &transaction);
+    storage::set_transaction(&env, &transaction);
+    index::move_status(&env, &transaction, &TransactionStatus::Pending);
 
     env.events().publish(
         ("DeferredSettlement", Symbol::new(&env, "dispute_initiated")),
@@ -39,11 +37,7 @@ pub fn resolve_dispute(
 ) -> Result<(), Error> {
     caller.require_auth();
 
-    let mut transaction: DeferredTransaction = env
-        .storage()
-        .instance()
-        .get(&DataKey::Transaction(transaction_id))
-        .ok_or(Error::TransactionNotFound)?;
+    let mut transaction = storage::get_transaction(&env, transaction_id)?;
 
     if transaction.status != TransactionStatus::Disputed {
         return Err(Error::NotPending);
@@ -53,13 +47,8 @@ pub fn resolve_dispute(
         return Err(Error::Unauthorized);
     }
 
-    // Get the token contract ID from storage or environment
-    let token_contract_id = env
-        .storage()
-        .instance()
-        .get(&DataKey::TokenContract)
-        .ok_or(Error::TokenContractNotSet)?;
-    let token_client = token::Client::new(&env, &token_contract_id);
+    // Always pay out in the asset the transaction was funded with
+    let token_client = token::Client::new(&env, &transaction.token);
 
     if release_to_seller {
         token_client.transfer(
@@ -77,9 +66,8 @@ pub fn resolve_dispute(
         transaction.status = TransactionStatus::Refunded;
     }
 
-    env.storage()
-        .instance()
-        .set(&DataKey::Transaction(transaction_id), &transaction);
+    storage::set_transaction(&env, &transaction);
+    index::move_status(&env, &transaction, &TransactionStatus::Disputed);
 
     env.events().publish(
         ("DeferredSettlement", Symbol::new(&env, "dispute_resolved")),
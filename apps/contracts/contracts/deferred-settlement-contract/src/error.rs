@@ -0,0 +1,28 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    // Initialization Errors
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    Unauthorized = 3,
+
+    // Transaction Errors
+    InvalidAmount = 10,
+    TransactionNotFound = 11,
+    NotPending = 12,
+    InvalidCondition = 13,
+    ConditionNotMet = 14,
+    RefundNotAvailable = 15,
+
+    // Token Errors
+    TokenContractNotSet = 20,
+    InvalidToken = 21,
+
+    // Oracle Errors
+    OracleAlreadyRegistered = 30,
+    OracleNotRegistered = 31,
+    InvalidOracleConfig = 32,
+}
@@ -0,0 +1,210 @@
+use crate::storage::{TRANSACTION_BUMP_AMOUNT, TRANSACTION_LIFETIME_THRESHOLD};
+use crate::types::{
+    ConditionNode, DataKey, DeferredTransaction, IndexKey, SettlementCondition, TransactionStatus,
+};
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val, Vec};
+
+/// Number of transaction ids kept in a single index page.
+pub const INDEX_PAGE_SIZE: u32 = 20;
+
+/// Width of a due bucket. TimeBased transactions are indexed by
+/// `deadline / DUE_BUCKET_SECONDS`, so buckets are ordered by deadline.
+pub const DUE_BUCKET_SECONDS: u64 = 86400;
+
+/// Most empty buckets skipped when the lowest due bucket empties.
+const MAX_DUE_START_ADVANCE: u32 = 30;
+
+fn get<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        env.storage().persistent().extend_ttl(
+            key,
+            TRANSACTION_LIFETIME_THRESHOLD,
+            TRANSACTION_BUMP_AMOUNT,
+        );
+    }
+    value
+}
+
+fn set<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage().persistent().extend_ttl(
+        key,
+        TRANSACTION_LIFETIME_THRESHOLD,
+        TRANSACTION_BUMP_AMOUNT,
+    );
+}
+
+pub fn count(env: &Env, index: &IndexKey) -> u32 {
+    get(env, &DataKey::IndexCount(index.clone())).unwrap_or(0)
+}
+
+fn set_count(env: &Env, index: &IndexKey, count: u32) {
+    let key = DataKey::IndexCount(index.clone());
+    if count == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        set(env, &key, &count);
+    }
+}
+
+pub fn page(env: &Env, index: &IndexKey, page: u32) -> Vec<u128> {
+    get(env, &DataKey::IndexPage(index.clone(), page)).unwrap_or(Vec::new(env))
+}
+
+fn set_page(env: &Env, index: &IndexKey, page: u32, ids: &Vec<u128>) {
+    let key = DataKey::IndexPage(index.clone(), page);
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        set(env, &key, ids);
+    }
+}
+
+fn add(env: &Env, index: &IndexKey, transaction_id: u128) {
+    let position = count(env, index);
+    let page_number = position / INDEX_PAGE_SIZE;
+
+    let mut ids = page(env, index, page_number);
+    ids.push_back(transaction_id);
+    set_page(env, index, page_number, &ids);
+
+    set(
+        env,
+        &DataKey::IndexPosition(index.clone(), transaction_id),
+        &position,
+    );
+    set_count(env, index, position + 1);
+}
+
+/// Swap-removes the id, moving the last entry into its slot. Returns false
+/// if the id was not in the index.
+fn remove(env: &Env, index: &IndexKey, transaction_id: u128) -> bool {
+    let position_key = DataKey::IndexPosition(index.clone(), transaction_id);
+    let position: u32 = match get(env, &position_key) {
+        Some(position) => position,
+        None => return false,
+    };
+
+    let last_position = count(env, index) - 1;
+    let last_page = last_position / INDEX_PAGE_SIZE;
+    let mut last_ids = page(env, index, last_page);
+    let last_id = last_ids.pop_back().unwrap();
+
+    if position != last_position {
+        let page_number = position / INDEX_PAGE_SIZE;
+        let slot = position % INDEX_PAGE_SIZE;
+        if page_number == last_page {
+            last_ids.set(slot, last_id);
+        } else {
+            let mut ids = page(env, index, page_number);
+            ids.set(slot, last_id);
+            set_page(env, index, page_number, &ids);
+        }
+        set(
+            env,
+            &DataKey::IndexPosition(index.clone(), last_id),
+            &position,
+        );
+    }
+
+    set_page(env, index, last_page, &last_ids);
+    env.storage().persistent().remove(&position_key);
+    set_count(env, index, last_position);
+    true
+}
+
+/// Reads up to `limit` ids starting at `offset`.
+pub fn read(env: &Env, index: &IndexKey, offset: u32, limit: u32) -> Vec<u128> {
+    let mut ids = Vec::new(env);
+    let end = offset.saturating_add(limit).min(count(env, index));
+    let mut position = offset;
+    while position < end {
+        let entries = page(env, index, position / INDEX_PAGE_SIZE);
+        let mut slot = position % INDEX_PAGE_SIZE;
+        while slot < entries.len() && position < end {
+            ids.push_back(entries.get_unchecked(slot));
+            slot += 1;
+            position += 1;
+        }
+    }
+    ids
+}
+
+pub fn due_bucket(deadline: u64) -> u64 {
+    deadline / DUE_BUCKET_SECONDS
+}
+
+pub fn get_due_start(env: &Env) -> Option<u64> {
+    get(env, &DataKey::DueStart)
+}
+
+fn is_time_based(transaction: &DeferredTransaction) -> bool {
+    transaction
+        .condition
+        .contains(ConditionNode::Leaf(SettlementCondition::TimeBased))
+}
+
+fn add_due(env: &Env, transaction: &DeferredTransaction) {
+    let bucket = due_bucket(transaction.deadline);
+    add(env, &IndexKey::Due(bucket), transaction.id);
+    if get_due_start(env).is_none_or(|start| bucket < start) {
+        set(env, &DataKey::DueStart, &bucket);
+    }
+}
+
+fn remove_due(env: &Env, transaction: &DeferredTransaction) {
+    let bucket = due_bucket(transaction.deadline);
+    if !remove(env, &IndexKey::Due(bucket), transaction.id) {
+        return;
+    }
+
+    // Skip the start past buckets that are now empty, a bounded number at a time
+    if get_due_start(env) != Some(bucket) {
+        return;
+    }
+    let mut start = bucket;
+    let mut advanced = 0;
+    while advanced < MAX_DUE_START_ADVANCE && count(env, &IndexKey::Due(start)) == 0 {
+        start += 1;
+        advanced += 1;
+    }
+    set(env, &DataKey::DueStart, &start);
+}
+
+/// Adds a new transaction to the buyer, seller and status indexes, and to
+/// the due index while it is a pending TimeBased transaction.
+pub fn index_transaction(env: &Env, transaction: &DeferredTransaction) {
+    add(
+        env,
+        &IndexKey::Buyer(transaction.buyer.clone()),
+        transaction.id,
+    );
+    add(
+        env,
+        &IndexKey::Seller(transaction.seller.clone()),
+        transaction.id,
+    );
+    add(
+        env,
+        &IndexKey::Status(transaction.status.clone()),
+        transaction.id,
+    );
+    if transaction.status == TransactionStatus::Pending && is_time_based(transaction) {
+        add_due(env, transaction);
+    }
+}
+
+/// Moves a transaction from the `from` status index to the one for its
+/// current status. Call on every status change.
+pub fn move_status(env: &Env, transaction: &DeferredTransaction, from: &TransactionStatus) {
+    remove(env, &IndexKey::Status(from.clone()), transaction.id);
+    add(
+        env,
+        &IndexKey::Status(transaction.status.clone()),
+        transaction.id,
+    );
+    if *from == TransactionStatus::Pending && is_time_based(transaction) {
+        remove_due(env, transaction);
+    }
+}
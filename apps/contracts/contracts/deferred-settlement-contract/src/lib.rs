@@ -1,35 +1,76 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
+use soroban_sdk::{contract, contractimpl, vec, Address, Env, Symbol, Vec};
 
 mod admin;
+mod condition;
 mod dispute;
+mod error;
+mod index;
+mod oracle;
 mod query;
 mod settlement;
+mod storage;
 mod transaction;
 mod types;
 
+#[cfg(test)]
+mod mock_oracle;
 #[cfg(test)]
 mod test;
 
-use types::{DataKey, DeferredTransaction, SettlementCondition};
+pub use error::Error;
+pub use oracle::{OracleClient, OracleInterface};
+pub use types::OracleAttestation;
+use types::{
+    ConditionNode, DeferredTransaction, DuePage, OracleConfig, SettlementCondition,
+    TransactionStatus,
+};
 
 #[contract]
 pub struct DeferredSettlementContract;
 
 #[contractimpl]
 impl DeferredSettlementContract {
-    pub fn initialize(env: Env, admin: Address) {
-        admin::initialize(env, admin);
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        admin::initialize(env, admin)
+    }
+
+    /// Sets the default token for transactions created without an explicit
+    /// token. Existing transactions keep the token they were created with.
+    pub fn set_token_contract(
+        env: Env,
+        admin: Address,
+        token_contract: Address,
+    ) -> Result<(), Error> {
+        admin::set_token_contract(env, admin, token_contract)
+    }
+
+    pub fn register_oracle(env: Env, admin: Address, oracle: Address) -> Result<(), Error> {
+        oracle::register_oracle(env, admin, oracle)
+    }
+
+    pub fn remove_oracle(env: Env, admin: Address, oracle: Address) -> Result<(), Error> {
+        oracle::remove_oracle(env, admin, oracle)
+    }
+
+    pub fn set_oracle_config(
+        env: Env,
+        admin: Address,
+        quorum: u32,
+        max_staleness: u64,
+    ) -> Result<(), Error> {
+        oracle::set_oracle_config(env, admin, quorum, max_staleness)
     }
 
-    pub fn set_token_contract(env: Env, admin: Address, token_contract: Address) {
-        admin.require_auth();
-        if !admin::is_admin(&env, &admin) {
-            panic!("Unauthorized");
-        }
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenContract, &token_contract);
+    /// Moves transactions stored by earlier versions of the contract out of
+    /// instance storage, in batches of ids up to `get_total_transactions`.
+    pub fn migrate_transactions(
+        env: Env,
+        admin: Address,
+        start_id: u128,
+        limit: u32,
+    ) -> Result<u32, Error> {
+        storage::migrate_transactions(env, admin, start_id, limit)
     }
 
     pub fn create_transaction(
@@ -39,7 +80,8 @@ impl DeferredSettlementContract {
         amount: i128,
         condition: Symbol,
         duration: u64,
-    ) -> u128 {
+        token: Option<Address>,
+    ) -> Result<u128, Error> {
         let condition = match condition {
             condition if condition == Symbol::new(&env, "TimeBased") => {
                 SettlementCondition::TimeBased
@@ -50,22 +92,46 @@ impl DeferredSettlementContract {
             condition if condition == Symbol::new(&env, "OracleConfirmation") => {
                 SettlementCondition::OracleConfirmation
             }
-            _ => panic!("Invalid condition"),
+            _ => return Err(Error::InvalidCondition),
         };
-        transaction::create_transaction(env, buyer, seller, amount, condition, duration).unwrap()
+        let condition = vec![&env, ConditionNode::Leaf(condition)];
+        transaction::create_transaction(env, buyer, seller, amount, condition, duration, token)
     }
 
-    pub fn verify_condition(
+    /// Creates a transaction that settles once a composite AND/OR expression
+    /// holds. `condition[0]` is the root; combinators list child indices.
+    pub fn create_composite_transaction(
         env: Env,
-        caller: Address,
-        transaction_id: u128,
-        oracle_input: Option<bool>,
-    ) {
-        settlement::verify_condition(env, caller, transaction_id, oracle_input).unwrap()
+        buyer: Address,
+        seller: Address,
+        amount: i128,
+        condition: Vec<ConditionNode>,
+        duration: u64,
+        token: Option<Address>,
+    ) -> Result<u128, Error> {
+        transaction::create_transaction(env, buyer, seller, amount, condition, duration, token)
+    }
+
+    pub fn verify_condition(env: Env, caller: Address, transaction_id: u128) -> Result<(), Error> {
+        settlement::verify_condition(env, caller, transaction_id)
     }
 
-    pub fn initiate_dispute(env: Env, caller: Address, transaction_id: u128) {
-        dispute::initiate_dispute(env, caller, transaction_id).unwrap()
+    /// Refunds the buyer once the deadline plus grace period has passed
+    /// without the transaction settling. Callable by anyone.
+    pub fn claim_refund(env: Env, transaction_id: u128) -> Result<(), Error> {
+        settlement::claim_refund(env, transaction_id)
+    }
+
+    pub fn set_refund_grace_period(
+        env: Env,
+        admin: Address,
+        grace_period: u64,
+    ) -> Result<(), Error> {
+        settlement::set_refund_grace_period(env, admin, grace_period)
+    }
+
+    pub fn initiate_dispute(env: Env, caller: Address, transaction_id: u128) -> Result<(), Error> {
+        dispute::initiate_dispute(env, caller, transaction_id)
     }
 
     pub fn resolve_dispute(
@@ -73,15 +139,67 @@ impl DeferredSettlementContract {
         caller: Address,
         transaction_id: u128,
         release_to_seller: bool,
-    ) {
-        dispute::resolve_dispute(env, caller, transaction_id, release_to_seller).unwrap()
+    ) -> Result<(), Error> {
+        dispute::resolve_dispute(env, caller, transaction_id, release_to_seller)
     }
 
-    pub fn get_transaction(env: Env, transaction_id: u128) -> DeferredTransaction {
-        query::get_transaction(env, transaction_id).unwrap()
+    pub fn get_transaction(env: Env, transaction_id: u128) -> Result<DeferredTransaction, Error> {
+        query::get_transaction(env, transaction_id)
     }
 
     pub fn get_total_transactions(env: Env) -> u128 {
         query::get_total_transactions(env)
     }
+
+    /// Ids of transactions where `buyer` is the buyer, oldest first.
+    pub fn get_buyer_transactions(env: Env, buyer: Address, offset: u32, limit: u32) -> Vec<u128> {
+        query::get_buyer_transactions(env, buyer, offset, limit)
+    }
+
+    /// Ids of transactions where `seller` is the seller, oldest first.
+    pub fn get_seller_transactions(
+        env: Env,
+        seller: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<u128> {
+        query::get_seller_transactions(env, seller, offset, limit)
+    }
+
+    /// Pages through transactions currently in `status`. Pages hold at most
+    /// `MAX_PAGE_SIZE` entries. Entries are swap-removed on status changes, so
+    /// the order is not stable.
+    pub fn get_transactions_by_status(
+        env: Env,
+        status: TransactionStatus,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<DeferredTransaction> {
+        query::get_transactions_by_status(env, status, offset, limit)
+    }
+
+    /// Pending TimeBased transactions past their deadline that a keeper can
+    /// settle with `verify_condition`, in deadline-bucket order. Start with
+    /// `bucket` None and pass the returned `next_bucket` and `next_position`
+    /// back to continue.
+    pub fn get_due_transactions(
+        env: Env,
+        bucket: Option<u64>,
+        position: u32,
+        limit: u32,
+    ) -> DuePage {
+        query::get_due_transactions(env, bucket, position, limit)
+    }
+
+    pub fn get_refund_grace_period(env: Env) -> u64 {
+        settlement::get_refund_grace_period(&env)
+    }
+
+    pub fn get_oracles(env: Env) -> Vec<Address> {
+        oracle::get_oracles(&env)
+    }
+
+    pub fn get_oracle_config(env: Env) -> OracleConfig {
+        oracle::get_oracle_config(&env)
+    }
 }
@@ -0,0 +1,34 @@
+//! Minimal oracle implementing `OracleInterface`, used by the test suite.
+
+use crate::oracle::OracleInterface;
+use crate::types::OracleAttestation;
+use soroban_sdk::{contract, contractimpl, Env};
+
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_attestation(env: Env, transaction_id: u128, confirmed: bool, timestamp: u64) {
+        env.storage().instance().set(
+            &transaction_id,
+            &OracleAttestation {
+                confirmed,
+                timestamp,
+            },
+        );
+    }
+}
+
+#[contractimpl]
+impl OracleInterface for MockOracle {
+    fn attest(env: Env, transaction_id: u128) -> OracleAttestation {
+        env.storage()
+            .instance()
+            .get(&transaction_id)
+            .unwrap_or(OracleAttestation {
+                confirmed: false,
+                timestamp: 0,
+            })
+    }
+}
@@ -0,0 +1,100 @@
+use crate::admin::require_admin;
+use crate::error::Error;
+use crate::types::{DataKey, OracleAttestation, OracleConfig};
+use soroban_sdk::{contractclient, Address, Env, Vec};
+
+pub const DEFAULT_ORACLE_QUORUM: u32 = 1;
+pub const DEFAULT_MAX_STALENESS: u64 = 86400; // 1 day
+
+/// Interface every registered oracle contract must implement.
+#[contractclient(name = "OracleClient")]
+pub trait OracleInterface {
+    fn attest(env: Env, transaction_id: u128) -> OracleAttestation;
+}
+
+pub fn get_oracles(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Oracles)
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn get_oracle_config(env: &Env) -> OracleConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::OracleConfig)
+        .unwrap_or(OracleConfig {
+            quorum: DEFAULT_ORACLE_QUORUM,
+            max_staleness: DEFAULT_MAX_STALENESS,
+        })
+}
+
+pub fn register_oracle(env: Env, admin: Address, oracle: Address) -> Result<(), Error> {
+    admin.require_auth();
+    require_admin(&env, &admin)?;
+
+    let mut oracles = get_oracles(&env);
+    if oracles.contains(&oracle) {
+        return Err(Error::OracleAlreadyRegistered);
+    }
+    oracles.push_back(oracle);
+    env.storage().instance().set(&DataKey::Oracles, &oracles);
+    Ok(())
+}
+
+pub fn remove_oracle(env: Env, admin: Address, oracle: Address) -> Result<(), Error> {
+    admin.require_auth();
+    require_admin(&env, &admin)?;
+
+    let mut oracles = get_oracles(&env);
+    let index = oracles
+        .first_index_of(&oracle)
+        .ok_or(Error::OracleNotRegistered)?;
+    oracles.remove(index);
+    env.storage().instance().set(&DataKey::Oracles, &oracles);
+    Ok(())
+}
+
+pub fn set_oracle_config(
+    env: Env,
+    admin: Address,
+    quorum: u32,
+    max_staleness: u64,
+) -> Result<(), Error> {
+    admin.require_auth();
+    require_admin(&env, &admin)?;
+
+    if quorum == 0 || max_staleness == 0 {
+        return Err(Error::InvalidOracleConfig);
+    }
+
+    env.storage().instance().set(
+        &DataKey::OracleConfig,
+        &OracleConfig {
+            quorum,
+            max_staleness,
+        },
+    );
+    Ok(())
+}
+
+/// Polls every registered oracle and reports whether at least `quorum` of them
+/// confirmed the transaction within the staleness window. Oracles that fail to
+/// respond count as not confirming.
+pub fn has_oracle_quorum(env: &Env, transaction_id: u128) -> bool {
+    let config = get_oracle_config(env);
+    let now = env.ledger().timestamp();
+
+    let mut confirmations: u32 = 0;
+    for oracle in get_oracles(env).iter() {
+        let client = OracleClient::new(env, &oracle);
+        if let Ok(Ok(attestation)) = client.try_attest(&transaction_id) {
+            let fresh =
+                attestation.timestamp <= now && now - attestation.timestamp <= config.max_staleness;
+            if attestation.confirmed && fresh {
+                confirmations += 1;
+            }
+        }
+    }
+    confirmations >= config.quorum
+}
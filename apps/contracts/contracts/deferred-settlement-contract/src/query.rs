@@ -1,11 +1,17 @@
-use crate::types::{DataKey, DeferredTransaction, Error};
-use soroban_sdk::Env;
+use crate::condition::settles_on_time;
+use crate::error::Error;
+use crate::index;
+use crate::storage;
+use crate::types::{DataKey, DeferredTransaction, DuePage, IndexKey, TransactionStatus};
+use soroban_sdk::{Address, Env, Vec};
+
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Most buckets and index entries `get_due_transactions` visits in one call.
+pub const MAX_DUE_SCAN: u32 = 2 * MAX_PAGE_SIZE;
 
 pub fn get_transaction(env: Env, transaction_id: u128) -> Result<DeferredTransaction, Error> {
-    env.storage()
-        .instance()
-        .get(&DataKey::Transaction(transaction_id))
-        .ok_or(Error::TransactionNotFound)
+    storage::get_transaction(&env, transaction_id)
 }
 
 pub fn get_total_transactions(env: Env) -> u128 {
@@ -14,3 +20,104 @@ pub fn get_total_transactions(env: Env) -> u128 {
         .get(&DataKey::TotalTransactions)
         .unwrap_or(0)
 }
+
+fn load(env: &Env, ids: Vec<u128>) -> Vec<DeferredTransaction> {
+    let mut transactions = Vec::new(env);
+    for transaction_id in ids.iter() {
+        if let Ok(transaction) = storage::get_transaction(env, transaction_id) {
+            transactions.push_back(transaction);
+        }
+    }
+    transactions
+}
+
+pub fn get_buyer_transactions(env: Env, buyer: Address, offset: u32, limit: u32) -> Vec<u128> {
+    index::read(
+        &env,
+        &IndexKey::Buyer(buyer),
+        offset,
+        limit.min(MAX_PAGE_SIZE),
+    )
+}
+
+pub fn get_seller_transactions(env: Env, seller: Address, offset: u32, limit: u32) -> Vec<u128> {
+    index::read(
+        &env,
+        &IndexKey::Seller(seller),
+        offset,
+        limit.min(MAX_PAGE_SIZE),
+    )
+}
+
+pub fn get_transactions_by_status(
+    env: Env,
+    status: TransactionStatus,
+    offset: u32,
+    limit: u32,
+) -> Vec<DeferredTransaction> {
+    let ids = index::read(
+        &env,
+        &IndexKey::Status(status),
+        offset,
+        limit.min(MAX_PAGE_SIZE),
+    );
+    load(&env, ids)
+}
+
+/// Pages through pending TimeBased transactions that a keeper can settle
+/// now, walking the due buckets in deadline order. Start with no bucket and
+/// pass `next_bucket` and `next_position` back to continue. Each call visits
+/// at most `MAX_DUE_SCAN` buckets and entries, so a page can come back short
+/// while `next_bucket` is still set.
+pub fn get_due_transactions(env: Env, bucket: Option<u64>, position: u32, limit: u32) -> DuePage {
+    let now = env.ledger().timestamp();
+    let last_bucket = index::due_bucket(now);
+    let limit = limit.min(MAX_PAGE_SIZE);
+    let mut transactions = Vec::new(&env);
+
+    let (mut bucket, mut position) = match bucket.or(index::get_due_start(&env)) {
+        Some(bucket) => (bucket, position),
+        None => {
+            return DuePage {
+                transactions,
+                next_bucket: None,
+                next_position: 0,
+            }
+        }
+    };
+
+    let mut scanned = 0;
+    while bucket <= last_bucket {
+        if transactions.len() >= limit || scanned >= MAX_DUE_SCAN {
+            return DuePage {
+                transactions,
+                next_bucket: Some(bucket),
+                next_position: position,
+            };
+        }
+        scanned += 1;
+
+        let key = IndexKey::Due(bucket);
+        let count = index::count(&env, &key);
+        if position < count {
+            let transaction_id = index::page(&env, &key, position / index::INDEX_PAGE_SIZE)
+                .get_unchecked(position % index::INDEX_PAGE_SIZE);
+            if let Ok(transaction) = storage::get_transaction(&env, transaction_id) {
+                if settles_on_time(&env, &transaction) {
+                    transactions.push_back(transaction);
+                }
+            }
+            position += 1;
+        }
+        if position >= count {
+            bucket += 1;
+            position = 0;
+        }
+    }
+
+    DuePage {
+        transactions,
+        next_bucket: None,
+        next_position: 0,
+    }
+}
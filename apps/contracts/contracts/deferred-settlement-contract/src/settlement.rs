@@ -1,54 +1,46 @@
-use crate::admin::is_admin;
-use crate::types::{
-    DataKey, DeferredTransaction, Error, SettlementCondition, TransactionEvent, TransactionStatus,
-};
+use crate::admin::require_admin;
+use crate::condition::{can_settle_now, is_satisfied, record_satisfied_leaves};
+use crate::error::Error;
+use crate::index;
+use crate::storage;
+use crate::types::{DataKey, TransactionEvent, TransactionStatus};
 use soroban_sdk::{token, Address, Env, Symbol};
 
-pub fn verify_condition(
-    env: Env,
-    caller: Address,
-    transaction_id: u128,
-    oracle_input: Option<bool>,
-) -> Result<(), Error> {
+pub const DEFAULT_REFUND_GRACE_PERIOD: u64 = 7 * 86400; // 7 days
+
+pub fn verify_condition(env: Env, caller: Address, transaction_id: u128) -> Result<(), Error> {
     caller.require_auth();
-    let mut transaction: DeferredTransaction = env
-        .storage()
-        .instance()
-        .get(&DataKey::Transaction(transaction_id))
-        .ok_or(Error::TransactionNotFound)?;
+    let mut transaction = storage::get_transaction(&env, transaction_id)?;
 
     if transaction.status != TransactionStatus::Pending {
         return Err(Error::NotPending);
     }
 
-    let is_verified = match transaction.condition {
-        SettlementCondition::TimeBased => env.ledger().timestamp() >= transaction.deadline,
-        SettlementCondition::BuyerApproval => caller == transaction.buyer,
-        SettlementCondition::OracleConfirmation => {
-            if !is_admin(&env, &caller) {
-                return Err(Error::Unauthorized);
-            }
-            oracle_input.unwrap_or(false)
+    let newly_satisfied = record_satisfied_leaves(&env, &mut transaction, &caller);
+    for leaf in newly_satisfied.iter() {
+        env.events().publish(
+            ("DeferredSettlement", Symbol::new(&env, "leaf_satisfied")),
+            TransactionEvent::LeafSatisfied(transaction_id, leaf),
+        );
+    }
+
+    if !is_satisfied(&env, &transaction) {
+        if newly_satisfied.is_empty() {
+            return Err(Error::ConditionNotMet);
         }
-    };
 
-    if !is_verified {
-        return Err(Error::ConditionNotMet);
+        // Keep the progress so later calls can complete the expression
+        storage::set_transaction(&env, &transaction);
+        return Ok(());
     }
 
     // Update status
     transaction.status = TransactionStatus::Completed;
-    env.storage()
-        .instance()
-        .set(&DataKey::Transaction(transaction_id), &transaction);
+    storage::set_transaction(&env, &transaction);
+    index::move_status(&env, &transaction, &TransactionStatus::Pending);
 
-    // Release funds
-    let token_contract_id = env
-        .storage()
-        .instance()
-        .get(&DataKey::TokenContract)
-        .ok_or(Error::TokenContractNotSet)?;
-    let token_client = token::Client::new(&env, &token_contract_id);
+    // Release funds in the asset the transaction was funded with
+    let token_client = token::Client::new(&env, &transaction.token);
     token_client.transfer(
         &env.current_contract_address(),
         &transaction.seller,
@@ -70,3 +62,60 @@ pub fn verify_condition(
 
     Ok(())
 }
+
+pub fn get_refund_grace_period(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::RefundGracePeriod)
+        .unwrap_or(DEFAULT_REFUND_GRACE_PERIOD)
+}
+
+pub fn set_refund_grace_period(env: Env, admin: Address, grace_period: u64) -> Result<(), Error> {
+    admin.require_auth();
+    require_admin(&env, &admin)?;
+    env.storage()
+        .instance()
+        .set(&DataKey::RefundGracePeriod, &grace_period);
+    Ok(())
+}
+
+/// Returns the funds of a pending transaction to the buyer once its deadline
+/// plus the grace period has passed. Callable by anyone, so funds never stay
+/// locked when a BuyerApproval or OracleConfirmation deal is never verified.
+pub fn claim_refund(env: Env, transaction_id: u128) -> Result<(), Error> {
+    let mut transaction = storage::get_transaction(&env, transaction_id)?;
+
+    if transaction.status != TransactionStatus::Pending {
+        return Err(Error::NotPending);
+    }
+
+    let refundable_at = transaction
+        .deadline
+        .saturating_add(get_refund_grace_period(&env));
+    if env.ledger().timestamp() < refundable_at {
+        return Err(Error::RefundNotAvailable);
+    }
+
+    // A deal that can still settle (e.g. a passed TimeBased deadline) goes to the seller
+    if can_settle_now(&env, &transaction) {
+        return Err(Error::RefundNotAvailable);
+    }
+
+    transaction.status = TransactionStatus::Refunded;
+    storage::set_transaction(&env, &transaction);
+    index::move_status(&env, &transaction, &TransactionStatus::Pending);
+
+    let token_client = token::Client::new(&env, &transaction.token);
+    token_client.transfer(
+        &env.current_contract_address(),
+        &transaction.buyer,
+        &transaction.amount,
+    );
+
+    env.events().publish(
+        ("DeferredSettlement", Symbol::new(&env, "funds_refunded")),
+        TransactionEvent::FundsRefunded(transaction_id, transaction.buyer),
+    );
+
+    Ok(())
+}
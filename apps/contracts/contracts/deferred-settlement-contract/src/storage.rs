@@ -0,0 +1,110 @@
+use crate::admin::require_admin;
+use crate::error::Error;
+use crate::index;
+use crate::types::{
+    ConditionNode, DataKey, DeferredTransaction, LegacyDeferredTransaction, TransactionEvent,
+};
+use soroban_sdk::{vec, Address, Env, Symbol, Vec};
+
+pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const TRANSACTION_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const TRANSACTION_LIFETIME_THRESHOLD: u32 = TRANSACTION_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub fn extend_instance_ttl(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+/// Loads a transaction from its own persistent entry and extends its TTL.
+pub fn get_transaction(env: &Env, transaction_id: u128) -> Result<DeferredTransaction, Error> {
+    let key = DataKey::Transaction(transaction_id);
+    let transaction = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(Error::TransactionNotFound)?;
+    env.storage().persistent().extend_ttl(
+        &key,
+        TRANSACTION_LIFETIME_THRESHOLD,
+        TRANSACTION_BUMP_AMOUNT,
+    );
+    Ok(transaction)
+}
+
+pub fn set_transaction(env: &Env, transaction: &DeferredTransaction) {
+    let key = DataKey::Transaction(transaction.id);
+    env.storage().persistent().set(&key, transaction);
+    env.storage().persistent().extend_ttl(
+        &key,
+        TRANSACTION_LIFETIME_THRESHOLD,
+        TRANSACTION_BUMP_AMOUNT,
+    );
+    extend_instance_ttl(env);
+}
+
+/// Moves up to `limit` transactions, starting at `start_id`, from the shared
+/// instance entry into their own persistent entries. Ids that are already
+/// migrated are skipped, so batches can be re-run safely. Returns how many
+/// transactions were moved.
+pub fn migrate_transactions(
+    env: Env,
+    admin: Address,
+    start_id: u128,
+    limit: u32,
+) -> Result<u32, Error> {
+    admin.require_auth();
+    require_admin(&env, &admin)?;
+
+    // Legacy transactions always paid out in the global token
+    let token: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::TokenContract)
+        .ok_or(Error::TokenContractNotSet)?;
+    let total: u128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::TotalTransactions)
+        .unwrap_or(0);
+    let end = total.min(start_id.saturating_add(limit as u128));
+
+    let mut migrated = 0u32;
+    let mut transaction_id = start_id;
+    while transaction_id < end {
+        let key = DataKey::Transaction(transaction_id);
+        let legacy: Option<LegacyDeferredTransaction> = env.storage().instance().get(&key);
+        if let Some(legacy) = legacy {
+            let transaction = DeferredTransaction {
+                id: legacy.id,
+                buyer: legacy.buyer,
+                seller: legacy.seller,
+                amount: legacy.amount,
+                token: token.clone(),
+                condition: vec![&env, ConditionNode::Leaf(legacy.condition)],
+                satisfied_leaves: Vec::new(&env),
+                created_at: legacy.created_at,
+                deadline: legacy.deadline,
+                status: legacy.status,
+            };
+            set_transaction(&env, &transaction);
+            index::index_transaction(&env, &transaction);
+            env.storage().instance().remove(&key);
+            migrated += 1;
+        }
+        transaction_id += 1;
+    }
+
+    env.events().publish(
+        (
+            "DeferredSettlement",
+            Symbol::new(&env, "transactions_migrated"),
+        ),
+        TransactionEvent::TransactionsMigrated(start_id, migrated),
+    );
+
+    Ok(migrated)
+}
@@ -2,12 +2,16 @@
 
 extern crate std;
 
-use crate::types::{SettlementCondition, TransactionStatus};
+use crate::error::Error;
+use crate::mock_oracle::{MockOracle, MockOracleClient};
+use crate::types::{
+    ConditionNode, DataKey, LegacyDeferredTransaction, SettlementCondition, TransactionStatus,
+};
 use crate::{DeferredSettlementContract, DeferredSettlementContractClient};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger as _},
-    token::StellarAssetClient,
-    Address, Env, Symbol,
+    testutils::{storage::Persistent as _, Address as _, Ledger as _},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env, Symbol,
 };
 
 #[test]
@@ -53,7 +57,7 @@ fn test_create_transaction() {
 
     // Create transaction
     let transaction_id =
-        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration);
+        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration, &None);
 
     // Verify transaction state
     let transaction = contract_client.get_transaction(&transaction_id);
@@ -62,11 +66,13 @@ fn test_create_transaction() {
     assert_eq!(transaction.seller, seller);
     assert_eq!(transaction.amount, amount);
     assert_eq!(transaction.status, TransactionStatus::Pending);
-    assert_eq!(transaction.condition, SettlementCondition::TimeBased);
+    assert_eq!(
+        transaction.condition,
+        vec![&env, ConditionNode::Leaf(SettlementCondition::TimeBased)]
+    );
 }
 
 #[test]
-#[should_panic(expected = "InvalidAmount")]
 fn test_create_transaction_invalid_amount() {
     let env = Env::default();
     let admin = Address::generate(&env);
@@ -87,7 +93,9 @@ fn test_create_transaction_invalid_amount() {
     let duration = 86400;
     let condition = Symbol::new(&env, "TimeBased");
 
-    contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration);
+    let result = contract_client
+        .try_create_transaction(&buyer, &seller, &amount, &condition, &duration, &None);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
 }
 
 #[test]
@@ -116,12 +124,12 @@ fn test_verify_time_based_condition() {
     token_client.mint(&buyer, &amount);
 
     let transaction_id =
-        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration);
+        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration, &None);
 
     // Fast forward ledger time
     env.ledger().with_mut(|l| l.timestamp += duration + 1);
 
-    contract_client.verify_condition(&buyer, &transaction_id, &None);
+    contract_client.verify_condition(&buyer, &transaction_id);
     let transaction = contract_client.get_transaction(&transaction_id);
     assert_eq!(transaction.status, TransactionStatus::Completed);
 }
@@ -152,7 +160,7 @@ fn test_initiate_and_resolve_dispute() {
     token_client.mint(&buyer, &amount);
 
     let transaction_id =
-        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration);
+        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration, &None);
 
     // Initiate dispute
     contract_client.initiate_dispute(&buyer, &transaction_id);
@@ -168,7 +176,6 @@ fn test_initiate_and_resolve_dispute() {
 }
 
 #[test]
-#[should_panic(expected = "Unauthorized")]
 fn test_unauthorized_dispute() {
     let env = Env::default();
     let admin = Address::generate(&env);
@@ -195,10 +202,11 @@ fn test_unauthorized_dispute() {
     token_client.mint(&buyer, &amount);
 
     let transaction_id =
-        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration);
+        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration, &None);
 
     // Try to initiate dispute with unauthorized address
-    contract_client.initiate_dispute(&unauthorized, &transaction_id);
+    let result = contract_client.try_initiate_dispute(&unauthorized, &transaction_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
 }
 
 #[test]
@@ -207,6 +215,7 @@ fn test_oracle_confirmation() {
     let admin = Address::generate(&env);
     let buyer = Address::generate(&env);
     let seller = Address::generate(&env);
+    let keeper = Address::generate(&env);
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin);
     let token_client = StellarAssetClient::new(&env, &token_contract.address());
@@ -214,10 +223,14 @@ fn test_oracle_confirmation() {
     let contract_address = env.register(DeferredSettlementContract, ());
     let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
 
+    let oracle_address = env.register(MockOracle, ());
+    let oracle_client = MockOracleClient::new(&env, &oracle_address);
+
     env.mock_all_auths();
 
     contract_client.initialize(&admin);
     contract_client.set_token_contract(&admin, &token_contract.address());
+    contract_client.register_oracle(&admin, &oracle_address);
 
     let amount = 1000;
     let duration = 86400;
@@ -227,10 +240,829 @@ fn test_oracle_confirmation() {
     token_client.mint(&buyer, &amount);
 
     let transaction_id =
-        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration);
+        contract_client.create_transaction(&buyer, &seller, &amount, &condition, &duration, &None);
+
+    // Anyone can trigger settlement once the oracle has confirmed
+    oracle_client.set_attestation(&transaction_id, &true, &env.ledger().timestamp());
+    contract_client.verify_condition(&keeper, &transaction_id);
+
+    let transaction = contract_client.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Completed);
+}
+
+fn setup_oracle_quorum(
+    env: &Env,
+) -> (
+    DeferredSettlementContractClient<'static>,
+    MockOracleClient<'static>,
+    MockOracleClient<'static>,
+    u128,
+) {
+    let admin = Address::generate(env);
+    let buyer = Address::generate(env);
+    let seller = Address::generate(env);
+    let token_admin = Address::generate(env);
+    let token_contract = env.register_stellar_asset_contract_v2(token_admin);
+    let token_client = StellarAssetClient::new(env, &token_contract.address());
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(env, &contract_address);
+
+    let oracle_a = MockOracleClient::new(env, &env.register(MockOracle, ()));
+    let oracle_b = MockOracleClient::new(env, &env.register(MockOracle, ()));
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    contract_client.set_token_contract(&admin, &token_contract.address());
+    contract_client.register_oracle(&admin, &oracle_a.address);
+    contract_client.register_oracle(&admin, &oracle_b.address);
+    contract_client.set_oracle_config(&admin, &2, &3600);
+
+    token_client.mint(&buyer, &1000);
+    let condition = Symbol::new(env, "OracleConfirmation");
+    let transaction_id =
+        contract_client.create_transaction(&buyer, &seller, &1000, &condition, &86400, &None);
+
+    (contract_client, oracle_a, oracle_b, transaction_id)
+}
+
+#[test]
+fn test_oracle_quorum_reached() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| l.timestamp = 10_000);
+    let (contract_client, oracle_a, oracle_b, transaction_id) = setup_oracle_quorum(&env);
+    let caller = Address::generate(&env);
+
+    assert_eq!(contract_client.get_oracles().len(), 2);
+
+    oracle_a.set_attestation(&transaction_id, &true, &9_000);
+    oracle_b.set_attestation(&transaction_id, &true, &10_000);
+    contract_client.verify_condition(&caller, &transaction_id);
+
+    let transaction = contract_client.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Completed);
+}
+
+#[test]
+fn test_oracle_quorum_not_reached() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| l.timestamp = 10_000);
+    let (contract_client, oracle_a, oracle_b, transaction_id) = setup_oracle_quorum(&env);
+    let caller = Address::generate(&env);
+
+    // One confirmation and one denial fall short of a quorum of two
+    oracle_a.set_attestation(&transaction_id, &true, &10_000);
+    oracle_b.set_attestation(&transaction_id, &false, &10_000);
+    let result = contract_client.try_verify_condition(&caller, &transaction_id);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
+}
+
+#[test]
+fn test_stale_oracle_attestation_ignored() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| l.timestamp = 10_000);
+    let (contract_client, oracle_a, oracle_b, transaction_id) = setup_oracle_quorum(&env);
+    let caller = Address::generate(&env);
+
+    // oracle_a's attestation is older than the one-hour staleness limit
+    oracle_a.set_attestation(&transaction_id, &true, &6_000);
+    oracle_b.set_attestation(&transaction_id, &true, &10_000);
+    let result = contract_client.try_verify_condition(&caller, &transaction_id);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
+}
+
+#[test]
+fn test_register_oracle_twice() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let oracle = Address::generate(&env);
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    contract_client.register_oracle(&admin, &oracle);
+    contract_client.remove_oracle(&admin, &oracle);
+    assert_eq!(contract_client.get_oracles().len(), 0);
+
+    contract_client.register_oracle(&admin, &oracle);
+    let result = contract_client.try_register_oracle(&admin, &oracle);
+    assert_eq!(result, Err(Ok(Error::OracleAlreadyRegistered)));
+}
+
+const DAY: u64 = 86400;
+
+fn setup_composite(
+    env: &Env,
+) -> (
+    DeferredSettlementContractClient<'static>,
+    MockOracleClient<'static>,
+    Address,
+    Address,
+) {
+    let admin = Address::generate(env);
+    let buyer = Address::generate(env);
+    let seller = Address::generate(env);
+    let token_admin = Address::generate(env);
+    let token_contract = env.register_stellar_asset_contract_v2(token_admin);
+    let token_client = StellarAssetClient::new(env, &token_contract.address());
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(env, &contract_address);
+    let oracle = MockOracleClient::new(env, &env.register(MockOracle, ()));
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    contract_client.set_token_contract(&admin, &token_contract.address());
+    contract_client.register_oracle(&admin, &oracle.address);
+    token_client.mint(&buyer, &10_000);
+
+    (contract_client, oracle, buyer, seller)
+}
+
+#[test]
+fn test_composite_or_condition() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+    let keeper = Address::generate(&env);
+
+    // Buyer approves OR 14 days pass
+    let condition = vec![
+        &env,
+        ConditionNode::Or(vec![&env, 1, 2]),
+        ConditionNode::Leaf(SettlementCondition::BuyerApproval),
+        ConditionNode::Leaf(SettlementCondition::TimeElapsed(14 * DAY)),
+    ];
+
+    let approved = contract_client.create_composite_transaction(
+        &buyer,
+        &seller,
+        &1000,
+        &condition,
+        &(30 * DAY),
+        &None,
+    );
+    contract_client.verify_condition(&buyer, &approved);
+    assert_eq!(
+        contract_client.get_transaction(&approved).status,
+        TransactionStatus::Completed
+    );
+
+    let timed_out = contract_client.create_composite_transaction(
+        &buyer,
+        &seller,
+        &1000,
+        &condition,
+        &(30 * DAY),
+        &None,
+    );
+    let result = contract_client.try_verify_condition(&keeper, &timed_out);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
+
+    env.ledger().with_mut(|l| l.timestamp += 14 * DAY);
+    contract_client.verify_condition(&keeper, &timed_out);
+    let transaction = contract_client.get_transaction(&timed_out);
+    assert_eq!(transaction.status, TransactionStatus::Completed);
+    assert_eq!(transaction.satisfied_leaves, vec![&env, 2]);
+}
+
+#[test]
+fn test_composite_and_condition_tracks_leaves() {
+    let env = Env::default();
+    let (contract_client, oracle, buyer, seller) = setup_composite(&env);
+    let keeper = Address::generate(&env);
+
+    // Oracle confirms AND 3 days pass
+    let condition = vec![
+        &env,
+        ConditionNode::And(vec![&env, 1, 2]),
+        ConditionNode::Leaf(SettlementCondition::OracleConfirmation),
+        ConditionNode::Leaf(SettlementCondition::TimeElapsed(3 * DAY)),
+    ];
+    let transaction_id = contract_client.create_composite_transaction(
+        &buyer,
+        &seller,
+        &1000,
+        &condition,
+        &(30 * DAY),
+        &None,
+    );
 
-    contract_client.verify_condition(&admin, &transaction_id, &Some(true));
+    // The oracle leaf is recorded, but the expression is not yet true
+    oracle.set_attestation(&transaction_id, &true, &env.ledger().timestamp());
+    contract_client.verify_condition(&keeper, &transaction_id);
+    let transaction = contract_client.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Pending);
+    assert_eq!(transaction.satisfied_leaves, vec![&env, 1]);
+
+    // Nothing new holds yet
+    let result = contract_client.try_verify_condition(&keeper, &transaction_id);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
 
+    // The oracle attestation has gone stale, but its leaf stays satisfied
+    env.ledger().with_mut(|l| l.timestamp += 3 * DAY);
+    contract_client.verify_condition(&keeper, &transaction_id);
     let transaction = contract_client.get_transaction(&transaction_id);
     assert_eq!(transaction.status, TransactionStatus::Completed);
+    assert_eq!(transaction.satisfied_leaves, vec![&env, 1, 2]);
+}
+
+#[test]
+fn test_composite_condition_rejects_backward_reference() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+
+    // Node 1 pointing back at the root would form a cycle
+    let condition = vec![
+        &env,
+        ConditionNode::And(vec![&env, 1]),
+        ConditionNode::Or(vec![&env, 0]),
+    ];
+    let result = contract_client
+        .try_create_composite_transaction(&buyer, &seller, &1000, &condition, &DAY, &None);
+    assert_eq!(result, Err(Ok(Error::InvalidCondition)));
+}
+
+#[test]
+fn test_transaction_keeps_its_own_token() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let default_token = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let other_token = env.register_stellar_asset_contract_v2(Address::generate(&env));
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    contract_client.set_token_contract(&admin, &default_token.address());
+    StellarAssetClient::new(&env, &default_token.address()).mint(&buyer, &1000);
+    StellarAssetClient::new(&env, &other_token.address()).mint(&buyer, &500);
+
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let default_id =
+        contract_client.create_transaction(&buyer, &seller, &1000, &condition, &86400, &None);
+    let explicit_id = contract_client.create_transaction(
+        &buyer,
+        &seller,
+        &500,
+        &condition,
+        &86400,
+        &Some(other_token.address()),
+    );
+    assert_eq!(
+        contract_client.get_transaction(&default_id).token,
+        default_token.address()
+    );
+    assert_eq!(
+        contract_client.get_transaction(&explicit_id).token,
+        other_token.address()
+    );
+
+    // Changing the default mid-flight does not affect pending transactions
+    contract_client.set_token_contract(&admin, &other_token.address());
+    contract_client.verify_condition(&buyer, &default_id);
+    contract_client.verify_condition(&buyer, &explicit_id);
+
+    assert_eq!(
+        TokenClient::new(&env, &default_token.address()).balance(&seller),
+        1000
+    );
+    assert_eq!(
+        TokenClient::new(&env, &other_token.address()).balance(&seller),
+        500
+    );
+}
+
+#[test]
+fn test_create_transaction_rejects_non_token() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let not_a_token = env.register(MockOracle, ());
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let result = contract_client.try_create_transaction(
+        &buyer,
+        &seller,
+        &1000,
+        &condition,
+        &86400,
+        &Some(not_a_token),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidToken)));
+}
+
+#[test]
+fn test_create_transaction_without_any_token() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let result =
+        contract_client.try_create_transaction(&buyer, &seller, &1000, &condition, &86400, &None);
+    assert_eq!(result, Err(Ok(Error::TokenContractNotSet)));
+}
+
+#[test]
+fn test_entrypoint_error_codes() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let token_contract = env.register_stellar_asset_contract_v2(Address::generate(&env));
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+    StellarAssetClient::new(&env, &token_contract.address()).mint(&buyer, &1000);
+
+    let result = contract_client.try_set_token_contract(&admin, &token_contract.address());
+    assert_eq!(result, Err(Ok(Error::NotInitialized)));
+
+    contract_client.initialize(&admin);
+    assert_eq!(
+        contract_client.try_initialize(&admin),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+
+    let result = contract_client.try_set_token_contract(&outsider, &token_contract.address());
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    contract_client.set_token_contract(&admin, &token_contract.address());
+
+    let result = contract_client.try_create_transaction(
+        &buyer,
+        &seller,
+        &1000,
+        &Symbol::new(&env, "Unknown"),
+        &86400,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidCondition)));
+
+    let result = contract_client.try_get_transaction(&7);
+    assert_eq!(result, Err(Ok(Error::TransactionNotFound)));
+
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let transaction_id =
+        contract_client.create_transaction(&buyer, &seller, &1000, &condition, &86400, &None);
+
+    // Resolving requires an open dispute
+    let result = contract_client.try_resolve_dispute(&buyer, &transaction_id, &true);
+    assert_eq!(result, Err(Ok(Error::NotPending)));
+
+    contract_client.verify_condition(&buyer, &transaction_id);
+    let result = contract_client.try_initiate_dispute(&buyer, &transaction_id);
+    assert_eq!(result, Err(Ok(Error::NotPending)));
+}
+
+#[test]
+fn test_claim_refund_after_deadline_and_grace() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+    let keeper = Address::generate(&env);
+
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let transaction_id =
+        contract_client.create_transaction(&buyer, &seller, &1000, &condition, &(3 * DAY), &None);
+    let token = TokenClient::new(
+        &env,
+        &contract_client.get_transaction(&transaction_id).token,
+    );
+    assert_eq!(token.balance(&buyer), 9_000);
+
+    // Past the deadline but still inside the default 7-day grace period
+    env.ledger().with_mut(|l| l.timestamp += 3 * DAY + 1);
+    let result = contract_client.try_claim_refund(&transaction_id);
+    assert_eq!(result, Err(Ok(Error::RefundNotAvailable)));
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += contract_client.get_refund_grace_period());
+    contract_client.claim_refund(&transaction_id);
+
+    let transaction = contract_client.get_transaction(&transaction_id);
+    assert_eq!(transaction.status, TransactionStatus::Refunded);
+    assert_eq!(token.balance(&buyer), 10_000);
+
+    let result = contract_client.try_claim_refund(&transaction_id);
+    assert_eq!(result, Err(Ok(Error::NotPending)));
+    let result = contract_client.try_verify_condition(&keeper, &transaction_id);
+    assert_eq!(result, Err(Ok(Error::NotPending)));
+}
+
+#[test]
+fn test_claim_refund_with_unreachable_delay() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+    let keeper = Address::generate(&env);
+
+    // A delay that can never elapse must not lock the funds
+    let condition = vec![
+        &env,
+        ConditionNode::Leaf(SettlementCondition::TimeElapsed(u64::MAX)),
+    ];
+    let transaction_id = contract_client
+        .create_composite_transaction(&buyer, &seller, &1000, &condition, &DAY, &None);
+    let token = TokenClient::new(
+        &env,
+        &contract_client.get_transaction(&transaction_id).token,
+    );
+
+    let result = contract_client.try_verify_condition(&keeper, &transaction_id);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += DAY + 1 + contract_client.get_refund_grace_period());
+    contract_client.claim_refund(&transaction_id);
+    assert_eq!(
+        contract_client.get_transaction(&transaction_id).status,
+        TransactionStatus::Refunded
+    );
+    assert_eq!(token.balance(&buyer), 10_000);
+}
+
+#[test]
+fn test_claim_refund_blocked_when_settlement_is_possible() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+
+    let condition = Symbol::new(&env, "TimeBased");
+    let transaction_id =
+        contract_client.create_transaction(&buyer, &seller, &1000, &condition, &DAY, &None);
+
+    // A passed TimeBased deadline means the seller is owed the funds
+    env.ledger().with_mut(|l| l.timestamp += 30 * DAY);
+    let result = contract_client.try_claim_refund(&transaction_id);
+    assert_eq!(result, Err(Ok(Error::RefundNotAvailable)));
+}
+
+#[test]
+fn test_set_refund_grace_period() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let outsider = Address::generate(&env);
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    assert_eq!(contract_client.get_refund_grace_period(), 7 * DAY);
+
+    contract_client.set_refund_grace_period(&admin, &DAY);
+    assert_eq!(contract_client.get_refund_grace_period(), DAY);
+
+    let result = contract_client.try_set_refund_grace_period(&outsider, &0);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_huge_grace_period_does_not_overflow() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let token_contract = env.register_stellar_asset_contract_v2(Address::generate(&env));
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    contract_client.set_token_contract(&admin, &token_contract.address());
+    StellarAssetClient::new(&env, &token_contract.address()).mint(&buyer, &1000);
+    contract_client.set_refund_grace_period(&admin, &u64::MAX);
+
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let transaction_id =
+        contract_client.create_transaction(&buyer, &seller, &1000, &condition, &DAY, &None);
+
+    env.ledger().with_mut(|l| l.timestamp += 2 * DAY);
+    let result = contract_client.try_claim_refund(&transaction_id);
+    assert_eq!(result, Err(Ok(Error::RefundNotAvailable)));
+}
+
+#[test]
+fn test_transactions_use_persistent_storage_with_ttl() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let transaction_id =
+        contract_client.create_transaction(&buyer, &seller, &1000, &condition, &DAY, &None);
+
+    env.as_contract(&contract_client.address, || {
+        let key = DataKey::Transaction(transaction_id);
+        assert!(!env.storage().instance().has(&key));
+        assert!(env.storage().persistent().has(&key));
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            crate::storage::TRANSACTION_BUMP_AMOUNT
+        );
+    });
+}
+
+#[test]
+fn test_migrate_transactions_from_instance_storage() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let token_contract = env.register_stellar_asset_contract_v2(token_admin);
+
+    let contract_address = env.register(DeferredSettlementContract, ());
+    let contract_client = DeferredSettlementContractClient::new(&env, &contract_address);
+
+    env.mock_all_auths();
+
+    contract_client.initialize(&admin);
+    contract_client.set_token_contract(&admin, &token_contract.address());
+    StellarAssetClient::new(&env, &token_contract.address()).mint(&contract_address, &3000);
+
+    // Transactions as an earlier version of the contract stored them
+    env.as_contract(&contract_address, || {
+        for id in 0..3u128 {
+            let legacy = LegacyDeferredTransaction {
+                id,
+                buyer: buyer.clone(),
+                seller: seller.clone(),
+                amount: 1000,
+                condition: SettlementCondition::BuyerApproval,
+                created_at: 0,
+                deadline: DAY,
+                status: TransactionStatus::Pending,
+            };
+            env.storage()
+                .instance()
+                .set(&DataKey::Transaction(id), &legacy);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalTransactions, &3u128);
+    });
+
+    let result = contract_client.try_get_transaction(&0);
+    assert_eq!(result, Err(Ok(Error::TransactionNotFound)));
+
+    let outsider = Address::generate(&env);
+    let result = contract_client.try_migrate_transactions(&outsider, &0, &10);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    assert_eq!(contract_client.migrate_transactions(&admin, &0, &2), 2);
+    assert_eq!(contract_client.migrate_transactions(&admin, &0, &10), 1);
+    assert_eq!(contract_client.migrate_transactions(&admin, &0, &10), 0);
+
+    let transaction = contract_client.get_transaction(&1);
+    assert_eq!(transaction.buyer, buyer);
+    assert_eq!(transaction.token, token_contract.address());
+    assert_eq!(
+        transaction.condition,
+        vec![
+            &env,
+            ConditionNode::Leaf(SettlementCondition::BuyerApproval)
+        ]
+    );
+    env.as_contract(&contract_address, || {
+        assert!(!env.storage().instance().has(&DataKey::Transaction(1)));
+    });
+
+    // Migrated transactions settle like new ones
+    contract_client.verify_condition(&buyer, &1);
+    let token = TokenClient::new(&env, &token_contract.address());
+    assert_eq!(token.balance(&seller), 1000);
+}
+
+#[test]
+fn test_transaction_cost_is_flat_as_transactions_grow() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+    let condition = Symbol::new(&env, "TimeBased");
+
+    // Ledger entries and bytes touched by the last call. The test host's CPU
+    // count grows with the size of its in-memory ledger, so it is not used here.
+    let footprint = || {
+        let resources = env.cost_estimate().resources();
+        (
+            resources.read_entries + resources.write_entries,
+            resources.read_bytes + resources.write_bytes,
+        )
+    };
+    let create = || {
+        let transaction_id =
+            contract_client.create_transaction(&buyer, &seller, &10, &condition, &DAY, &None);
+        (transaction_id, footprint())
+    };
+    let verify = |transaction_id: u128| {
+        contract_client.verify_condition(&buyer, &transaction_id);
+        footprint()
+    };
+
+    let (first, early_create) = create();
+    env.ledger().with_mut(|l| l.timestamp += DAY);
+    let early_verify = verify(first);
+
+    // Every index the calls touch holds hundreds of entries by now
+    for _ in 0..300 {
+        create();
+    }
+    let (_, late_create) = create();
+    env.ledger().with_mut(|l| l.timestamp += DAY);
+    let late_verify = verify(150);
+
+    // A swap-remove may touch one more page and position per index, and
+    // fuller pages weigh more, but nothing scales with the index size
+    for (early, late) in [(early_create, late_create), (early_verify, late_verify)] {
+        assert!(
+            late.0 <= early.0 + 4,
+            "entries grew from {} to {}",
+            early.0,
+            late.0
+        );
+        assert!(
+            late.1 <= 2 * early.1,
+            "bytes grew from {} to {}",
+            early.1,
+            late.1
+        );
+    }
+}
+
+#[test]
+fn test_buyer_seller_and_status_indexes() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+    let other_seller = Address::generate(&env);
+
+    let condition = Symbol::new(&env, "BuyerApproval");
+    let first = contract_client.create_transaction(&buyer, &seller, &100, &condition, &DAY, &None);
+    let second =
+        contract_client.create_transaction(&buyer, &other_seller, &100, &condition, &DAY, &None);
+    let third = contract_client.create_transaction(&buyer, &seller, &100, &condition, &DAY, &None);
+
+    assert_eq!(
+        contract_client.get_buyer_transactions(&buyer, &0, &10),
+        vec![&env, first, second, third]
+    );
+    assert_eq!(
+        contract_client.get_buyer_transactions(&buyer, &1, &1),
+        vec![&env, second]
+    );
+    assert_eq!(
+        contract_client.get_seller_transactions(&seller, &0, &10),
+        vec![&env, first, third]
+    );
+    assert_eq!(
+        contract_client.get_seller_transactions(&other_seller, &5, &10),
+        vec![&env]
+    );
+
+    contract_client.verify_condition(&buyer, &first);
+    contract_client.initiate_dispute(&seller, &third);
+
+    let pending = contract_client.get_transactions_by_status(&TransactionStatus::Pending, &0, &10);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get_unchecked(0).id, second);
+    let completed =
+        contract_client.get_transactions_by_status(&TransactionStatus::Completed, &0, &10);
+    assert_eq!(completed.get_unchecked(0).id, first);
+
+    contract_client.resolve_dispute(&buyer, &third, &false);
+    let disputed =
+        contract_client.get_transactions_by_status(&TransactionStatus::Disputed, &0, &10);
+    assert!(disputed.is_empty());
+    let refunded =
+        contract_client.get_transactions_by_status(&TransactionStatus::Refunded, &0, &10);
+    assert_eq!(refunded.len(), 1);
+    assert_eq!(refunded.get_unchecked(0).id, third);
+}
+
+#[test]
+fn test_due_transactions_view() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+    let keeper = Address::generate(&env);
+
+    let time_based = Symbol::new(&env, "TimeBased");
+    let approval = Symbol::new(&env, "BuyerApproval");
+    let short = contract_client.create_transaction(&buyer, &seller, &100, &time_based, &DAY, &None);
+    contract_client.create_transaction(&buyer, &seller, &100, &approval, &DAY, &None);
+    let long =
+        contract_client.create_transaction(&buyer, &seller, &100, &time_based, &(3 * DAY), &None);
+    let later = contract_client.create_transaction(&buyer, &seller, &100, &time_based, &DAY, &None);
+
+    let page = contract_client.get_due_transactions(&None, &0, &10);
+    assert!(page.transactions.is_empty());
+    assert_eq!(page.next_bucket, None);
+
+    // Only TimeBased deals past their deadline are due; BuyerApproval never is
+    env.ledger().with_mut(|l| l.timestamp += DAY);
+    let due = contract_client
+        .get_due_transactions(&None, &0, &10)
+        .transactions;
+    assert_eq!(due.len(), 2);
+    assert_eq!(due.get_unchecked(0).id, short);
+    assert_eq!(due.get_unchecked(1).id, later);
+
+    let page = contract_client.get_due_transactions(&None, &0, &1);
+    assert_eq!(page.transactions.get_unchecked(0).id, short);
+    let page = contract_client.get_due_transactions(&page.next_bucket, &page.next_position, &1);
+    assert_eq!(page.transactions.len(), 1);
+    assert_eq!(page.transactions.get_unchecked(0).id, later);
+    assert_eq!(page.next_bucket, None);
+
+    // A keeper settles everything the view returns
+    for transaction in due.iter() {
+        contract_client.verify_condition(&keeper, &transaction.id);
+    }
+    let page = contract_client.get_due_transactions(&None, &0, &10);
+    assert!(page.transactions.is_empty());
+
+    env.ledger().with_mut(|l| l.timestamp += 2 * DAY);
+    let due = contract_client
+        .get_due_transactions(&None, &0, &10)
+        .transactions;
+    assert_eq!(due.len(), 1);
+    assert_eq!(due.get_unchecked(0).id, long);
+}
+
+#[test]
+fn test_due_transactions_scan_is_bounded() {
+    let env = Env::default();
+    let (contract_client, _, buyer, seller) = setup_composite(&env);
+    let keeper = Address::generate(&env);
+
+    let time_based = Symbol::new(&env, "TimeBased");
+    let soon = contract_client.create_transaction(&buyer, &seller, &100, &time_based, &DAY, &None);
+    let distant =
+        contract_client.create_transaction(&buyer, &seller, &100, &time_based, &(300 * DAY), &None);
+
+    env.ledger().with_mut(|l| l.timestamp += DAY);
+    contract_client.verify_condition(&keeper, &soon);
+
+    // Hundreds of empty buckets lie before the distant deadline
+    env.ledger().with_mut(|l| l.timestamp += 300 * DAY);
+    let (mut bucket, mut position) = (None, 0);
+    let mut calls = 0;
+    let mut due = vec![&env];
+    loop {
+        let page = contract_client.get_due_transactions(&bucket, &position, &10);
+        for transaction in page.transactions.iter() {
+            due.push_back(transaction.id);
+        }
+        calls += 1;
+        if page.next_bucket.is_none() {
+            break;
+        }
+        (bucket, position) = (page.next_bucket, page.next_position);
+    }
+    assert!(calls > 1);
+    assert_eq!(due, vec![&env, distant]);
+}
+
+#[test]
+fn test_due_view_makes_no_oracle_calls() {
+    let env = Env::default();
+    let (contract_client, oracle, buyer, seller) = setup_composite(&env);
+
+    // Due by time, but the oracle leaf has not been recorded by verify_condition
+    let condition = vec![
+        &env,
+        ConditionNode::And(vec![&env, 1, 2]),
+        ConditionNode::Leaf(SettlementCondition::TimeBased),
+        ConditionNode::Leaf(SettlementCondition::OracleConfirmation),
+    ];
+    let transaction_id = contract_client
+        .create_composite_transaction(&buyer, &seller, &100, &condition, &DAY, &None);
+
+    env.ledger().with_mut(|l| l.timestamp += DAY);
+    oracle.set_attestation(&transaction_id, &true, &env.ledger().timestamp());
+    let page = contract_client.get_due_transactions(&None, &0, &10);
+    assert!(page.transactions.is_empty());
 }
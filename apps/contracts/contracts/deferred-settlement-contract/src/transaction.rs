@@ -1,15 +1,20 @@
+use crate::condition::validate_condition;
+use crate::error::Error;
+use crate::index;
+use crate::storage;
 use crate::types::{
-    DataKey, DeferredTransaction, Error, SettlementCondition, TransactionEvent, TransactionStatus,
+    ConditionNode, DataKey, DeferredTransaction, TransactionEvent, TransactionStatus,
 };
-use soroban_sdk::{token, Address, Env, Symbol};
+use soroban_sdk::{token, Address, Env, Symbol, Vec};
 
 pub fn create_transaction(
     env: Env,
     buyer: Address,
     seller: Address,
     amount: i128,
-    condition: SettlementCondition,
+    condition: Vec<ConditionNode>,
     duration: u64,
+    token: Option<Address>,
 ) -> Result<u128, Error> {
     buyer.require_auth();
 
@@ -17,6 +22,22 @@ pub fn create_transaction(
         return Err(Error::InvalidAmount);
     }
 
+    validate_condition(&condition)?;
+
+    // Fall back to the global default token, then make sure it is a token contract
+    let token = match token {
+        Some(token) => token,
+        None => env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenContract)
+            .ok_or(Error::TokenContractNotSet)?,
+    };
+    let token_client = token::Client::new(&env, &token);
+    if !matches!(token_client.try_decimals(), Ok(Ok(_))) {
+        return Err(Error::InvalidToken);
+    }
+
     let transaction_id: u128 = env
         .storage()
         .instance()
@@ -28,25 +49,19 @@ pub fn create_transaction(
         buyer: buyer.clone(),
         seller: seller.clone(),
         amount,
+        token,
         condition,
+        satisfied_leaves: Vec::new(&env),
         created_at: env.ledger().timestamp(),
         deadline: env.ledger().timestamp() + duration,
         status: TransactionStatus::Pending,
     };
 
-    // Get the token contract ID from storage or environment
-    let token_contract_id = env
-        .storage()
-        .instance()
-        .get(&DataKey::TokenContract)
-        .ok_or(Error::TokenContractNotSet)?;
-    let token_client = token::Client::new(&env, &token_contract_id);
     token_client.transfer(&buyer, &env.current_contract_address(), &amount);
 
     // Store transaction
-    env.storage()
-        .instance()
-        .set(&DataKey::Transaction(transaction_id), &transaction);
+    storage::set_transaction(&env, &transaction);
+    index::index_transaction(&env, &transaction);
     env.storage()
         .instance()
         .set(&DataKey::TotalTransactions, &(transaction_id + 1));
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, Symbol};
+use soroban_sdk::{contracttype, Address, Symbol, Vec};
 
 #[contracttype]
 pub enum DataKey {
@@ -6,11 +6,57 @@ pub enum DataKey {
     TotalTransactions,
     Admin,
     TokenContract,
+    Oracles,
+    OracleConfig,
+    RefundGracePeriod,
+    IndexCount(IndexKey),
+    IndexPage(IndexKey, u32),
+    IndexPosition(IndexKey, u128),
+    DueStart, // Lowest due bucket that may still hold transactions
 }
 
+/// A paged list of transaction ids. Each index keeps a count, fixed-size
+/// pages of ids and the position of every id, so adding and removing an
+/// entry touches a bounded number of entries however large the index grows.
 #[contracttype]
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IndexKey {
+    Buyer(Address),
+    Seller(Address),
+    Status(TransactionStatus),
+    Due(u64), // Pending TimeBased transactions by deadline bucket
+}
+
+/// A page of due transactions and where the scan stopped. `next_bucket` is
+/// None once every bucket up to the current time has been scanned.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DuePage {
+    pub transactions: Vec<DeferredTransaction>,
+    pub next_bucket: Option<u64>,
+    pub next_position: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DeferredTransaction {
+    pub id: u128,
+    pub buyer: Address,
+    pub seller: Address,
+    pub amount: i128,
+    pub token: Address, // Asset locked at creation, used for every payout
+    pub condition: Vec<ConditionNode>, // Expression tree, root at index 0
+    pub satisfied_leaves: Vec<u32>, // Leaf indices satisfied so far
+    pub created_at: u64,
+    pub deadline: u64,
+    pub status: TransactionStatus,
+}
+
+/// Shape of transactions written to instance storage before they moved to
+/// per-transaction persistent entries. Only read by `migrate_transactions`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyDeferredTransaction {
     pub id: u128,
     pub buyer: Address,
     pub seller: Address,
@@ -25,12 +71,38 @@ pub struct DeferredTransaction {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SettlementCondition {
     TimeBased,
+    TimeElapsed(u64), // Seconds since creation
     BuyerApproval,
     OracleConfirmation,
 }
 
+/// Node of a condition expression. Combinators refer to their children by
+/// index, and children always come after their parent in the list.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConditionNode {
+    Leaf(SettlementCondition),
+    And(Vec<u32>),
+    Or(Vec<u32>),
+}
+
+/// What an oracle reports for a transaction, and when it last observed it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleAttestation {
+    pub confirmed: bool,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    pub quorum: u32,        // Confirmations needed to settle
+    pub max_staleness: u64, // Oldest attestation age accepted, in seconds
+}
+
 #[contracttype]
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum TransactionStatus {
     Pending,
     Completed,
@@ -43,19 +115,10 @@ pub enum TransactionStatus {
 pub enum TransactionEvent {
     TransactionCreated(u128, Address, Address, i128),
     ConditionVerified(u128, Symbol),
+    LeafSatisfied(u128, u32),
     FundsReleased(u128, Address),
     FundsRefunded(u128, Address),
     DisputeInitiated(u128),
     DisputeResolved(u128, bool),
-}
-
-#[contracttype]
-#[derive(Clone, Debug)]
-pub enum Error {
-    InvalidAmount,
-    TransactionNotFound,
-    NotPending,
-    Unauthorized,
-    ConditionNotMet,
-    TokenContractNotSet,
+    TransactionsMigrated(u128, u32),
 }
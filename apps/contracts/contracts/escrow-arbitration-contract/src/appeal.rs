@@ -0,0 +1,222 @@
+use crate::contract::{execute_ruling, get_escrow, validate_award};
+use crate::error::ContractError;
+use crate::escrow_storage::*;
+use crate::events::*;
+use crate::registry;
+use soroban_sdk::{token, Address, Env, Map, Vec};
+
+fn get_terms_and_ruling(env: &Env, escrow_id: u64) -> Result<(AppealTerms, Ruling), ContractError> {
+    match (get_appeal_terms(env, escrow_id), get_ruling(env, escrow_id)) {
+        (Some(terms), Some(ruling)) => Ok((terms, ruling)),
+        _ => Err(ContractError::AppealNotAllowed),
+    }
+}
+
+fn voting_deadline(terms: &AppealTerms, appeal: &Appeal) -> u64 {
+    appeal.filed_at.saturating_add(terms.voting_period)
+}
+
+/// Appeal panel members who voted for `award`.
+fn backers(env: &Env, appeal: &Appeal, award: &Award) -> Vec<Address> {
+    let mut members = Vec::new(env);
+    for (member, vote) in appeal.votes.iter() {
+        if vote == *award {
+            members.push_back(member);
+        }
+    }
+    members
+}
+
+fn return_bond(env: &Env, escrow: &Escrow, appeal: &Appeal, recipient: &Address) {
+    let token_client = token::Client::new(env, &escrow.token);
+    token_client.transfer(
+        &env.current_contract_address(),
+        recipient,
+        &(appeal.bond as i128),
+    );
+}
+
+/// Executes the award a majority of the appeal panel backed. The fee goes to
+/// the members who voted for it.
+fn decide_appeal(env: &Env, escrow: &mut Escrow, ruling: &Ruling, appeal: &Appeal, award: Award) {
+    let overturned = award != ruling.award;
+    if overturned {
+        registry::record_overturned(env, escrow, &ruling.award, &appeal.appellant);
+    }
+
+    // A successful appeal gets its bond back; a failed one compensates the respondent
+    let bond_recipient = if overturned {
+        appeal.appellant.clone()
+    } else if appeal.appellant == escrow.buyer {
+        escrow.seller.clone()
+    } else {
+        escrow.buyer.clone()
+    };
+    return_bond(env, escrow, appeal, &bond_recipient);
+
+    execute_ruling(env, escrow, &award, &backers(env, appeal, &award));
+
+    emit_appeal_resolved(env, escrow.id, appeal.appellant.clone(), award, overturned);
+}
+
+/// Escalates a ruling to the appeal panel. Either party can appeal unless the
+/// award gave them the full amount, and only while the appeal window is
+/// open. The bond is held until the panel decides.
+pub fn file_appeal(env: &Env, escrow_id: u64, appellant: &Address) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+
+    if escrow.status != EscrowStatus::Ruled {
+        return Err(ContractError::AppealNotAllowed);
+    }
+
+    let (terms, ruling) = get_terms_and_ruling(env, escrow_id)?;
+    let awarded = if *appellant == escrow.buyer {
+        ruling.award.to_buyer
+    } else if *appellant == escrow.seller {
+        ruling.award.to_seller
+    } else {
+        return Err(ContractError::ParticipantOnly);
+    };
+    if awarded == escrow.amount {
+        return Err(ContractError::AppealNotAllowed);
+    }
+
+    if env.ledger().timestamp() > ruling.ruled_at.saturating_add(terms.window) {
+        return Err(ContractError::AppealWindowClosed);
+    }
+
+    let token_client = token::Client::new(env, &escrow.token);
+    token_client.transfer(
+        appellant,
+        &env.current_contract_address(),
+        &(terms.bond as i128),
+    );
+
+    escrow.status = EscrowStatus::Appealed;
+    set_escrow(env, &escrow);
+    registry::record_appealed(env, &escrow, &ruling.award);
+    set_appeal(
+        env,
+        escrow_id,
+        &Appeal {
+            appellant: appellant.clone(),
+            bond: terms.bond,
+            filed_at: env.ledger().timestamp(),
+            votes: Map::new(env),
+        },
+    );
+
+    emit_appeal_filed(env, escrow_id, appellant.clone(), terms.bond);
+
+    Ok(())
+}
+
+/// Records an appeal panel member's award until the voting period ends. Once
+/// a majority of the panel proposes the same award, it executes and the bond
+/// is settled.
+pub fn vote_appeal(
+    env: &Env,
+    escrow_id: u64,
+    member: &Address,
+    award: Award,
+) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+
+    if escrow.status != EscrowStatus::Appealed {
+        return Err(ContractError::AppealNotAllowed);
+    }
+
+    let (terms, ruling) = get_terms_and_ruling(env, escrow_id)?;
+    if !terms.panel.contains(member) {
+        return Err(ContractError::PanelMemberOnly);
+    }
+
+    validate_award(&escrow, &award)?;
+
+    let mut appeal = get_appeal(env, escrow_id).ok_or(ContractError::AppealNotAllowed)?;
+    if env.ledger().timestamp() > voting_deadline(&terms, &appeal) {
+        return Err(ContractError::VotingClosed);
+    }
+    if appeal.votes.contains_key(member.clone()) {
+        return Err(ContractError::AlreadyVoted);
+    }
+    appeal.votes.set(member.clone(), award.clone());
+    set_appeal(env, escrow_id, &appeal);
+
+    emit_appeal_vote_cast(env, escrow_id, member.clone(), award.clone());
+
+    if backers(env, &appeal, &award).len() > terms.panel.len() / 2 {
+        decide_appeal(env, &mut escrow, &ruling, &appeal, award);
+    }
+
+    Ok(())
+}
+
+/// Closes appeal voting once its period has passed without a majority of the
+/// panel. A majority of the votes actually cast decides. With no votes or no
+/// majority among them the original ruling stands and the appellant gets the
+/// bond back, since the panel never reached a decision. Callable by anyone.
+pub fn close_appeal(env: &Env, escrow_id: u64) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+
+    if escrow.status != EscrowStatus::Appealed {
+        return Err(ContractError::AppealNotAllowed);
+    }
+
+    let (terms, ruling) = get_terms_and_ruling(env, escrow_id)?;
+    let appeal = get_appeal(env, escrow_id).ok_or(ContractError::AppealNotAllowed)?;
+    if env.ledger().timestamp() <= voting_deadline(&terms, &appeal) {
+        return Err(ContractError::VotingOpen);
+    }
+
+    let mut missed = Vec::new(env);
+    for member in terms.panel.iter() {
+        if !appeal.votes.contains_key(member.clone()) {
+            missed.push_back(member);
+        }
+    }
+
+    let majority = appeal
+        .votes
+        .values()
+        .iter()
+        .find(|award| backers(env, &appeal, award).len() * 2 > appeal.votes.len());
+
+    emit_appeal_vote_closed(env, escrow_id, missed, majority.is_some());
+
+    match majority {
+        Some(award) => decide_appeal(env, &mut escrow, &ruling, &appeal, award),
+        None => {
+            return_bond(env, &escrow, &appeal, &appeal.appellant);
+            let deciders = registry::deciding_arbitrators(env, &escrow, &ruling.award);
+            execute_ruling(env, &mut escrow, &ruling.award, &deciders);
+            emit_appeal_resolved(env, escrow_id, appeal.appellant, ruling.award, false);
+        }
+    }
+
+    Ok(())
+}
+
+/// Executes the arbitrator's ruling once the appeal window has closed with
+/// no appeal. Callable by anyone.
+pub fn finalize_ruling(env: &Env, escrow_id: u64) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+
+    if escrow.status != EscrowStatus::Ruled {
+        return Err(ContractError::OperationNotAllowed);
+    }
+
+    let (terms, ruling) = get_terms_and_ruling(env, escrow_id)?;
+    if env.ledger().timestamp() <= ruling.ruled_at.saturating_add(terms.window) {
+        return Err(ContractError::AppealWindowOpen);
+    }
+
+    let deciders = registry::deciding_arbitrators(env, &escrow, &ruling.award);
+    execute_ruling(env, &mut escrow, &ruling.award, &deciders);
+
+    Ok(())
+}
+
+pub fn get_appeal_details(env: &Env, escrow_id: u64) -> Result<Appeal, ContractError> {
+    get_appeal(env, escrow_id).ok_or(ContractError::DataNotFound)
+}
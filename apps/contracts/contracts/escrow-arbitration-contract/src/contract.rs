@@ -2,25 +2,58 @@ use crate::error::ContractError;
 use crate::escrow_storage;
 use crate::escrow_storage::*;
 use crate::events::*;
+use crate::panel;
+use crate::registry;
 use soroban_sdk::{token, Address, Env, String, Vec};
 
 pub fn create_escrow(
     env: &Env,
     buyer: &Address,
     seller: &Address,
-    arbitrator: &Address,
     token: &Address,
     amount: u128,
     description: String,
+    terms: EscrowTerms,
 ) -> Result<u64, ContractError> {
+    let arbitrator = &terms.arbitrator;
+    let panel = terms.panel();
+    let appeal_terms = terms.appeal();
+    let delivery = terms.delivery();
+
     if amount == 0 {
         return Err(ContractError::InvalidAmount);
     }
 
+    if let Some(terms) = &delivery {
+        // Delivery on the last day must still get a full inspection period
+        if terms.deadline <= env.ledger().timestamp()
+            || terms.inspection_period == 0
+            || terms.release_deadline < terms.deadline.saturating_add(terms.inspection_period)
+        {
+            return Err(ContractError::InvalidDeadline);
+        }
+    }
+
     if buyer == seller || buyer == arbitrator || seller == arbitrator {
         return Err(ContractError::InvalidInput);
     }
 
+    registry::check_fee_rate(terms.max_fee_bps)?;
+
+    if let Some(panel) = &panel {
+        validate_panel(panel, buyer, seller, arbitrator)?;
+    }
+
+    if let Some(terms) = &appeal_terms {
+        validate_appeal_terms(terms, buyer, seller, arbitrator, &panel)?;
+    }
+
+    let arbitrators = match &panel {
+        Some(panel) => panel.members.clone(),
+        None => Vec::from_array(env, [arbitrator.clone()]),
+    };
+    registry::check_min_stake(env, &arbitrators, terms.min_arbitrator_stake)?;
+
     let escrow_id = escrow_storage::get_next_escrow_id(env);
     let timestamp = env.ledger().timestamp();
 
@@ -38,12 +71,30 @@ pub fn create_escrow(
         completed_at: None,
         disputed_at: None,
         dispute_reason: None,
+        delivery_deadline: delivery.as_ref().map(|terms| terms.deadline),
+        cancel_requested_by: None,
+        inspection_period: delivery.as_ref().map(|terms| terms.inspection_period),
+        delivered_at: None,
+        release_deadline: delivery.as_ref().map(|terms| terms.release_deadline),
+        max_fee_bps: registry::snapshot_fee_bps(env, &arbitrators, terms.max_fee_bps),
+        min_arbitrator_stake: terms.min_arbitrator_stake,
     };
 
     set_escrow(env, &escrow);
+    if let Some(terms) = &appeal_terms {
+        set_appeal_terms(env, escrow_id, terms);
+    }
     add_user_escrow(env, buyer, escrow_id);
     add_user_escrow(env, seller, escrow_id);
     add_user_escrow(env, arbitrator, escrow_id);
+    if let Some(panel) = &panel {
+        set_panel(env, escrow_id, panel);
+        for member in panel.members.iter() {
+            if member != *arbitrator {
+                add_user_escrow(env, &member, escrow_id);
+            }
+        }
+    }
 
     emit_escrow_created(
         env,
@@ -70,6 +121,10 @@ pub fn deposit(env: &Env, escrow_id: u64, buyer: &Address) -> Result<(), Contrac
         return Err(ContractError::EscrowAlreadyFunded);
     }
 
+    // Stake may have been withdrawn since creation; it is locked from here on
+    let arbitrators = registry::case_arbitrators(env, &escrow);
+    registry::check_min_stake(env, &arbitrators, escrow.min_arbitrator_stake)?;
+
     // Transfer tokens from buyer to contract
     let contract_address = env.current_contract_address();
     let token_client = token::Client::new(env, &escrow.token);
@@ -80,6 +135,7 @@ pub fn deposit(env: &Env, escrow_id: u64, buyer: &Address) -> Result<(), Contrac
     escrow.funded_at = Some(env.ledger().timestamp());
 
     set_escrow(env, &escrow);
+    registry::open_case(env, &escrow);
 
     emit_deposited(env, escrow_id, buyer.clone(), escrow.amount);
 
@@ -97,6 +153,73 @@ pub fn release_funds(env: &Env, escrow_id: u64, buyer: &Address) -> Result<(), C
         return Err(ContractError::EscrowNotFunded);
     }
 
+    pay_seller(env, &mut escrow);
+
+    Ok(())
+}
+
+pub fn mark_delivered(env: &Env, escrow_id: u64, seller: &Address) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+
+    if escrow.seller != *seller {
+        return Err(ContractError::SellerOnly);
+    }
+
+    if escrow.status != EscrowStatus::Funded {
+        return Err(ContractError::EscrowNotFunded);
+    }
+
+    if escrow.delivered_at.is_some() {
+        return Err(ContractError::AlreadyDelivered);
+    }
+
+    let timestamp = env.ledger().timestamp();
+    if escrow
+        .delivery_deadline
+        .is_some_and(|deadline| timestamp > deadline)
+    {
+        return Err(ContractError::DeliveryDeadlinePassed);
+    }
+
+    escrow.delivered_at = Some(timestamp);
+
+    set_escrow(env, &escrow);
+
+    emit_delivered(env, escrow_id, seller.clone(), timestamp);
+
+    Ok(())
+}
+
+pub fn auto_release(env: &Env, escrow_id: u64) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+
+    if escrow.status != EscrowStatus::Funded {
+        return Err(ContractError::EscrowNotFunded);
+    }
+
+    // An undelivered escrow stays refundable to the buyer, never auto-released
+    let delivered_at = escrow.delivered_at.ok_or(ContractError::NotDelivered)?;
+
+    let timestamp = env.ledger().timestamp();
+    let release_due = escrow
+        .release_deadline
+        .is_some_and(|deadline| timestamp > deadline);
+    if !release_due {
+        let inspection_period = escrow
+            .inspection_period
+            .ok_or(ContractError::OperationNotAllowed)?;
+
+        if timestamp <= delivered_at.saturating_add(inspection_period) {
+            return Err(ContractError::InspectionPeriodActive);
+        }
+    }
+
+    pay_seller(env, &mut escrow);
+
+    Ok(())
+}
+
+fn pay_seller(env: &Env, escrow: &mut Escrow) {
     // Transfer tokens from contract to seller
     let contract_address = env.current_contract_address();
     let token_client = token::Client::new(env, &escrow.token);
@@ -106,17 +229,16 @@ pub fn release_funds(env: &Env, escrow_id: u64, buyer: &Address) -> Result<(), C
     escrow.status = EscrowStatus::Completed;
     escrow.completed_at = Some(env.ledger().timestamp());
 
-    set_escrow(env, &escrow);
+    set_escrow(env, escrow);
+    registry::close_case(env, escrow);
 
     emit_funds_released(
         env,
-        escrow_id,
-        buyer.clone(),
+        escrow.id,
+        escrow.buyer.clone(),
         escrow.seller.clone(),
         escrow.amount,
     );
-
-    Ok(())
 }
 
 pub fn raise_dispute(
@@ -150,49 +272,178 @@ pub fn arbitrate(
     env: &Env,
     escrow_id: u64,
     arbitrator: &Address,
-    release_to_seller: bool,
+    award: Award,
 ) -> Result<(), ContractError> {
     let mut escrow = get_escrow(env, escrow_id)?;
 
-    if escrow.arbitrator != *arbitrator {
-        return Err(ContractError::ArbitratorOnly);
+    let panel = get_panel(env, escrow_id);
+    match &panel {
+        Some(panel) if !panel.members.contains(arbitrator) => {
+            return Err(ContractError::PanelMemberOnly);
+        }
+        None if escrow.arbitrator != *arbitrator => {
+            return Err(ContractError::ArbitratorOnly);
+        }
+        _ => {}
     }
 
     if escrow.status != EscrowStatus::Disputed {
         return Err(ContractError::EscrowNotDisputed);
     }
 
+    validate_award(&escrow, &award)?;
+
+    // On a panel this is one member's vote; the majority issues the ruling
+    if let Some(panel) = panel {
+        return panel::cast_vote(env, &mut escrow, &panel, arbitrator, award);
+    }
+
+    issue_ruling(env, &mut escrow, award);
+
+    Ok(())
+}
+
+/// Turns a decided award into the escrow's ruling. With appeal terms the
+/// ruling waits out the appeal window first, otherwise it executes now.
+pub(crate) fn issue_ruling(env: &Env, escrow: &mut Escrow, award: Award) {
+    let escrow_id = escrow.id;
+    registry::record_resolved(env, escrow, &award);
+    if get_appeal_terms(env, escrow_id).is_some() {
+        escrow.status = EscrowStatus::Ruled;
+        set_escrow(env, escrow);
+        set_ruling(
+            env,
+            escrow_id,
+            &Ruling {
+                award: award.clone(),
+                ruled_at: env.ledger().timestamp(),
+            },
+        );
+
+        emit_ruling_issued(env, escrow_id, escrow.arbitrator.clone(), award);
+        return;
+    }
+
+    let deciders = registry::deciding_arbitrators(env, escrow, &award);
+    execute_ruling(env, escrow, &award, &deciders);
+}
+
+/// Checks that the award legs add up to exactly the escrowed amount and that
+/// the fee stays within the rate fixed at creation.
+pub(crate) fn validate_award(escrow: &Escrow, award: &Award) -> Result<(), ContractError> {
+    let total = award
+        .to_seller
+        .checked_add(award.to_buyer)
+        .and_then(|sum| sum.checked_add(award.arbitrator_fee))
+        .ok_or(ContractError::InvalidAward)?;
+    if total != escrow.amount {
+        return Err(ContractError::InvalidAward);
+    }
+    registry::check_fee(escrow, award.arbitrator_fee)
+}
+
+fn validate_panel(
+    panel: &PanelTerms,
+    buyer: &Address,
+    seller: &Address,
+    arbitrator: &Address,
+) -> Result<(), ContractError> {
+    let size = panel.members.len();
+    if size < 3 || size % 2 != 1 || panel.voting_period == 0 {
+        return Err(ContractError::InvalidPanel);
+    }
+    if !panel.members.contains(arbitrator) {
+        return Err(ContractError::InvalidPanel);
+    }
+
+    for (index, member) in panel.members.iter().enumerate() {
+        if member == *buyer || member == *seller {
+            return Err(ContractError::InvalidPanel);
+        }
+        if panel.members.first_index_of(&member) != Some(index as u32) {
+            return Err(ContractError::InvalidPanel);
+        }
+    }
+    Ok(())
+}
+
+fn validate_appeal_terms(
+    terms: &AppealTerms,
+    buyer: &Address,
+    seller: &Address,
+    arbitrator: &Address,
+    panel: &Option<PanelTerms>,
+) -> Result<(), ContractError> {
+    let size = terms.panel.len();
+    if size < 3 || size % 2 != 1 || terms.window == 0 || terms.bond == 0 || terms.voting_period == 0
+    {
+        return Err(ContractError::InvalidAppealTerms);
+    }
+
+    for (index, member) in terms.panel.iter().enumerate() {
+        if member == *buyer || member == *seller || member == *arbitrator {
+            return Err(ContractError::InvalidAppealTerms);
+        }
+        // Appeals go to a different bench than the first instance
+        if let Some(panel) = panel {
+            if panel.members.contains(&member) {
+                return Err(ContractError::InvalidAppealTerms);
+            }
+        }
+        if terms.panel.first_index_of(&member) != Some(index as u32) {
+            return Err(ContractError::InvalidAppealTerms);
+        }
+    }
+    Ok(())
+}
+
+/// Pays out every leg of a final award and closes the escrow. The fee is
+/// shared equally by `fee_recipients`, the arbitrators who backed the award;
+/// the first of them also takes any remainder.
+pub(crate) fn execute_ruling(
+    env: &Env,
+    escrow: &mut Escrow,
+    award: &Award,
+    fee_recipients: &Vec<Address>,
+) {
     let contract_address = env.current_contract_address();
     let token_client = token::Client::new(env, &escrow.token);
 
-    if release_to_seller {
-        // Release funds to seller
-        token_client.transfer(&contract_address, &escrow.seller, &(escrow.amount as i128));
-    } else {
-        // Refund to buyer
-        token_client.transfer(&contract_address, &escrow.buyer, &(escrow.amount as i128));
+    let legs = [
+        (&escrow.seller, award.to_seller),
+        (&escrow.buyer, award.to_buyer),
+    ];
+    for (recipient, amount) in legs {
+        if amount > 0 {
+            token_client.transfer(&contract_address, recipient, &(amount as i128));
+        }
+    }
+
+    // A ruling with a fee always has at least one arbitrator behind it
+    if award.arbitrator_fee > 0 {
+        let count = fee_recipients.len() as u128;
+        let share = award.arbitrator_fee / count;
+        let remainder = award.arbitrator_fee - share * count;
+        for (index, recipient) in fee_recipients.iter().enumerate() {
+            let amount = if index == 0 { share + remainder } else { share };
+            if amount > 0 {
+                token_client.transfer(&contract_address, &recipient, &(amount as i128));
+            }
+        }
     }
 
     escrow.status = EscrowStatus::Completed;
     escrow.completed_at = Some(env.ledger().timestamp());
 
-    set_escrow(env, &escrow);
+    set_escrow(env, escrow);
+    registry::close_case(env, escrow);
 
-    emit_arbitration_completed(
-        env,
-        escrow_id,
-        arbitrator.clone(),
-        release_to_seller,
-        escrow.amount,
-    );
-
-    Ok(())
+    emit_arbitration_completed(env, escrow.id, escrow.arbitrator.clone(), award.clone());
 }
 
 pub fn refund(env: &Env, escrow_id: u64, requester: &Address) -> Result<(), ContractError> {
     let mut escrow = get_escrow(env, escrow_id)?;
 
-    // Only buyer or seller can request refund for non-disputed escrows
     if escrow.buyer != *requester && escrow.seller != *requester {
         return Err(ContractError::ParticipantOnly);
     }
@@ -202,6 +453,55 @@ pub fn refund(env: &Env, escrow_id: u64, requester: &Address) -> Result<(), Cont
         return Err(ContractError::OperationNotAllowed);
     }
 
+    // The seller can always back out. The buyer can only cancel alone once
+    // the seller has missed the delivery deadline without marking delivery.
+    if escrow.buyer == *requester {
+        if escrow.delivered_at.is_some() {
+            return Err(ContractError::AlreadyDelivered);
+        }
+        match escrow.delivery_deadline {
+            Some(deadline) if env.ledger().timestamp() > deadline => {}
+            _ => return Err(ContractError::DeliveryDeadlineNotReached),
+        }
+    }
+
+    refund_buyer(env, &mut escrow);
+
+    Ok(())
+}
+
+pub fn request_cancellation(
+    env: &Env,
+    escrow_id: u64,
+    party: &Address,
+) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+
+    if escrow.buyer != *party && escrow.seller != *party {
+        return Err(ContractError::ParticipantOnly);
+    }
+
+    if escrow.status != EscrowStatus::Funded {
+        return Err(ContractError::OperationNotAllowed);
+    }
+
+    match &escrow.cancel_requested_by {
+        Some(requester) if requester == party => Err(ContractError::CancellationAlreadyRequested),
+        // The other party already asked, so both have now consented
+        Some(_) => {
+            refund_buyer(env, &mut escrow);
+            Ok(())
+        }
+        None => {
+            escrow.cancel_requested_by = Some(party.clone());
+            set_escrow(env, &escrow);
+            emit_cancellation_requested(env, escrow_id, party.clone());
+            Ok(())
+        }
+    }
+}
+
+fn refund_buyer(env: &Env, escrow: &mut Escrow) {
     // Transfer tokens back to buyer
     let contract_address = env.current_contract_address();
     let token_client = token::Client::new(env, &escrow.token);
@@ -211,11 +511,10 @@ pub fn refund(env: &Env, escrow_id: u64, requester: &Address) -> Result<(), Cont
     escrow.status = EscrowStatus::Cancelled;
     escrow.completed_at = Some(env.ledger().timestamp());
 
-    set_escrow(env, &escrow);
-
-    emit_refunded(env, escrow_id, escrow.buyer.clone(), escrow.amount);
+    set_escrow(env, escrow);
+    registry::close_case(env, escrow);
 
-    Ok(())
+    emit_refunded(env, escrow.id, escrow.buyer.clone(), escrow.amount);
 }
 
 pub fn get_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, ContractError> {
@@ -34,4 +34,42 @@ pub enum ContractError {
     InvalidInput = 19,
     DataNotFound = 20,
     OperationNotAllowed = 21,
+
+    // Appeal errors
+    InvalidAppealTerms = 22,
+    AppealNotAllowed = 23,
+    AppealWindowClosed = 24,
+    AppealWindowOpen = 25,
+    PanelMemberOnly = 26,
+    AlreadyVoted = 27,
+    InvalidAward = 28,
+
+    // Panel errors
+    InvalidPanel = 29,
+    VotingClosed = 30,
+    VotingOpen = 31,
+
+    // Registry errors
+    ArbitratorNotRegistered = 32,
+    ArbitratorAlreadyRegistered = 33,
+    InsufficientStake = 34,
+    StakeLocked = 35,
+    InvalidFeeRate = 36,
+    StakeTokenNotSet = 37,
+
+    // Cancellation errors
+    InvalidDeadline = 38,
+    DeliveryDeadlineNotReached = 39,
+    CancellationAlreadyRequested = 40,
+
+    // Delivery errors
+    NotDelivered = 41,
+    AlreadyDelivered = 42,
+    InspectionPeriodActive = 43,
+
+    // Registry errors
+    StakeOutstanding = 44,
+
+    // Delivery errors
+    DeliveryDeadlinePassed = 45,
 }
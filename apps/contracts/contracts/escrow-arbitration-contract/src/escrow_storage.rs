@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, String, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -8,6 +8,95 @@ pub enum EscrowStatus {
     Completed,
     Disputed,
     Cancelled,
+    Ruled,    // Arbitrator ruled, appeal window open
+    Appealed, // Appeal bond posted, awaiting the appeal panel
+}
+
+/// Who decides disputes and on what terms, agreed at creation. An empty
+/// panel leaves the arbitrator to rule alone, an empty appeal panel makes
+/// rulings final, and a zero delivery deadline leaves out delivery terms.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowTerms {
+    pub arbitrator: Address,
+    pub panel: PanelTerms,
+    pub appeal: AppealTerms,
+    pub delivery: DeliveryTerms,
+    pub min_arbitrator_stake: u128, // Zero accepts any arbitrator
+    pub max_fee_bps: u32,           // Highest arbitration fee, in basis points of the amount
+}
+
+impl EscrowTerms {
+    pub fn panel(&self) -> Option<PanelTerms> {
+        (!self.panel.members.is_empty()).then(|| self.panel.clone())
+    }
+
+    pub fn appeal(&self) -> Option<AppealTerms> {
+        (!self.appeal.panel.is_empty()).then(|| self.appeal.clone())
+    }
+
+    pub fn delivery(&self) -> Option<DeliveryTerms> {
+        (self.delivery.deadline != 0).then(|| self.delivery.clone())
+    }
+}
+
+/// First-instance panel named at creation. The escrow's arbitrator presides
+/// and must be one of the members.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PanelTerms {
+    pub members: Vec<Address>, // Odd-sized, at least three members
+    pub voting_period: u64,    // Seconds after the dispute to cast votes
+}
+
+/// Delivery terms agreed at creation. Once the deadline passes
+/// without delivery the buyer may cancel alone; once the inspection period
+/// after delivery ends without a dispute anyone may release to the seller.
+/// The release deadline bounds how long the buyer can stay silent: after it
+/// anyone may release a delivered, undisputed escrow.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeliveryTerms {
+    pub deadline: u64,          // Absolute timestamp for the seller to deliver by
+    pub inspection_period: u64, // Seconds after delivery for the buyer to dispute
+    pub release_deadline: u64,  // Absolute timestamp for the auto-release
+}
+
+/// Appeal terms agreed at creation. Without them the arbitrator's ruling
+/// executes immediately.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppealTerms {
+    pub panel: Vec<Address>, // Odd-sized, at least three members
+    pub window: u64,         // Seconds after the ruling to file an appeal
+    pub bond: u128,          // Posted by the appellant in the escrow token
+    pub voting_period: u64,  // Seconds after the appeal for the panel to vote
+}
+
+/// How a ruling splits the escrowed amount. The legs must add up to
+/// `escrow.amount`; the fee goes to the arbitrators who backed the ruling.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Award {
+    pub to_seller: u128,
+    pub to_buyer: u128,
+    pub arbitrator_fee: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ruling {
+    pub award: Award,
+    pub ruled_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Appeal {
+    pub appellant: Address,
+    pub bond: u128,
+    pub filed_at: u64,
+    pub votes: Map<Address, Award>, // Panel member -> proposed award
 }
 
 #[contracttype]
@@ -26,6 +115,28 @@ pub struct Escrow {
     pub completed_at: Option<u64>,
     pub disputed_at: Option<u64>,
     pub dispute_reason: Option<String>,
+    pub delivery_deadline: Option<u64>, // After it passes the buyer may cancel alone
+    pub cancel_requested_by: Option<Address>, // Party awaiting the other's consent to cancel
+    pub inspection_period: Option<u64>, // Seconds after delivery before anyone may release
+    pub delivered_at: Option<u64>,
+    pub release_deadline: Option<u64>, // After it anyone may release a delivered escrow
+    pub max_fee_bps: u32,              // Fee cap fixed at creation, in basis points of the amount
+    pub min_arbitrator_stake: u128,
+}
+
+/// Public record of a registered arbitrator. The stake is held in the
+/// registry's stake token and is locked while the arbitrator has open cases.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitratorProfile {
+    pub arbitrator: Address,
+    pub stake: u128,
+    pub fee_bps: u32, // Highest arbitration fee accepted, in basis points of the amount
+    pub active_cases: u32,
+    pub cases_resolved: u32,
+    pub cases_appealed: u32,
+    pub cases_overturned: u32,
+    pub registered_at: u64,
 }
 
 // Storage key types
@@ -35,6 +146,12 @@ pub enum StorageKey {
     Escrow(u64),
     UserEscrows(Address),
     EscrowCounter,
+    Panel(u64),
+    PanelVotes(u64),
+    AppealTerms(u64),
+    Ruling(u64),
+    Appeal(u64),
+    Arbitrator(Address),
 }
 
 // Storage key constants
@@ -78,3 +195,69 @@ pub fn add_user_escrow(env: &Env, user: &Address, escrow_id: u64) {
     escrows.push_back(escrow_id);
     env.storage().persistent().set(&key, &escrows);
 }
+
+// Appeal storage functions
+pub fn get_appeal_terms(env: &Env, escrow_id: u64) -> Option<AppealTerms> {
+    let key = StorageKey::AppealTerms(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+pub fn set_appeal_terms(env: &Env, escrow_id: u64, terms: &AppealTerms) {
+    let key = StorageKey::AppealTerms(escrow_id);
+    env.storage().persistent().set(&key, terms);
+}
+
+pub fn get_ruling(env: &Env, escrow_id: u64) -> Option<Ruling> {
+    let key = StorageKey::Ruling(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+pub fn set_ruling(env: &Env, escrow_id: u64, ruling: &Ruling) {
+    let key = StorageKey::Ruling(escrow_id);
+    env.storage().persistent().set(&key, ruling);
+}
+
+pub fn get_appeal(env: &Env, escrow_id: u64) -> Option<Appeal> {
+    let key = StorageKey::Appeal(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+pub fn set_appeal(env: &Env, escrow_id: u64, appeal: &Appeal) {
+    let key = StorageKey::Appeal(escrow_id);
+    env.storage().persistent().set(&key, appeal);
+}
+
+// Panel storage functions
+pub fn get_panel(env: &Env, escrow_id: u64) -> Option<PanelTerms> {
+    let key = StorageKey::Panel(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+pub fn set_panel(env: &Env, escrow_id: u64, panel: &PanelTerms) {
+    let key = StorageKey::Panel(escrow_id);
+    env.storage().persistent().set(&key, panel);
+}
+
+pub fn get_panel_votes(env: &Env, escrow_id: u64) -> Map<Address, Award> {
+    let key = StorageKey::PanelVotes(escrow_id);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Map::new(env))
+}
+
+pub fn set_panel_votes(env: &Env, escrow_id: u64, votes: &Map<Address, Award>) {
+    let key = StorageKey::PanelVotes(escrow_id);
+    env.storage().persistent().set(&key, votes);
+}
+
+// Arbitrator registry storage functions
+pub fn get_arbitrator_profile(env: &Env, arbitrator: &Address) -> Option<ArbitratorProfile> {
+    let key = StorageKey::Arbitrator(arbitrator.clone());
+    env.storage().persistent().get(&key)
+}
+
+pub fn set_arbitrator_profile(env: &Env, profile: &ArbitratorProfile) {
+    let key = StorageKey::Arbitrator(profile.arbitrator.clone());
+    env.storage().persistent().set(&key, profile);
+}
@@ -1,4 +1,5 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use crate::escrow_storage::Award;
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -42,7 +43,88 @@ pub struct DisputeRaisedEvent {
 pub struct ArbitrationCompletedEvent {
     pub escrow_id: u64,
     pub arbitrator: Address,
-    pub release_to_seller: bool,
+    pub to_seller: u128,
+    pub to_buyer: u128,
+    pub arbitrator_fee: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RulingIssuedEvent {
+    pub escrow_id: u64,
+    pub arbitrator: Address,
+    pub award: Award,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PanelVoteCastEvent {
+    pub escrow_id: u64,
+    pub member: Address,
+    pub award: Award,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PanelVoteClosedEvent {
+    pub escrow_id: u64,
+    pub missed: Vec<Address>, // Members who did not vote before the deadline
+    pub decided: bool,        // False when the buyer was refunded by default
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppealFiledEvent {
+    pub escrow_id: u64,
+    pub appellant: Address,
+    pub bond: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppealVoteCastEvent {
+    pub escrow_id: u64,
+    pub member: Address,
+    pub award: Award,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppealVoteClosedEvent {
+    pub escrow_id: u64,
+    pub missed: Vec<Address>, // Appeal panel members who did not vote in time
+    pub decided: bool,        // False when the original ruling stood by default
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppealResolvedEvent {
+    pub escrow_id: u64,
+    pub appellant: Address,
+    pub award: Award,
+    pub overturned: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitratorRegisteredEvent {
+    pub arbitrator: Address,
+    pub stake: u128,
+    pub fee_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StakeChangedEvent {
+    pub arbitrator: Address,
+    pub stake: u128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitratorSlashedEvent {
+    pub escrow_id: u64,
+    pub arbitrator: Address,
     pub amount: u128,
 }
 
@@ -54,6 +136,21 @@ pub struct RefundedEvent {
     pub amount: u128,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeliveredEvent {
+    pub escrow_id: u64,
+    pub seller: Address,
+    pub delivered_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancellationRequestedEvent {
+    pub escrow_id: u64,
+    pub party: Address,
+}
+
 pub fn emit_escrow_created(
     env: &Env,
     escrow_id: u64,
@@ -110,18 +207,13 @@ pub fn emit_dispute_raised(env: &Env, escrow_id: u64, disputer: Address, reason:
     env.events().publish(("dispute_raised",), event);
 }
 
-pub fn emit_arbitration_completed(
-    env: &Env,
-    escrow_id: u64,
-    arbitrator: Address,
-    release_to_seller: bool,
-    amount: u128,
-) {
+pub fn emit_arbitration_completed(env: &Env, escrow_id: u64, arbitrator: Address, award: Award) {
     let event = ArbitrationCompletedEvent {
         escrow_id,
         arbitrator,
-        release_to_seller,
-        amount,
+        to_seller: award.to_seller,
+        to_buyer: award.to_buyer,
+        arbitrator_fee: award.arbitrator_fee,
     };
     env.events().publish(("arbitration_completed",), event);
 }
@@ -134,3 +226,110 @@ pub fn emit_refunded(env: &Env, escrow_id: u64, buyer: Address, amount: u128) {
     };
     env.events().publish(("refunded",), event);
 }
+
+pub fn emit_delivered(env: &Env, escrow_id: u64, seller: Address, delivered_at: u64) {
+    let event = DeliveredEvent {
+        escrow_id,
+        seller,
+        delivered_at,
+    };
+    env.events().publish(("delivered",), event);
+}
+
+pub fn emit_cancellation_requested(env: &Env, escrow_id: u64, party: Address) {
+    let event = CancellationRequestedEvent { escrow_id, party };
+    env.events().publish(("cancellation_requested",), event);
+}
+
+pub fn emit_ruling_issued(env: &Env, escrow_id: u64, arbitrator: Address, award: Award) {
+    let event = RulingIssuedEvent {
+        escrow_id,
+        arbitrator,
+        award,
+    };
+    env.events().publish(("ruling_issued",), event);
+}
+
+pub fn emit_panel_vote_cast(env: &Env, escrow_id: u64, member: Address, award: Award) {
+    let event = PanelVoteCastEvent {
+        escrow_id,
+        member,
+        award,
+    };
+    env.events().publish(("panel_vote_cast",), event);
+}
+
+pub fn emit_panel_vote_closed(env: &Env, escrow_id: u64, missed: Vec<Address>, decided: bool) {
+    let event = PanelVoteClosedEvent {
+        escrow_id,
+        missed,
+        decided,
+    };
+    env.events().publish(("panel_vote_closed",), event);
+}
+
+pub fn emit_appeal_filed(env: &Env, escrow_id: u64, appellant: Address, bond: u128) {
+    let event = AppealFiledEvent {
+        escrow_id,
+        appellant,
+        bond,
+    };
+    env.events().publish(("appeal_filed",), event);
+}
+
+pub fn emit_appeal_vote_cast(env: &Env, escrow_id: u64, member: Address, award: Award) {
+    let event = AppealVoteCastEvent {
+        escrow_id,
+        member,
+        award,
+    };
+    env.events().publish(("appeal_vote_cast",), event);
+}
+
+pub fn emit_appeal_vote_closed(env: &Env, escrow_id: u64, missed: Vec<Address>, decided: bool) {
+    let event = AppealVoteClosedEvent {
+        escrow_id,
+        missed,
+        decided,
+    };
+    env.events().publish(("appeal_vote_closed",), event);
+}
+
+pub fn emit_appeal_resolved(
+    env: &Env,
+    escrow_id: u64,
+    appellant: Address,
+    award: Award,
+    overturned: bool,
+) {
+    let event = AppealResolvedEvent {
+        escrow_id,
+        appellant,
+        award,
+        overturned,
+    };
+    env.events().publish(("appeal_resolved",), event);
+}
+
+pub fn emit_arbitrator_registered(env: &Env, arbitrator: Address, stake: u128, fee_bps: u32) {
+    let event = ArbitratorRegisteredEvent {
+        arbitrator,
+        stake,
+        fee_bps,
+    };
+    env.events().publish(("arbitrator_registered",), event);
+}
+
+pub fn emit_stake_changed(env: &Env, arbitrator: Address, stake: u128) {
+    let event = StakeChangedEvent { arbitrator, stake };
+    env.events().publish(("stake_changed",), event);
+}
+
+pub fn emit_arbitrator_slashed(env: &Env, escrow_id: u64, arbitrator: Address, amount: u128) {
+    let event = ArbitratorSlashedEvent {
+        escrow_id,
+        arbitrator,
+        amount,
+    };
+    env.events().publish(("arbitrator_slashed",), event);
+}
@@ -1,9 +1,12 @@
 #![no_std]
 
+mod appeal;
 mod contract;
 mod error;
 mod escrow_storage;
 mod events;
+mod panel;
+mod registry;
 mod storage;
 mod test;
 
@@ -28,26 +31,66 @@ impl EscrowArbitrationContract {
         Ok(())
     }
 
-    /// Create a new escrow transaction
+    /// Set the token arbitrators stake in and the share of stake slashed per
+    /// overturned ruling, in basis points (admin only)
+    pub fn set_registry_config(
+        env: Env,
+        admin: Address,
+        stake_token: Address,
+        slash_bps: u32,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        registry::set_registry_config(&env, &admin, &stake_token, slash_bps)
+    }
+
+    /// Register as an arbitrator by staking a bond and advertising a fee rate
+    pub fn register_arbitrator(
+        env: Env,
+        arbitrator: Address,
+        stake: u128,
+        fee_bps: u32,
+    ) -> Result<(), ContractError> {
+        arbitrator.require_auth();
+        registry::register_arbitrator(&env, &arbitrator, stake, fee_bps)
+    }
+
+    /// Add to a registered arbitrator's stake
+    pub fn add_stake(env: Env, arbitrator: Address, amount: u128) -> Result<(), ContractError> {
+        arbitrator.require_auth();
+        registry::add_stake(&env, &arbitrator, amount)
+    }
+
+    /// Withdraw stake (only while the arbitrator has no open cases)
+    pub fn withdraw_stake(
+        env: Env,
+        arbitrator: Address,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        arbitrator.require_auth();
+        registry::withdraw_stake(&env, &arbitrator, amount)
+    }
+
+    /// Update a registered arbitrator's advertised fee rate
+    pub fn set_fee_rate(env: Env, arbitrator: Address, fee_bps: u32) -> Result<(), ContractError> {
+        arbitrator.require_auth();
+        registry::set_fee_rate(&env, &arbitrator, fee_bps)
+    }
+
+    /// Create a new escrow transaction. `terms` names the arbitrator and any
+    /// panel, appeal, delivery and minimum stake terms. A non-zero
+    /// `min_arbitrator_stake` requires the arbitrator, and every panel
+    /// member, to be registered with at least that stake.
     pub fn create_escrow(
         env: Env,
         buyer: Address,
         seller: Address,
-        arbitrator: Address,
         token: Address,
         amount: u128,
         description: String,
+        terms: escrow_storage::EscrowTerms,
     ) -> Result<u64, ContractError> {
         buyer.require_auth();
-        contract::create_escrow(
-            &env,
-            &buyer,
-            &seller,
-            &arbitrator,
-            &token,
-            amount,
-            description,
-        )
+        contract::create_escrow(&env, &buyer, &seller, &token, amount, description, terms)
     }
 
     /// Deposit funds into escrow
@@ -62,6 +105,20 @@ impl EscrowArbitrationContract {
         contract::release_funds(&env, escrow_id, &buyer)
     }
 
+    /// Mark the goods or service as delivered (seller only, by the delivery
+    /// deadline), starting the buyer's inspection period
+    pub fn mark_delivered(env: Env, escrow_id: u64, seller: Address) -> Result<(), ContractError> {
+        seller.require_auth();
+        contract::mark_delivered(&env, escrow_id, &seller)
+    }
+
+    /// Release funds to seller once the inspection period after delivery
+    /// ends, or the release deadline passes, without a dispute (callable by
+    /// anyone)
+    pub fn auto_release(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        contract::auto_release(&env, escrow_id)
+    }
+
     /// Raise a dispute
     pub fn raise_dispute(
         env: Env,
@@ -73,28 +130,97 @@ impl EscrowArbitrationContract {
         contract::raise_dispute(&env, escrow_id, &disputer, reason)
     }
 
-    /// Arbitrate dispute (arbitrator only)
+    /// Arbitrate dispute (arbitrator only) by splitting the amount between
+    /// seller, buyer and an optional arbitrator fee. On a panel escrow each
+    /// member calls this to vote and the majority award becomes the ruling.
+    /// With appeal terms the ruling only executes once the appeal window
+    /// closes or the appeal panel decides.
     pub fn arbitrate(
         env: Env,
         escrow_id: u64,
         arbitrator: Address,
-        release_to_seller: bool,
+        award: escrow_storage::Award,
     ) -> Result<(), ContractError> {
         arbitrator.require_auth();
-        contract::arbitrate(&env, escrow_id, &arbitrator, release_to_seller)
+        contract::arbitrate(&env, escrow_id, &arbitrator, award)
     }
 
-    /// Refund funds to buyer
+    /// Close panel voting after its deadline; a majority of cast votes decides
+    pub fn close_panel_vote(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        panel::close_panel_vote(&env, escrow_id)
+    }
+
+    /// Appeal a ruling (losing party only, posts the appeal bond)
+    pub fn file_appeal(env: Env, escrow_id: u64, appellant: Address) -> Result<(), ContractError> {
+        appellant.require_auth();
+        appeal::file_appeal(&env, escrow_id, &appellant)
+    }
+
+    /// Vote on an appeal (appeal panel members only)
+    pub fn vote_appeal(
+        env: Env,
+        escrow_id: u64,
+        member: Address,
+        award: escrow_storage::Award,
+    ) -> Result<(), ContractError> {
+        member.require_auth();
+        appeal::vote_appeal(&env, escrow_id, &member, award)
+    }
+
+    /// Close appeal voting after its deadline; a majority of cast votes
+    /// decides, otherwise the original ruling stands and the bond is returned
+    pub fn close_appeal(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        appeal::close_appeal(&env, escrow_id)
+    }
+
+    /// Execute an unappealed ruling after the appeal window closes
+    pub fn finalize_ruling(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        appeal::finalize_ruling(&env, escrow_id)
+    }
+
+    /// Refund funds to buyer. The seller may do so at any time; the buyer
+    /// only after the delivery deadline has passed
     pub fn refund(env: Env, escrow_id: u64, requester: Address) -> Result<(), ContractError> {
         requester.require_auth();
         contract::refund(&env, escrow_id, &requester)
     }
 
+    /// Consent to cancel a funded escrow; refunds the buyer once both parties agree
+    pub fn request_cancellation(
+        env: Env,
+        escrow_id: u64,
+        party: Address,
+    ) -> Result<(), ContractError> {
+        party.require_auth();
+        contract::request_cancellation(&env, escrow_id, &party)
+    }
+
     /// Get escrow details
     pub fn get_escrow(env: Env, escrow_id: u64) -> Result<escrow_storage::Escrow, ContractError> {
         contract::get_escrow(&env, escrow_id)
     }
 
+    /// Get the appeal filed against an escrow's ruling, with per-member votes
+    pub fn get_appeal(env: Env, escrow_id: u64) -> Result<escrow_storage::Appeal, ContractError> {
+        appeal::get_appeal_details(&env, escrow_id)
+    }
+
+    /// Get the votes cast so far by an escrow's arbitration panel
+    pub fn get_panel_votes(
+        env: Env,
+        escrow_id: u64,
+    ) -> Result<soroban_sdk::Map<Address, escrow_storage::Award>, ContractError> {
+        panel::get_votes(&env, escrow_id)
+    }
+
+    /// Get a registered arbitrator's stake, fee rate and case record
+    pub fn get_arbitrator(
+        env: Env,
+        arbitrator: Address,
+    ) -> Result<escrow_storage::ArbitratorProfile, ContractError> {
+        registry::get_arbitrator(&env, &arbitrator)
+    }
+
     /// Get escrows for a user
     pub fn get_user_escrows(
         env: Env,
@@ -0,0 +1,91 @@
+use crate::contract::{get_escrow, issue_ruling};
+use crate::error::ContractError;
+use crate::escrow_storage::*;
+use crate::events::*;
+use soroban_sdk::{Address, Env, Map, Vec};
+
+fn voting_deadline(escrow: &Escrow, panel: &PanelTerms) -> u64 {
+    escrow
+        .disputed_at
+        .unwrap_or(escrow.created_at)
+        .saturating_add(panel.voting_period)
+}
+
+fn support_for(votes: &Map<Address, Award>, award: &Award) -> u32 {
+    votes.values().iter().filter(|vote| vote == award).count() as u32
+}
+
+/// Records a panel member's award. As soon as a majority of the full panel
+/// backs the same award, it becomes the escrow's ruling.
+pub fn cast_vote(
+    env: &Env,
+    escrow: &mut Escrow,
+    panel: &PanelTerms,
+    member: &Address,
+    award: Award,
+) -> Result<(), ContractError> {
+    if env.ledger().timestamp() > voting_deadline(escrow, panel) {
+        return Err(ContractError::VotingClosed);
+    }
+
+    let mut votes = get_panel_votes(env, escrow.id);
+    if votes.contains_key(member.clone()) {
+        return Err(ContractError::AlreadyVoted);
+    }
+    votes.set(member.clone(), award.clone());
+    set_panel_votes(env, escrow.id, &votes);
+
+    emit_panel_vote_cast(env, escrow.id, member.clone(), award.clone());
+
+    if support_for(&votes, &award) > panel.members.len() / 2 {
+        issue_ruling(env, escrow, award);
+    }
+
+    Ok(())
+}
+
+/// Closes panel voting once the deadline has passed without a majority.
+/// Members who did not vote are reported and left out: a majority of the
+/// votes actually cast decides. With no votes or a tie the buyer is refunded
+/// in full. Callable by anyone.
+pub fn close_panel_vote(env: &Env, escrow_id: u64) -> Result<(), ContractError> {
+    let mut escrow = get_escrow(env, escrow_id)?;
+    let panel = get_panel(env, escrow_id).ok_or(ContractError::OperationNotAllowed)?;
+
+    if escrow.status != EscrowStatus::Disputed {
+        return Err(ContractError::EscrowNotDisputed);
+    }
+
+    if env.ledger().timestamp() <= voting_deadline(&escrow, &panel) {
+        return Err(ContractError::VotingOpen);
+    }
+
+    let votes = get_panel_votes(env, escrow_id);
+    let mut missed = Vec::new(env);
+    for member in panel.members.iter() {
+        if !votes.contains_key(member.clone()) {
+            missed.push_back(member);
+        }
+    }
+
+    let majority = votes
+        .values()
+        .iter()
+        .find(|award| support_for(&votes, award) * 2 > votes.len());
+    let award = majority.clone().unwrap_or(Award {
+        to_seller: 0,
+        to_buyer: escrow.amount,
+        arbitrator_fee: 0,
+    });
+
+    emit_panel_vote_closed(env, escrow_id, missed, majority.is_some());
+
+    issue_ruling(env, &mut escrow, award);
+
+    Ok(())
+}
+
+pub fn get_votes(env: &Env, escrow_id: u64) -> Result<Map<Address, Award>, ContractError> {
+    get_panel(env, escrow_id).ok_or(ContractError::DataNotFound)?;
+    Ok(get_panel_votes(env, escrow_id))
+}
@@ -0,0 +1,277 @@
+use crate::error::ContractError;
+use crate::escrow_storage::*;
+use crate::events::*;
+use crate::storage;
+use soroban_sdk::{token, Address, Env, Vec};
+
+const BASIS_POINTS: u32 = 10_000;
+
+fn require_admin(env: &Env, admin: &Address) -> Result<(), ContractError> {
+    let stored = storage::get_admin(env).ok_or(ContractError::NotInitialized)?;
+    if stored != *admin {
+        return Err(ContractError::AdminOnly);
+    }
+    Ok(())
+}
+
+fn stake_token(env: &Env) -> Result<token::Client<'_>, ContractError> {
+    let token = storage::get_stake_token(env).ok_or(ContractError::StakeTokenNotSet)?;
+    Ok(token::Client::new(env, &token))
+}
+
+fn get_profile(env: &Env, arbitrator: &Address) -> Result<ArbitratorProfile, ContractError> {
+    get_arbitrator_profile(env, arbitrator).ok_or(ContractError::ArbitratorNotRegistered)
+}
+
+pub fn set_registry_config(
+    env: &Env,
+    admin: &Address,
+    stake_token: &Address,
+    slash_bps: u32,
+) -> Result<(), ContractError> {
+    require_admin(env, admin)?;
+    if slash_bps > BASIS_POINTS {
+        return Err(ContractError::InvalidInput);
+    }
+    // Stakes are paid back in the token they were posted in
+    let current = storage::get_stake_token(env);
+    if current.is_some_and(|current| current != *stake_token) && storage::get_total_stake(env) > 0 {
+        return Err(ContractError::StakeOutstanding);
+    }
+    storage::set_stake_token(env, stake_token);
+    storage::set_slash_bps(env, slash_bps);
+    Ok(())
+}
+
+pub fn check_fee_rate(fee_bps: u32) -> Result<(), ContractError> {
+    if fee_bps > BASIS_POINTS {
+        return Err(ContractError::InvalidFeeRate);
+    }
+    Ok(())
+}
+
+pub fn register_arbitrator(
+    env: &Env,
+    arbitrator: &Address,
+    stake: u128,
+    fee_bps: u32,
+) -> Result<(), ContractError> {
+    if get_arbitrator_profile(env, arbitrator).is_some() {
+        return Err(ContractError::ArbitratorAlreadyRegistered);
+    }
+    if stake == 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    check_fee_rate(fee_bps)?;
+
+    stake_token(env)?.transfer(
+        arbitrator,
+        &env.current_contract_address(),
+        &(stake as i128),
+    );
+
+    storage::set_total_stake(env, storage::get_total_stake(env) + stake);
+
+    let profile = ArbitratorProfile {
+        arbitrator: arbitrator.clone(),
+        stake,
+        fee_bps,
+        active_cases: 0,
+        cases_resolved: 0,
+        cases_appealed: 0,
+        cases_overturned: 0,
+        registered_at: env.ledger().timestamp(),
+    };
+    set_arbitrator_profile(env, &profile);
+
+    emit_arbitrator_registered(env, arbitrator.clone(), stake, fee_bps);
+
+    Ok(())
+}
+
+pub fn add_stake(env: &Env, arbitrator: &Address, amount: u128) -> Result<(), ContractError> {
+    let mut profile = get_profile(env, arbitrator)?;
+    if amount == 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    stake_token(env)?.transfer(
+        arbitrator,
+        &env.current_contract_address(),
+        &(amount as i128),
+    );
+
+    profile.stake += amount;
+    storage::set_total_stake(env, storage::get_total_stake(env) + amount);
+    set_arbitrator_profile(env, &profile);
+
+    emit_stake_changed(env, arbitrator.clone(), profile.stake);
+
+    Ok(())
+}
+
+/// Returns stake to the arbitrator. The stake stays locked while any escrow
+/// naming them is still open, so it is always there to slash.
+pub fn withdraw_stake(env: &Env, arbitrator: &Address, amount: u128) -> Result<(), ContractError> {
+    let mut profile = get_profile(env, arbitrator)?;
+    if profile.active_cases > 0 {
+        return Err(ContractError::StakeLocked);
+    }
+    if amount == 0 || amount > profile.stake {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    stake_token(env)?.transfer(
+        &env.current_contract_address(),
+        arbitrator,
+        &(amount as i128),
+    );
+
+    profile.stake -= amount;
+    storage::set_total_stake(env, storage::get_total_stake(env) - amount);
+    set_arbitrator_profile(env, &profile);
+
+    emit_stake_changed(env, arbitrator.clone(), profile.stake);
+
+    Ok(())
+}
+
+pub fn set_fee_rate(env: &Env, arbitrator: &Address, fee_bps: u32) -> Result<(), ContractError> {
+    let mut profile = get_profile(env, arbitrator)?;
+    check_fee_rate(fee_bps)?;
+    profile.fee_bps = fee_bps;
+    set_arbitrator_profile(env, &profile);
+    Ok(())
+}
+
+pub fn get_arbitrator(env: &Env, arbitrator: &Address) -> Result<ArbitratorProfile, ContractError> {
+    get_profile(env, arbitrator)
+}
+
+/// Checks that every arbitrator on a new escrow is registered with at least
+/// `min_stake`. A zero minimum accepts any arbitrator.
+pub fn check_min_stake(
+    env: &Env,
+    arbitrators: &Vec<Address>,
+    min_stake: u128,
+) -> Result<(), ContractError> {
+    if min_stake == 0 {
+        return Ok(());
+    }
+    for arbitrator in arbitrators.iter() {
+        if get_profile(env, &arbitrator)?.stake < min_stake {
+            return Err(ContractError::InsufficientStake);
+        }
+    }
+    Ok(())
+}
+
+fn fee_cap(amount: u128, fee_bps: u32) -> u128 {
+    amount * fee_bps as u128 / BASIS_POINTS as u128
+}
+
+/// Fee rate to fix on a new escrow: the agreed cap, lowered to the rate any
+/// registered arbitrator on the case advertises at creation. Later changes
+/// to an advertised rate do not affect the escrow.
+pub fn snapshot_fee_bps(env: &Env, arbitrators: &Vec<Address>, agreed_bps: u32) -> u32 {
+    let mut fee_bps = agreed_bps;
+    for arbitrator in arbitrators.iter() {
+        if let Some(profile) = get_arbitrator_profile(env, &arbitrator) {
+            fee_bps = fee_bps.min(profile.fee_bps);
+        }
+    }
+    fee_bps
+}
+
+/// Rejects an arbitration fee above the rate fixed on the escrow at creation.
+pub fn check_fee(escrow: &Escrow, fee: u128) -> Result<(), ContractError> {
+    if fee > fee_cap(escrow.amount, escrow.max_fee_bps) {
+        return Err(ContractError::InvalidAward);
+    }
+    Ok(())
+}
+
+/// Arbitrators sitting on an escrow: the panel if there is one, otherwise
+/// the single arbitrator.
+pub fn case_arbitrators(env: &Env, escrow: &Escrow) -> Vec<Address> {
+    match get_panel(env, escrow.id) {
+        Some(panel) => panel.members,
+        None => Vec::from_array(env, [escrow.arbitrator.clone()]),
+    }
+}
+
+/// Arbitrators whose ruling decided an escrow: panel members who voted for
+/// the award, or the single arbitrator.
+pub fn deciding_arbitrators(env: &Env, escrow: &Escrow, award: &Award) -> Vec<Address> {
+    if get_panel(env, escrow.id).is_none() {
+        return Vec::from_array(env, [escrow.arbitrator.clone()]);
+    }
+    let mut deciders = Vec::new(env);
+    for (member, vote) in get_panel_votes(env, escrow.id).iter() {
+        if vote == *award {
+            deciders.push_back(member);
+        }
+    }
+    deciders
+}
+
+fn update_profiles(env: &Env, arbitrators: &Vec<Address>, update: impl Fn(&mut ArbitratorProfile)) {
+    for arbitrator in arbitrators.iter() {
+        if let Some(mut profile) = get_arbitrator_profile(env, &arbitrator) {
+            update(&mut profile);
+            set_arbitrator_profile(env, &profile);
+        }
+    }
+}
+
+pub fn open_case(env: &Env, escrow: &Escrow) {
+    update_profiles(env, &case_arbitrators(env, escrow), |profile| {
+        profile.active_cases += 1
+    });
+}
+
+pub fn close_case(env: &Env, escrow: &Escrow) {
+    update_profiles(env, &case_arbitrators(env, escrow), |profile| {
+        profile.active_cases = profile.active_cases.saturating_sub(1)
+    });
+}
+
+pub fn record_resolved(env: &Env, escrow: &Escrow, award: &Award) {
+    update_profiles(env, &deciding_arbitrators(env, escrow, award), |profile| {
+        profile.cases_resolved += 1
+    });
+}
+
+pub fn record_appealed(env: &Env, escrow: &Escrow, award: &Award) {
+    update_profiles(env, &deciding_arbitrators(env, escrow, award), |profile| {
+        profile.cases_appealed += 1
+    });
+}
+
+/// Counts an overturned ruling against the arbitrators who made it and
+/// slashes part of their stake to the appellant.
+pub fn record_overturned(env: &Env, escrow: &Escrow, award: &Award, appellant: &Address) {
+    let slash_bps = storage::get_slash_bps(env) as u128;
+    for arbitrator in deciding_arbitrators(env, escrow, award).iter() {
+        let mut profile = match get_arbitrator_profile(env, &arbitrator) {
+            Some(profile) => profile,
+            None => continue,
+        };
+        profile.cases_overturned += 1;
+
+        let slashed = profile.stake * slash_bps / BASIS_POINTS as u128;
+        if slashed > 0 {
+            if let Ok(token_client) = stake_token(env) {
+                token_client.transfer(
+                    &env.current_contract_address(),
+                    appellant,
+                    &(slashed as i128),
+                );
+                profile.stake -= slashed;
+                storage::set_total_stake(env, storage::get_total_stake(env) - slashed);
+                emit_arbitrator_slashed(env, escrow.id, arbitrator.clone(), slashed);
+            }
+        }
+        set_arbitrator_profile(env, &profile);
+    }
+}
@@ -1,6 +1,11 @@
 use soroban_sdk::{Address, Env};
 
 const ADMIN_KEY: &str = "admin";
+const STAKE_TOKEN_KEY: &str = "stake_token";
+const SLASH_BPS_KEY: &str = "slash_bps";
+const TOTAL_STAKE_KEY: &str = "total_stake";
+
+pub const DEFAULT_SLASH_BPS: u32 = 1_000; // 10% of stake per overturned ruling
 
 pub fn has_admin(env: &Env) -> bool {
     env.storage().instance().has(&ADMIN_KEY)
@@ -9,3 +14,35 @@ pub fn has_admin(env: &Env) -> bool {
 pub fn set_admin(env: &Env, admin: &Address) {
     env.storage().instance().set(&ADMIN_KEY, admin);
 }
+
+pub fn get_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ADMIN_KEY)
+}
+
+pub fn get_stake_token(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&STAKE_TOKEN_KEY)
+}
+
+pub fn set_stake_token(env: &Env, token: &Address) {
+    env.storage().instance().set(&STAKE_TOKEN_KEY, token);
+}
+
+pub fn get_slash_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&SLASH_BPS_KEY)
+        .unwrap_or(DEFAULT_SLASH_BPS)
+}
+
+pub fn set_slash_bps(env: &Env, slash_bps: u32) {
+    env.storage().instance().set(&SLASH_BPS_KEY, &slash_bps);
+}
+
+// Stake held for all arbitrators, in the stake token
+pub fn get_total_stake(env: &Env) -> u128 {
+    env.storage().instance().get(&TOTAL_STAKE_KEY).unwrap_or(0)
+}
+
+pub fn set_total_stake(env: &Env, total: u128) {
+    env.storage().instance().set(&TOTAL_STAKE_KEY, &total);
+}
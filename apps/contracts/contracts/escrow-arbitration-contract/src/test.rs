@@ -2,14 +2,42 @@
 extern crate std;
 
 use crate::error::ContractError;
-use crate::escrow_storage::EscrowStatus;
+use crate::escrow_storage::{
+    AppealTerms, Award, DeliveryTerms, EscrowStatus, EscrowTerms, PanelTerms,
+};
+use crate::events::{ArbitrationCompletedEvent, PanelVoteClosedEvent};
 use crate::{EscrowArbitrationContract, EscrowArbitrationContractClient};
-use soroban_sdk::{testutils::Address as _, token, Address, Env, String};
+use soroban_sdk::{
+    testutils::{Address as _, Events as _, Ledger as _},
+    token, vec, Address, Env, IntoVal, String, Vec,
+};
 use token::Client as TokenClient;
 use token::StellarAssetClient as TokenAdminClient;
 
 const ESCROW_AMOUNT: u128 = 1000;
 const TEST_DESCRIPTION: &str = "Test escrow transaction";
+const APPEAL_BOND: u128 = 100;
+const APPEAL_WINDOW: u64 = 3 * 24 * 60 * 60;
+const VOTING_PERIOD: u64 = 5 * 24 * 60 * 60;
+const DELIVERY_PERIOD: u64 = 7 * 24 * 60 * 60;
+const INSPECTION_PERIOD: u64 = 2 * 24 * 60 * 60;
+const RELEASE_PERIOD: u64 = 30 * 24 * 60 * 60;
+
+fn seller_award() -> Award {
+    Award {
+        to_seller: ESCROW_AMOUNT,
+        to_buyer: 0,
+        arbitrator_fee: 0,
+    }
+}
+
+fn buyer_award() -> Award {
+    Award {
+        to_seller: 0,
+        to_buyer: ESCROW_AMOUNT,
+        arbitrator_fee: 0,
+    }
+}
 
 fn create_token_contract<'a>(
     env: &Env,
@@ -69,14 +97,60 @@ impl<'a> EscrowTest<'a> {
         }
     }
 
+    /// Terms naming `arbitrator` with no panel, appeal or delivery terms.
+    fn terms_for(&self, arbitrator: &Address) -> EscrowTerms {
+        EscrowTerms {
+            arbitrator: arbitrator.clone(),
+            panel: PanelTerms {
+                members: vec![&self.env],
+                voting_period: 0,
+            },
+            appeal: AppealTerms {
+                panel: vec![&self.env],
+                window: 0,
+                bond: 0,
+                voting_period: 0,
+            },
+            delivery: DeliveryTerms {
+                deadline: 0,
+                inspection_period: 0,
+                release_deadline: 0,
+            },
+            min_arbitrator_stake: 0,
+            max_fee_bps: 1_000,
+        }
+    }
+
+    fn terms(&self) -> EscrowTerms {
+        self.terms_for(&self.arbitrator)
+    }
+
     fn create_escrow(&self) -> u64 {
         self.contract.create_escrow(
             &self.buyer,
             &self.seller,
-            &self.arbitrator,
             &self.token.address,
             &ESCROW_AMOUNT,
             &self.description,
+            &self.terms(),
+        )
+    }
+
+    fn create_escrow_with_deadlines(&self) -> u64 {
+        self.contract.create_escrow(
+            &self.buyer,
+            &self.seller,
+            &self.token.address,
+            &ESCROW_AMOUNT,
+            &self.description,
+            &EscrowTerms {
+                delivery: DeliveryTerms {
+                    deadline: self.env.ledger().timestamp() + DELIVERY_PERIOD,
+                    inspection_period: INSPECTION_PERIOD,
+                    release_deadline: self.env.ledger().timestamp() + RELEASE_PERIOD,
+                },
+                ..self.terms()
+            },
         )
     }
 
@@ -84,6 +158,90 @@ impl<'a> EscrowTest<'a> {
         self.contract.deposit(&escrow_id, &self.buyer);
     }
 
+    fn appeal_panel(&self) -> Vec<Address> {
+        vec![
+            &self.env,
+            Address::generate(&self.env),
+            Address::generate(&self.env),
+            Address::generate(&self.env),
+        ]
+    }
+
+    /// Funded and disputed escrow with appeal terms; both parties can post the bond.
+    fn setup_with_appealable_dispute() -> (Self, u64, Vec<Address>) {
+        let test = Self::setup();
+        let panel = test.appeal_panel();
+        let terms = AppealTerms {
+            panel: panel.clone(),
+            window: APPEAL_WINDOW,
+            bond: APPEAL_BOND,
+            voting_period: VOTING_PERIOD,
+        };
+        let escrow_id = test.contract.create_escrow(
+            &test.buyer,
+            &test.seller,
+            &test.token.address,
+            &ESCROW_AMOUNT,
+            &test.description,
+            &EscrowTerms {
+                appeal: terms,
+                ..test.terms()
+            },
+        );
+        test.deposit_funds(escrow_id);
+
+        let token_admin = TokenAdminClient::new(&test.env, &test.token.address);
+        token_admin.mint(&test.buyer, &(APPEAL_BOND as i128));
+        token_admin.mint(&test.seller, &(APPEAL_BOND as i128));
+
+        let reason = String::from_str(&test.env, "Item not as described");
+        test.contract
+            .raise_dispute(&escrow_id, &test.buyer, &reason);
+        (test, escrow_id, panel)
+    }
+
+    /// Funded and disputed escrow decided by a three-member panel that the
+    /// arbitrator presides over.
+    fn setup_with_panel_dispute() -> (Self, u64, Vec<Address>) {
+        let test = Self::setup();
+        let members = vec![
+            &test.env,
+            test.arbitrator.clone(),
+            Address::generate(&test.env),
+            Address::generate(&test.env),
+        ];
+        let panel = PanelTerms {
+            members: members.clone(),
+            voting_period: VOTING_PERIOD,
+        };
+        let escrow_id = test.contract.create_escrow(
+            &test.buyer,
+            &test.seller,
+            &test.token.address,
+            &ESCROW_AMOUNT,
+            &test.description,
+            &EscrowTerms {
+                panel,
+                ..test.terms()
+            },
+        );
+        test.deposit_funds(escrow_id);
+
+        let reason = String::from_str(&test.env, "Item not as described");
+        test.contract
+            .raise_dispute(&escrow_id, &test.buyer, &reason);
+        (test, escrow_id, members)
+    }
+
+    /// Uses the escrow token as the stake token and registers `arbitrator`.
+    fn register_arbitrator(&self, arbitrator: &Address, stake: u128, fee_bps: u32) {
+        self.contract
+            .set_registry_config(&self.admin, &self.token.address, &1_000);
+        TokenAdminClient::new(&self.env, &self.token.address).mint(arbitrator, &(stake as i128));
+        self.contract
+            .register_arbitrator(arbitrator, &stake, &fee_bps);
+    }
+
     fn setup_with_funded_escrow() -> (Self, u64) {
         let test = Self::setup();
         let escrow_id = test.create_escrow();
@@ -132,10 +290,10 @@ fn test_create_escrow_zero_amount() {
     let result = test.contract.try_create_escrow(
         &test.buyer,
         &test.seller,
-        &test.arbitrator,
         &test.token.address,
         &0,
         &test.description,
+        &test.terms(),
     );
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAmount));
@@ -147,10 +305,10 @@ fn test_create_escrow_same_buyer_seller() {
     let result = test.contract.try_create_escrow(
         &test.buyer,
         &test.buyer,
-        &test.arbitrator,
         &test.token.address,
         &ESCROW_AMOUNT,
         &test.description,
+        &test.terms(),
     );
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidInput));
@@ -162,10 +320,10 @@ fn test_create_escrow_same_buyer_arbitrator() {
     let result = test.contract.try_create_escrow(
         &test.buyer,
         &test.seller,
-        &test.buyer,
         &test.token.address,
         &ESCROW_AMOUNT,
         &test.description,
+        &test.terms_for(&test.buyer),
     );
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidInput));
@@ -177,10 +335,10 @@ fn test_create_escrow_same_seller_arbitrator() {
     let result = test.contract.try_create_escrow(
         &test.buyer,
         &test.seller,
-        &test.seller,
         &test.token.address,
         &ESCROW_AMOUNT,
         &test.description,
+        &test.terms_for(&test.seller),
     );
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidInput));
@@ -346,7 +504,8 @@ fn test_arbitrate_release_to_seller() {
     let seller_balance_before = test.token.balance(&test.seller);
     let contract_balance_before = test.token.balance(&test.contract.address);
 
-    test.contract.arbitrate(&escrow_id, &test.arbitrator, &true);
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &seller_award());
 
     assert_eq!(
         test.token.balance(&test.seller),
@@ -374,7 +533,7 @@ fn test_arbitrate_refund_to_buyer() {
     let contract_balance_before = test.token.balance(&test.contract.address);
 
     test.contract
-        .arbitrate(&escrow_id, &test.arbitrator, &false);
+        .arbitrate(&escrow_id, &test.arbitrator, &buyer_award());
 
     assert_eq!(
         test.token.balance(&test.buyer),
@@ -389,6 +548,132 @@ fn test_arbitrate_refund_to_buyer() {
     assert_eq!(escrow.status, EscrowStatus::Completed);
 }
 
+#[test]
+fn test_arbitrate_split_award_with_fee() {
+    let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
+
+    let dispute_reason = String::from_str(&test.env, "Partial delivery");
+    test.contract
+        .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
+
+    let award = Award {
+        to_seller: 650,
+        to_buyer: 300,
+        arbitrator_fee: 50,
+    };
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &award);
+
+    assert_eq!(test.token.balance(&test.seller), 650);
+    assert_eq!(test.token.balance(&test.buyer), 300);
+    assert_eq!(test.token.balance(&test.arbitrator), 50);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+
+    let (_, _, data) = test.env.events().all().last().unwrap();
+    let event: ArbitrationCompletedEvent = data.into_val(&test.env);
+    assert_eq!(
+        event,
+        ArbitrationCompletedEvent {
+            escrow_id,
+            arbitrator: test.arbitrator.clone(),
+            to_seller: 650,
+            to_buyer: 300,
+            arbitrator_fee: 50,
+        }
+    );
+}
+
+#[test]
+fn test_arbitrator_fee_capped_at_agreed_rate() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &ESCROW_AMOUNT,
+        &test.description,
+        &EscrowTerms {
+            max_fee_bps: 200,
+            ..test.terms()
+        },
+    );
+    test.deposit_funds(escrow_id);
+    assert_eq!(test.contract.get_escrow(&escrow_id).max_fee_bps, 200);
+
+    let dispute_reason = String::from_str(&test.env, "Dispute reason");
+    test.contract
+        .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
+
+    // The arbitrator cannot award themselves the escrow
+    let take_all = Award {
+        to_seller: 0,
+        to_buyer: 0,
+        arbitrator_fee: ESCROW_AMOUNT,
+    };
+    let result = test
+        .contract
+        .try_arbitrate(&escrow_id, &test.arbitrator, &take_all);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAward));
+
+    let award = Award {
+        to_seller: 780,
+        to_buyer: 200,
+        arbitrator_fee: 20,
+    };
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &award);
+    assert_eq!(test.token.balance(&test.arbitrator), 20);
+}
+
+#[test]
+fn test_create_escrow_invalid_fee_cap() {
+    let test = EscrowTest::setup();
+    let result = test.contract.try_create_escrow(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &ESCROW_AMOUNT,
+        &test.description,
+        &EscrowTerms {
+            max_fee_bps: 10_001,
+            ..test.terms()
+        },
+    );
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidFeeRate));
+}
+
+#[test]
+fn test_arbitrate_award_must_match_amount() {
+    let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
+
+    let dispute_reason = String::from_str(&test.env, "Dispute reason");
+    test.contract
+        .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
+
+    for award in [
+        Award {
+            to_seller: 700,
+            to_buyer: 200,
+            arbitrator_fee: 0,
+        },
+        Award {
+            to_seller: 700,
+            to_buyer: 300,
+            arbitrator_fee: 50,
+        },
+        Award {
+            to_seller: u128::MAX,
+            to_buyer: 1,
+            arbitrator_fee: 0,
+        },
+    ] {
+        let result = test
+            .contract
+            .try_arbitrate(&escrow_id, &test.arbitrator, &award);
+        assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAward));
+    }
+}
+
 #[test]
 fn test_arbitrate_non_arbitrator() {
     let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
@@ -397,7 +682,9 @@ fn test_arbitrate_non_arbitrator() {
     test.contract
         .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
 
-    let result = test.contract.try_arbitrate(&escrow_id, &test.buyer, &true);
+    let result = test
+        .contract
+        .try_arbitrate(&escrow_id, &test.buyer, &seller_award());
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::ArbitratorOnly));
 }
@@ -408,19 +695,48 @@ fn test_arbitrate_not_disputed() {
 
     let result = test
         .contract
-        .try_arbitrate(&escrow_id, &test.arbitrator, &true);
+        .try_arbitrate(&escrow_id, &test.arbitrator, &seller_award());
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::EscrowNotDisputed));
 }
 
 // Refund tests
 #[test]
-fn test_refund_by_buyer() {
+fn test_refund_by_buyer_before_delivery_deadline() {
     let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
 
+    let result = test.contract.try_refund(&escrow_id, &test.buyer);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::DeliveryDeadlineNotReached)
+    );
+
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+    test.deposit_funds(escrow_id);
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += DELIVERY_PERIOD);
+    let result = test.contract.try_refund(&escrow_id, &test.buyer);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::DeliveryDeadlineNotReached)
+    );
+}
+
+#[test]
+fn test_refund_by_buyer_after_delivery_deadline() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+    test.deposit_funds(escrow_id);
+
     let buyer_balance_before = test.token.balance(&test.buyer);
     let contract_balance_before = test.token.balance(&test.contract.address);
 
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += DELIVERY_PERIOD + 1);
     test.contract.refund(&escrow_id, &test.buyer);
 
     assert_eq!(
@@ -437,6 +753,107 @@ fn test_refund_by_buyer() {
     assert!(escrow.completed_at.is_some());
 }
 
+#[test]
+fn test_create_escrow_with_invalid_delivery_deadlines() {
+    let test = EscrowTest::setup();
+    test.env.ledger().with_mut(|l| l.timestamp = 1_000);
+
+    for delivery in [
+        // Delivery deadline already passed
+        DeliveryTerms {
+            deadline: 1_000,
+            inspection_period: INSPECTION_PERIOD,
+            release_deadline: 1_000 + RELEASE_PERIOD,
+        },
+        // Auto-release before a last-minute delivery could be inspected
+        DeliveryTerms {
+            deadline: 1_000 + DELIVERY_PERIOD,
+            inspection_period: INSPECTION_PERIOD,
+            release_deadline: 1_000 + DELIVERY_PERIOD + INSPECTION_PERIOD - 1,
+        },
+        DeliveryTerms {
+            deadline: u64::MAX,
+            inspection_period: INSPECTION_PERIOD,
+            release_deadline: 1_000 + RELEASE_PERIOD,
+        },
+    ] {
+        let result = test.contract.try_create_escrow(
+            &test.buyer,
+            &test.seller,
+            &test.token.address,
+            &ESCROW_AMOUNT,
+            &test.description,
+            &EscrowTerms {
+                delivery,
+                ..test.terms()
+            },
+        );
+        assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidDeadline));
+    }
+}
+
+#[test]
+fn test_refund_by_buyer_after_delivery() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+    test.deposit_funds(escrow_id);
+
+    test.contract.mark_delivered(&escrow_id, &test.seller);
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += DELIVERY_PERIOD + 1);
+
+    let result = test.contract.try_refund(&escrow_id, &test.buyer);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AlreadyDelivered));
+}
+
+#[test]
+fn test_mutual_cancellation() {
+    let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
+    let buyer_balance_before = test.token.balance(&test.buyer);
+
+    test.contract.request_cancellation(&escrow_id, &test.buyer);
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Funded);
+    assert_eq!(escrow.cancel_requested_by, Some(test.buyer.clone()));
+    assert_eq!(test.token.balance(&test.buyer), buyer_balance_before);
+
+    let result = test
+        .contract
+        .try_request_cancellation(&escrow_id, &test.buyer);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::CancellationAlreadyRequested)
+    );
+
+    test.contract.request_cancellation(&escrow_id, &test.seller);
+    assert_eq!(
+        test.token.balance(&test.buyer),
+        buyer_balance_before + ESCROW_AMOUNT as i128
+    );
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_request_cancellation_non_participant() {
+    let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
+    let outsider = Address::generate(&test.env);
+
+    let result = test
+        .contract
+        .try_request_cancellation(&escrow_id, &outsider);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::ParticipantOnly));
+
+    let dispute_reason = String::from_str(&test.env, "Dispute reason");
+    test.contract
+        .raise_dispute(&escrow_id, &test.seller, &dispute_reason);
+    let result = test
+        .contract
+        .try_request_cancellation(&escrow_id, &test.buyer);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::OperationNotAllowed));
+}
+
 #[test]
 fn test_refund_by_seller() {
     let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
@@ -512,12 +929,13 @@ fn test_multiple_arbitration_attempts() {
     test.contract
         .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
 
-    test.contract.arbitrate(&escrow_id, &test.arbitrator, &true);
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &seller_award());
 
     // Try to arbitrate again
     let result = test
         .contract
-        .try_arbitrate(&escrow_id, &test.arbitrator, &false);
+        .try_arbitrate(&escrow_id, &test.arbitrator, &buyer_award());
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::EscrowNotDisputed));
 }
@@ -594,3 +1012,816 @@ fn test_unauthorized_actions_protection() {
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), Ok(ContractError::ParticipantOnly));
 }
+
+// Appeal tests
+#[test]
+fn test_create_escrow_invalid_appeal_terms() {
+    let test = EscrowTest::setup();
+    let panel = test.appeal_panel();
+
+    let even_panel = vec![&test.env, panel.get_unchecked(0), panel.get_unchecked(1)];
+    let with_arbitrator = vec![
+        &test.env,
+        panel.get_unchecked(0),
+        panel.get_unchecked(1),
+        test.arbitrator.clone(),
+    ];
+    for invalid in [
+        AppealTerms {
+            panel: even_panel,
+            window: APPEAL_WINDOW,
+            bond: APPEAL_BOND,
+            voting_period: VOTING_PERIOD,
+        },
+        AppealTerms {
+            panel: with_arbitrator,
+            window: APPEAL_WINDOW,
+            bond: APPEAL_BOND,
+            voting_period: VOTING_PERIOD,
+        },
+        AppealTerms {
+            panel: panel.clone(),
+            window: 0,
+            bond: APPEAL_BOND,
+            voting_period: VOTING_PERIOD,
+        },
+        AppealTerms {
+            panel: panel.clone(),
+            window: APPEAL_WINDOW,
+            bond: APPEAL_BOND,
+            voting_period: 0,
+        },
+    ] {
+        let result = test.contract.try_create_escrow(
+            &test.buyer,
+            &test.seller,
+            &test.token.address,
+            &ESCROW_AMOUNT,
+            &test.description,
+            &EscrowTerms {
+                appeal: invalid,
+                ..test.terms()
+            },
+        );
+        assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAppealTerms));
+    }
+}
+
+#[test]
+fn test_ruling_finalizes_after_window() {
+    let (test, escrow_id, _) = EscrowTest::setup_with_appealable_dispute();
+
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &seller_award());
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Ruled);
+    assert_eq!(test.token.balance(&test.seller), APPEAL_BOND as i128);
+
+    let result = test.contract.try_finalize_ruling(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AppealWindowOpen));
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += APPEAL_WINDOW + 1);
+    let result = test.contract.try_file_appeal(&escrow_id, &test.buyer);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AppealWindowClosed));
+
+    test.contract.finalize_ruling(&escrow_id);
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert_eq!(
+        test.token.balance(&test.seller),
+        (ESCROW_AMOUNT + APPEAL_BOND) as i128
+    );
+}
+
+#[test]
+fn test_only_losing_party_can_appeal() {
+    let (test, escrow_id, _) = EscrowTest::setup_with_appealable_dispute();
+
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &seller_award());
+
+    let result = test.contract.try_file_appeal(&escrow_id, &test.seller);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AppealNotAllowed));
+}
+
+#[test]
+fn test_successful_appeal_overturns_ruling_and_returns_bond() {
+    let (test, escrow_id, panel) = EscrowTest::setup_with_appealable_dispute();
+
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &seller_award());
+    test.contract.file_appeal(&escrow_id, &test.buyer);
+    assert_eq!(test.token.balance(&test.buyer), 0);
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Appealed);
+
+    let outsider = Address::generate(&test.env);
+    let result = test
+        .contract
+        .try_vote_appeal(&escrow_id, &outsider, &buyer_award());
+    assert_eq!(result.unwrap_err(), Ok(ContractError::PanelMemberOnly));
+
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(0), &buyer_award());
+    let result =
+        test.contract
+            .try_vote_appeal(&escrow_id, &panel.get_unchecked(0), &seller_award());
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AlreadyVoted));
+
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(1), &buyer_award());
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert_eq!(test.contract.get_appeal(&escrow_id).votes.len(), 2);
+    assert_eq!(
+        test.token.balance(&test.buyer),
+        (ESCROW_AMOUNT + APPEAL_BOND) as i128
+    );
+    assert_eq!(test.token.balance(&test.seller), APPEAL_BOND as i128);
+}
+
+#[test]
+fn test_either_party_can_appeal_split_award() {
+    let (test, escrow_id, panel) = EscrowTest::setup_with_appealable_dispute();
+
+    let split = Award {
+        to_seller: 500,
+        to_buyer: 500,
+        arbitrator_fee: 0,
+    };
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &split);
+    test.contract.file_appeal(&escrow_id, &test.seller);
+
+    // Votes only count towards identical awards
+    let with_fee = Award {
+        to_seller: 900,
+        to_buyer: 0,
+        arbitrator_fee: 100,
+    };
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(0), &with_fee);
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(1), &seller_award());
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Appealed
+    );
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(2), &with_fee);
+
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Completed
+    );
+    assert_eq!(
+        test.token.balance(&test.seller),
+        (900 + APPEAL_BOND) as i128
+    );
+    // The overturned arbitrator is not paid; the members behind the award are
+    assert_eq!(test.token.balance(&test.arbitrator), 0);
+    assert_eq!(test.token.balance(&panel.get_unchecked(0)), 50);
+    assert_eq!(test.token.balance(&panel.get_unchecked(1)), 0);
+    assert_eq!(test.token.balance(&panel.get_unchecked(2)), 50);
+}
+
+#[test]
+fn test_failed_appeal_forfeits_bond() {
+    let (test, escrow_id, panel) = EscrowTest::setup_with_appealable_dispute();
+
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &buyer_award());
+    test.contract.file_appeal(&escrow_id, &test.seller);
+
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(0), &seller_award());
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(1), &buyer_award());
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(2), &buyer_award());
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert_eq!(test.token.balance(&test.seller), 0);
+    assert_eq!(
+        test.token.balance(&test.buyer),
+        (ESCROW_AMOUNT + 2 * APPEAL_BOND) as i128
+    );
+}
+
+#[test]
+fn test_close_appeal_decides_on_cast_votes() {
+    let (test, escrow_id, panel) = EscrowTest::setup_with_appealable_dispute();
+
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &seller_award());
+    test.contract.file_appeal(&escrow_id, &test.buyer);
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(0), &buyer_award());
+
+    let result = test.contract.try_close_appeal(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::VotingOpen));
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += VOTING_PERIOD + 1);
+    let result =
+        test.contract
+            .try_vote_appeal(&escrow_id, &panel.get_unchecked(1), &seller_award());
+    assert_eq!(result.unwrap_err(), Ok(ContractError::VotingClosed));
+
+    test.contract.close_appeal(&escrow_id);
+
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Completed
+    );
+    assert_eq!(
+        test.token.balance(&test.buyer),
+        (ESCROW_AMOUNT + APPEAL_BOND) as i128
+    );
+}
+
+#[test]
+fn test_close_appeal_without_majority_keeps_ruling() {
+    let (test, escrow_id, panel) = EscrowTest::setup_with_appealable_dispute();
+
+    let split = Award {
+        to_seller: 500,
+        to_buyer: 450,
+        arbitrator_fee: 50,
+    };
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &split);
+    test.contract.file_appeal(&escrow_id, &test.seller);
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(0), &seller_award());
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(1), &buyer_award());
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += VOTING_PERIOD + 1);
+    test.contract.close_appeal(&escrow_id);
+
+    // The ruling stands and the appellant gets the bond back
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Completed
+    );
+    assert_eq!(
+        test.token.balance(&test.seller),
+        (500 + APPEAL_BOND) as i128
+    );
+    assert_eq!(test.token.balance(&test.buyer), (450 + APPEAL_BOND) as i128);
+    assert_eq!(test.token.balance(&test.arbitrator), 50);
+}
+
+// Panel tests
+#[test]
+fn test_create_escrow_invalid_panel() {
+    let test = EscrowTest::setup();
+    let member = Address::generate(&test.env);
+    let other = Address::generate(&test.env);
+
+    for members in [
+        vec![&test.env, test.arbitrator.clone(), member.clone()],
+        vec![
+            &test.env,
+            member.clone(),
+            other.clone(),
+            Address::generate(&test.env),
+        ],
+        vec![
+            &test.env,
+            test.arbitrator.clone(),
+            member.clone(),
+            test.buyer.clone(),
+        ],
+        vec![
+            &test.env,
+            test.arbitrator.clone(),
+            member.clone(),
+            member.clone(),
+        ],
+    ] {
+        let result = test.contract.try_create_escrow(
+            &test.buyer,
+            &test.seller,
+            &test.token.address,
+            &ESCROW_AMOUNT,
+            &test.description,
+            &EscrowTerms {
+                panel: PanelTerms {
+                    members,
+                    voting_period: VOTING_PERIOD,
+                },
+                ..test.terms()
+            },
+        );
+        assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidPanel));
+    }
+}
+
+#[test]
+fn test_panel_majority_decides() {
+    let (test, escrow_id, members) = EscrowTest::setup_with_panel_dispute();
+    let split = Award {
+        to_seller: 600,
+        to_buyer: 400,
+        arbitrator_fee: 0,
+    };
+
+    let outsider = Address::generate(&test.env);
+    let result = test.contract.try_arbitrate(&escrow_id, &outsider, &split);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::PanelMemberOnly));
+
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(0), &split);
+    let result = test
+        .contract
+        .try_arbitrate(&escrow_id, &members.get_unchecked(0), &split);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AlreadyVoted));
+
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(1), &buyer_award());
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Disputed
+    );
+
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(2), &split);
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Completed
+    );
+    assert_eq!(test.token.balance(&test.seller), 600);
+    assert_eq!(test.token.balance(&test.buyer), 400);
+
+    let votes = test.contract.get_panel_votes(&escrow_id);
+    assert_eq!(votes.len(), 3);
+    assert_eq!(votes.get_unchecked(members.get_unchecked(1)), buyer_award());
+}
+
+#[test]
+fn test_panel_fee_split_among_majority() {
+    let (test, escrow_id, members) = EscrowTest::setup_with_panel_dispute();
+    let split = Award {
+        to_seller: 600,
+        to_buyer: 349,
+        arbitrator_fee: 51,
+    };
+
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(0), &split);
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(1), &buyer_award());
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(2), &split);
+
+    // The minority member is not paid; the odd unit goes to one of the majority
+    let first = test.token.balance(&members.get_unchecked(0));
+    let third = test.token.balance(&members.get_unchecked(2));
+    assert_eq!(test.token.balance(&members.get_unchecked(1)), 0);
+    assert_eq!(first + third, 51);
+    assert!(first == 25 || first == 26);
+    assert_eq!(test.token.balance(&test.seller), 600);
+    assert_eq!(test.token.balance(&test.buyer), 349);
+}
+
+#[test]
+fn test_panel_with_unbounded_voting_period() {
+    let test = EscrowTest::setup();
+    let members = vec![
+        &test.env,
+        test.arbitrator.clone(),
+        Address::generate(&test.env),
+        Address::generate(&test.env),
+    ];
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &ESCROW_AMOUNT,
+        &test.description,
+        &EscrowTerms {
+            panel: PanelTerms {
+                members: members.clone(),
+                voting_period: u64::MAX,
+            },
+            ..test.terms()
+        },
+    );
+    test.deposit_funds(escrow_id);
+    let reason = String::from_str(&test.env, "Item not as described");
+    test.contract
+        .raise_dispute(&escrow_id, &test.buyer, &reason);
+
+    // The deadline saturates instead of overflowing, so voting stays open
+    let result = test.contract.try_close_panel_vote(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::VotingOpen));
+
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(1), &seller_award());
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(2), &seller_award());
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Completed
+    );
+    assert_eq!(test.token.balance(&test.seller), ESCROW_AMOUNT as i128);
+}
+
+#[test]
+fn test_panel_vote_closes_without_non_voting_members() {
+    let (test, escrow_id, members) = EscrowTest::setup_with_panel_dispute();
+
+    test.contract
+        .arbitrate(&escrow_id, &members.get_unchecked(1), &seller_award());
+
+    let result = test.contract.try_close_panel_vote(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::VotingOpen));
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += VOTING_PERIOD + 1);
+    let result = test
+        .contract
+        .try_arbitrate(&escrow_id, &members.get_unchecked(2), &buyer_award());
+    assert_eq!(result.unwrap_err(), Ok(ContractError::VotingClosed));
+
+    test.contract.close_panel_vote(&escrow_id);
+
+    let closed = test
+        .env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| *topics == ("panel_vote_closed",).into_val(&test.env))
+        .map(|(_, _, data)| -> PanelVoteClosedEvent { data.into_val(&test.env) })
+        .unwrap();
+    assert!(closed.decided);
+    assert_eq!(
+        closed.missed,
+        vec![
+            &test.env,
+            members.get_unchecked(0),
+            members.get_unchecked(2)
+        ]
+    );
+    assert_eq!(test.token.balance(&test.seller), ESCROW_AMOUNT as i128);
+}
+
+#[test]
+fn test_panel_vote_without_votes_refunds_buyer() {
+    let (test, escrow_id, _) = EscrowTest::setup_with_panel_dispute();
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += VOTING_PERIOD + 1);
+    test.contract.close_panel_vote(&escrow_id);
+
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert_eq!(test.token.balance(&test.buyer), ESCROW_AMOUNT as i128);
+}
+
+// Arbitrator registry tests
+#[test]
+fn test_register_arbitrator() {
+    let test = EscrowTest::setup();
+
+    let result = test
+        .contract
+        .try_register_arbitrator(&test.arbitrator, &500, &200);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::StakeTokenNotSet));
+
+    let result = test
+        .contract
+        .try_set_registry_config(&test.buyer, &test.token.address, &1_000);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AdminOnly));
+
+    test.register_arbitrator(&test.arbitrator, 500, 200);
+    let profile = test.contract.get_arbitrator(&test.arbitrator);
+    assert_eq!(profile.stake, 500);
+    assert_eq!(profile.fee_bps, 200);
+    assert_eq!(profile.cases_resolved, 0);
+    assert_eq!(test.token.balance(&test.arbitrator), 0);
+
+    let result = test
+        .contract
+        .try_register_arbitrator(&test.arbitrator, &500, &200);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::ArbitratorAlreadyRegistered)
+    );
+
+    let result = test.contract.try_set_fee_rate(&test.arbitrator, &10_001);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidFeeRate));
+}
+
+#[test]
+fn test_create_escrow_requires_minimum_stake() {
+    let test = EscrowTest::setup();
+    let create = |min_stake: u128| {
+        test.contract.try_create_escrow(
+            &test.buyer,
+            &test.seller,
+            &test.token.address,
+            &ESCROW_AMOUNT,
+            &test.description,
+            &EscrowTerms {
+                min_arbitrator_stake: min_stake,
+                ..test.terms()
+            },
+        )
+    };
+
+    assert_eq!(
+        create(500).unwrap_err(),
+        Ok(ContractError::ArbitratorNotRegistered)
+    );
+
+    test.register_arbitrator(&test.arbitrator, 300, 200);
+    assert_eq!(
+        create(500).unwrap_err(),
+        Ok(ContractError::InsufficientStake)
+    );
+
+    TokenAdminClient::new(&test.env, &test.token.address).mint(&test.arbitrator, &200);
+    test.contract.add_stake(&test.arbitrator, &200);
+    assert!(create(500).is_ok());
+}
+
+#[test]
+fn test_registered_arbitrator_fee_cap_and_stake_lock() {
+    let test = EscrowTest::setup();
+    test.register_arbitrator(&test.arbitrator, 500, 500); // Up to 5% fee
+    let escrow_id = test.create_escrow();
+    test.deposit_funds(escrow_id);
+
+    let result = test.contract.try_withdraw_stake(&test.arbitrator, &100);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::StakeLocked));
+
+    let dispute_reason = String::from_str(&test.env, "Dispute reason");
+    test.contract
+        .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
+
+    let too_expensive = Award {
+        to_seller: 900,
+        to_buyer: 0,
+        arbitrator_fee: 100,
+    };
+    let result = test
+        .contract
+        .try_arbitrate(&escrow_id, &test.arbitrator, &too_expensive);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAward));
+
+    let award = Award {
+        to_seller: 950,
+        to_buyer: 0,
+        arbitrator_fee: 50,
+    };
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &award);
+
+    let profile = test.contract.get_arbitrator(&test.arbitrator);
+    assert_eq!(profile.cases_resolved, 1);
+    assert_eq!(profile.active_cases, 0);
+
+    test.contract.withdraw_stake(&test.arbitrator, &500);
+    assert_eq!(test.token.balance(&test.arbitrator), 550);
+}
+
+#[test]
+fn test_fee_rate_fixed_at_creation() {
+    let test = EscrowTest::setup();
+    test.register_arbitrator(&test.arbitrator, 500, 200); // Up to 2% fee
+    let escrow_id = test.create_escrow();
+    test.deposit_funds(escrow_id);
+    assert_eq!(test.contract.get_escrow(&escrow_id).max_fee_bps, 200);
+
+    // Raising the advertised rate afterwards does not reach open escrows
+    test.contract.set_fee_rate(&test.arbitrator, &10_000);
+    let dispute_reason = String::from_str(&test.env, "Dispute reason");
+    test.contract
+        .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
+
+    let raised = Award {
+        to_seller: 900,
+        to_buyer: 0,
+        arbitrator_fee: 100,
+    };
+    let result = test
+        .contract
+        .try_arbitrate(&escrow_id, &test.arbitrator, &raised);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InvalidAward));
+}
+
+#[test]
+fn test_stake_token_locked_while_stake_outstanding() {
+    let test = EscrowTest::setup();
+    test.register_arbitrator(&test.arbitrator, 500, 200);
+
+    let other_token = Address::generate(&test.env);
+    let result = test
+        .contract
+        .try_set_registry_config(&test.admin, &other_token, &1_000);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::StakeOutstanding));
+
+    // The slash rate can still change, and the token once every stake is out
+    test.contract
+        .set_registry_config(&test.admin, &test.token.address, &500);
+    test.contract.withdraw_stake(&test.arbitrator, &500);
+    test.contract
+        .set_registry_config(&test.admin, &other_token, &500);
+}
+
+#[test]
+fn test_deposit_rechecks_minimum_stake() {
+    let test = EscrowTest::setup();
+    test.register_arbitrator(&test.arbitrator, 500, 200);
+    let escrow_id = test.contract.create_escrow(
+        &test.buyer,
+        &test.seller,
+        &test.token.address,
+        &ESCROW_AMOUNT,
+        &test.description,
+        &EscrowTerms {
+            min_arbitrator_stake: 500,
+            ..test.terms()
+        },
+    );
+
+    // Nothing is locked before funding, so the stake can still leave
+    test.contract.withdraw_stake(&test.arbitrator, &100);
+    let result = test.contract.try_deposit(&escrow_id, &test.buyer);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::InsufficientStake));
+
+    TokenAdminClient::new(&test.env, &test.token.address).mint(&test.arbitrator, &100);
+    test.contract.add_stake(&test.arbitrator, &100);
+    test.deposit_funds(escrow_id);
+
+    let result = test.contract.try_withdraw_stake(&test.arbitrator, &100);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::StakeLocked));
+}
+
+#[test]
+fn test_overturned_arbitrator_is_slashed() {
+    let (test, escrow_id, panel) = EscrowTest::setup_with_appealable_dispute();
+    test.register_arbitrator(&test.arbitrator, 1_000, 0);
+
+    test.contract
+        .arbitrate(&escrow_id, &test.arbitrator, &seller_award());
+    test.contract.file_appeal(&escrow_id, &test.buyer);
+    assert_eq!(
+        test.contract
+            .get_arbitrator(&test.arbitrator)
+            .cases_appealed,
+        1
+    );
+
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(0), &buyer_award());
+    test.contract
+        .vote_appeal(&escrow_id, &panel.get_unchecked(1), &buyer_award());
+
+    let profile = test.contract.get_arbitrator(&test.arbitrator);
+    assert_eq!(profile.cases_overturned, 1);
+    assert_eq!(profile.stake, 900);
+
+    // Buyer gets the escrow, the bond back and the slashed stake
+    assert_eq!(
+        test.token.balance(&test.buyer),
+        (ESCROW_AMOUNT + APPEAL_BOND + 100) as i128
+    );
+}
+
+// Delivery and auto-release tests
+#[test]
+fn test_mark_delivered() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+
+    let result = test.contract.try_mark_delivered(&escrow_id, &test.seller);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::EscrowNotFunded));
+
+    test.deposit_funds(escrow_id);
+    let result = test.contract.try_mark_delivered(&escrow_id, &test.buyer);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::SellerOnly));
+
+    test.contract.mark_delivered(&escrow_id, &test.seller);
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.delivered_at, Some(test.env.ledger().timestamp()));
+    assert_eq!(escrow.status, EscrowStatus::Funded);
+
+    let result = test.contract.try_mark_delivered(&escrow_id, &test.seller);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::AlreadyDelivered));
+}
+
+#[test]
+fn test_auto_release_after_inspection_period() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+    test.deposit_funds(escrow_id);
+
+    let result = test.contract.try_auto_release(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::NotDelivered));
+
+    test.contract.mark_delivered(&escrow_id, &test.seller);
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += INSPECTION_PERIOD);
+    let result = test.contract.try_auto_release(&escrow_id);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::InspectionPeriodActive)
+    );
+
+    test.env.ledger().with_mut(|l| l.timestamp += 1);
+    test.contract.auto_release(&escrow_id);
+
+    assert_eq!(test.token.balance(&test.seller), ESCROW_AMOUNT as i128);
+    let escrow = test.contract.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert!(escrow.completed_at.is_some());
+}
+
+#[test]
+fn test_mark_delivered_after_deadline() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+    test.deposit_funds(escrow_id);
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += DELIVERY_PERIOD + 1);
+    let result = test.contract.try_mark_delivered(&escrow_id, &test.seller);
+    assert_eq!(
+        result.unwrap_err(),
+        Ok(ContractError::DeliveryDeadlinePassed)
+    );
+}
+
+#[test]
+fn test_undelivered_escrow_refunded_after_release_deadline() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+    test.deposit_funds(escrow_id);
+    let buyer_balance_before = test.token.balance(&test.buyer);
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += RELEASE_PERIOD + 1);
+
+    // Without delivery the release deadline does not pay the seller
+    let result = test.contract.try_auto_release(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::NotDelivered));
+
+    test.contract.refund(&escrow_id, &test.buyer);
+    assert_eq!(test.token.balance(&test.seller), 0);
+    assert_eq!(
+        test.token.balance(&test.buyer),
+        buyer_balance_before + ESCROW_AMOUNT as i128
+    );
+    assert_eq!(
+        test.contract.get_escrow(&escrow_id).status,
+        EscrowStatus::Cancelled
+    );
+}
+
+#[test]
+fn test_auto_release_blocked_by_dispute() {
+    let test = EscrowTest::setup();
+    let escrow_id = test.create_escrow_with_deadlines();
+    test.deposit_funds(escrow_id);
+    test.contract.mark_delivered(&escrow_id, &test.seller);
+
+    let dispute_reason = String::from_str(&test.env, "Item not as described");
+    test.contract
+        .raise_dispute(&escrow_id, &test.buyer, &dispute_reason);
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += INSPECTION_PERIOD + 1);
+    let result = test.contract.try_auto_release(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::EscrowNotFunded));
+    assert_eq!(test.token.balance(&test.seller), 0);
+}
+
+#[test]
+fn test_auto_release_without_inspection_period() {
+    let (test, escrow_id) = EscrowTest::setup_with_funded_escrow();
+    test.contract.mark_delivered(&escrow_id, &test.seller);
+
+    test.env
+        .ledger()
+        .with_mut(|l| l.timestamp += INSPECTION_PERIOD + 1);
+    let result = test.contract.try_auto_release(&escrow_id);
+    assert_eq!(result.unwrap_err(), Ok(ContractError::OperationNotAllowed));
+}
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, vec, Address, BytesN, Env, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -10,6 +10,14 @@ pub enum TransactionStatus {
     Disputed,
 }
 
+/// Part of the holdback that matures `offset` seconds after creation.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseTranche {
+    pub offset: u64,
+    pub percentage: u32, // Share of the holdback amount
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Transaction {
@@ -17,17 +25,91 @@ pub struct Transaction {
     pub seller: Address,
     pub amount: u128,
     pub token: Address,
-    pub holdback_rate: u32,
+    pub holdback_rate: u32, // Basis points of the amount
+    pub holdback_amount: u128,
+    pub final_amount: u128,
+    pub release_time: u64, // When the last tranche matures
+    pub status: TransactionStatus,
+    pub created_at: u64,
+    pub tranches: Vec<ReleaseTranche>, // Ordered by offset, percentages sum to 100
+    pub released_tranches: u32,
+    pub released_amount: u128, // Paid to the seller so far
+    pub reserved_amount: u128, // Held for pending warranty claims
+    pub claimed_amount: u128,  // Paid to the buyer on approved claims
+}
+
+/// Layout of transactions stored before release schedules and warranty
+/// claims, when the holdback rate was a whole percent and the holdback was
+/// released in one go at `release_time`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyTransaction {
+    pub buyer: Address,
+    pub seller: Address,
+    pub amount: u128,
+    pub token: Address,
+    pub holdback_rate: u32, // Whole percent of the amount
     pub holdback_amount: u128,
     pub final_amount: u128,
     pub release_time: u64,
     pub status: TransactionStatus,
 }
 
+impl LegacyTransaction {
+    /// The creation time was not recorded, so the upgraded transaction starts
+    /// at zero with a single tranche maturing at the original release time.
+    pub fn upgrade(self, env: &Env) -> Transaction {
+        let completed = self.status == TransactionStatus::Completed;
+        Transaction {
+            buyer: self.buyer,
+            seller: self.seller,
+            amount: self.amount,
+            token: self.token,
+            holdback_rate: self.holdback_rate * 100,
+            holdback_amount: self.holdback_amount,
+            final_amount: self.final_amount,
+            release_time: self.release_time,
+            status: self.status,
+            created_at: 0,
+            tranches: vec![
+                env,
+                ReleaseTranche {
+                    offset: self.release_time,
+                    percentage: 100,
+                },
+            ],
+            released_tranches: if completed { 1 } else { 0 },
+            released_amount: if completed { self.holdback_amount } else { 0 },
+            reserved_amount: 0,
+            claimed_amount: 0,
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Settled, // Closed by a dispute resolution, its amount followed the outcome
+}
+
+/// Buyer's claim against the holdback, decided by the admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WarrantyClaim {
+    pub amount: u128,
+    pub reason_hash: BytesN<32>, // Hash of the off-chain claim details
+    pub status: ClaimStatus,
+    pub filed_at: u64,
+}
+
 #[contracttype]
 #[derive(Debug, Eq, PartialEq)]
 pub enum DataKey {
     Transaction(u128),
+    Claims(u128), // Vec<WarrantyClaim>, indexed by claim id
     TransactionCounter,
     Token,
     Admin,
@@ -12,4 +12,8 @@ pub enum Error {
     InvalidStatus = 7,
     Unauthorized = 8,
     AlreadyInitialized = 9,
+    InvalidReleaseSchedule = 10,
+    InvalidClaim = 11,
+    ClaimNotFound = 12,
+    TooManyClaims = 13,
 }
@@ -1,8 +1,13 @@
 use crate::entities::*;
 use crate::errors::*;
-use soroban_sdk::{contract, contractimpl, log, token, Address, Env};
+use soroban_sdk::{
+    contract, contractimpl, log, symbol_short, token, vec, Address, BytesN, Env, Map, Symbol,
+    TryFromVal, Val, Vec,
+};
 
 pub const DAY_IN_SECONDS: u64 = 86400;
+pub const MAX_BPS: u32 = 10_000;
+pub const MAX_OPEN_CLAIMS: u32 = 10;
 
 #[contract]
 pub struct HoldBackContract;
@@ -25,6 +30,27 @@ impl HoldBackContract {
         token: Address,
         holdback_rate: u32,
         holdback_days: u32,
+    ) -> Result<u128, Error> {
+        let tranches = vec![
+            &env,
+            ReleaseTranche {
+                offset: holdback_days as u64 * DAY_IN_SECONDS,
+                percentage: 100,
+            },
+        ];
+        Self::create_scheduled_payment(env, buyer, seller, amount, token, holdback_rate, tranches)
+    }
+
+    /// Like `create_payment`, but the holdback is released in tranches as
+    /// each one's offset from creation passes.
+    pub fn create_scheduled_payment(
+        env: Env,
+        buyer: Address,
+        seller: Address,
+        amount: u128,
+        token: Address,
+        holdback_rate: u32,
+        tranches: Vec<ReleaseTranche>,
     ) -> Result<u128, Error> {
         buyer.require_auth();
         let admin: Address = env
@@ -36,7 +62,7 @@ impl HoldBackContract {
         if amount == 0 {
             return Err(Error::InvalidAmount);
         }
-        if holdback_rate == 0 || holdback_rate > 100 {
+        if holdback_rate == 0 || holdback_rate > MAX_BPS {
             return Err(Error::InvalidHoldbackRate);
         }
         if buyer == seller || buyer == admin || seller == admin {
@@ -45,8 +71,10 @@ impl HoldBackContract {
         if buyer == token || seller == token {
             return Err(Error::InvalidSeller);
         }
+        let created_at = env.ledger().timestamp();
+        Self::validate_tranches(&tranches, created_at)?;
 
-        let holdback_amount = (amount * holdback_rate as u128) / 100;
+        let holdback_amount = (amount * holdback_rate as u128) / MAX_BPS as u128;
         let final_amount = amount
             .checked_sub(holdback_amount)
             .ok_or(Error::InvalidAmount)?;
@@ -73,6 +101,7 @@ impl HoldBackContract {
             .persistent()
             .set(&DataKey::TransactionCounter, &transaction_id);
 
+        let last_offset = tranches.last().map(|tranche| tranche.offset).unwrap_or(0);
         let transaction = Transaction {
             buyer: buyer.clone(),
             seller: seller.clone(),
@@ -81,8 +110,14 @@ impl HoldBackContract {
             holdback_rate,
             holdback_amount,
             final_amount,
-            release_time: env.ledger().timestamp() + (holdback_days as u64 * DAY_IN_SECONDS),
+            release_time: created_at + last_offset,
             status: TransactionStatus::Held,
+            created_at,
+            tranches,
+            released_tranches: 0,
+            released_amount: 0,
+            reserved_amount: 0,
+            claimed_amount: 0,
         };
         env.storage()
             .persistent()
@@ -95,7 +130,7 @@ impl HoldBackContract {
 
         log!(
             &env,
-            "Transaction {} created with holdback {}%",
+            "Transaction {} created with holdback {} bps",
             transaction_id,
             holdback_rate
         );
@@ -104,11 +139,7 @@ impl HoldBackContract {
 
     pub fn approve_release(env: Env, transaction_id: u128, buyer: Address) -> Result<(), Error> {
         buyer.require_auth();
-        let mut transaction: Transaction = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Transaction(transaction_id))
-            .ok_or(Error::TransactionNotFound)?;
+        let mut transaction: Transaction = Self::load_transaction(&env, transaction_id)?;
         if transaction.buyer != buyer {
             return Err(Error::Unauthorized);
         }
@@ -127,11 +158,7 @@ impl HoldBackContract {
 
     pub fn initiate_dispute(env: Env, transaction_id: u128, buyer: Address) -> Result<(), Error> {
         buyer.require_auth();
-        let mut transaction: Transaction = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Transaction(transaction_id))
-            .ok_or(Error::TransactionNotFound)?;
+        let mut transaction: Transaction = Self::load_transaction(&env, transaction_id)?;
         if transaction.buyer != buyer {
             return Err(Error::Unauthorized);
         }
@@ -167,45 +194,41 @@ impl HoldBackContract {
             return Err(Error::Unauthorized);
         }
 
-        let mut transaction: Transaction = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Transaction(transaction_id))
-            .ok_or(Error::TransactionNotFound)?;
+        let mut transaction: Transaction = Self::load_transaction(&env, transaction_id)?;
         if transaction.status != TransactionStatus::Disputed {
             return Err(Error::InvalidStatus);
         }
 
+        // Matured tranches and approved claims have already been paid out.
+        // Amounts reserved for pending claims follow the dispute's outcome.
+        let remaining =
+            transaction.holdback_amount - transaction.released_amount - transaction.claimed_amount;
+        transaction.reserved_amount = 0;
+        Self::settle_pending_claims(&env, transaction_id);
         let token_client = token::Client::new(&env, &transaction.token);
         if refund {
             token_client.transfer(
                 &env.current_contract_address(),
                 &transaction.buyer,
-                &(transaction.holdback_amount as i128),
+                &(remaining as i128),
             );
             transaction.status = TransactionStatus::Cancelled;
             env.events().publish(
                 ("holdback_refunded",),
-                (
-                    transaction_id,
-                    transaction.buyer.clone(),
-                    transaction.holdback_amount,
-                ),
+                (transaction_id, transaction.buyer.clone(), remaining),
             );
         } else {
             token_client.transfer(
                 &env.current_contract_address(),
                 &transaction.seller,
-                &(transaction.holdback_amount as i128),
+                &(remaining as i128),
             );
+            transaction.released_amount += remaining;
+            transaction.released_tranches = transaction.tranches.len();
             transaction.status = TransactionStatus::Completed;
             env.events().publish(
                 ("holdback_released",),
-                (
-                    transaction_id,
-                    transaction.seller.clone(),
-                    transaction.holdback_amount,
-                ),
+                (transaction_id, transaction.seller.clone(), remaining),
             );
         }
         env.storage()
@@ -214,12 +237,30 @@ impl HoldBackContract {
         Ok(())
     }
 
-    pub fn check_and_release(env: Env, transaction_id: u128) -> Result<(), Error> {
-        let transaction: Transaction = env
+    /// Marks every pending claim as settled by a dispute resolution.
+    fn settle_pending_claims(env: &Env, transaction_id: u128) {
+        let mut claims: Vec<WarrantyClaim> = env
             .storage()
             .persistent()
-            .get(&DataKey::Transaction(transaction_id))
-            .ok_or(Error::TransactionNotFound)?;
+            .get(&DataKey::Claims(transaction_id))
+            .unwrap_or(Vec::new(env));
+        let mut settled = false;
+        for (claim_id, mut claim) in claims.clone().iter().enumerate() {
+            if claim.status == ClaimStatus::Pending {
+                claim.status = ClaimStatus::Settled;
+                claims.set(claim_id as u32, claim);
+                settled = true;
+            }
+        }
+        if settled {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Claims(transaction_id), &claims);
+        }
+    }
+
+    pub fn check_and_release(env: Env, transaction_id: u128) -> Result<(), Error> {
+        let transaction: Transaction = Self::load_transaction(&env, transaction_id)?;
         if transaction.status != TransactionStatus::Held
             && transaction.status != TransactionStatus::HoldbackPending
         {
@@ -230,45 +271,258 @@ impl HoldBackContract {
         Ok(())
     }
 
+    fn validate_tranches(tranches: &Vec<ReleaseTranche>, created_at: u64) -> Result<(), Error> {
+        if tranches.is_empty() {
+            return Err(Error::InvalidReleaseSchedule);
+        }
+        let mut total: u32 = 0;
+        let mut previous_offset: Option<u64> = None;
+        for tranche in tranches.iter() {
+            if tranche.percentage == 0
+                || previous_offset.is_some_and(|o| tranche.offset <= o)
+                || created_at.checked_add(tranche.offset).is_none()
+            {
+                return Err(Error::InvalidReleaseSchedule);
+            }
+            total = total.saturating_add(tranche.percentage);
+            previous_offset = Some(tranche.offset);
+        }
+        if total != 100 {
+            return Err(Error::InvalidReleaseSchedule);
+        }
+        Ok(())
+    }
+
     fn release_holdback_if_due(env: &Env, transaction_id: u128) -> Result<(), Error> {
-        let mut transaction: Transaction = env
-            .storage()
+        let mut transaction: Transaction = Self::load_transaction(env, transaction_id)?;
+
+        // Buyer approval releases every tranche, otherwise only matured ones
+        let approved = transaction.status == TransactionStatus::HoldbackPending;
+        let now = env.ledger().timestamp();
+        let already_released = transaction.released_tranches;
+        for tranche in transaction.tranches.iter().skip(already_released as usize) {
+            if !approved && now < transaction.created_at.saturating_add(tranche.offset) {
+                break;
+            }
+            transaction.released_tranches += 1;
+        }
+
+        Self::pay_seller_share(env, transaction_id, &mut transaction);
+        env.storage()
             .persistent()
-            .get(&DataKey::Transaction(transaction_id))
-            .ok_or(Error::TransactionNotFound)?;
+            .set(&DataKey::Transaction(transaction_id), &transaction);
+        Ok(())
+    }
 
-        if transaction.status == TransactionStatus::HoldbackPending
-            || (transaction.status == TransactionStatus::Held
-                && env.ledger().timestamp() >= transaction.release_time)
-        {
-            let token_client = token::Client::new(&env, &transaction.token);
+    /// Pays the seller whatever the matured tranches entitle them to, after
+    /// setting aside approved and pending claims, and completes the
+    /// transaction once every tranche has matured and no claim is pending.
+    fn pay_seller_share(env: &Env, transaction_id: u128, transaction: &mut Transaction) {
+        let all_matured = transaction.released_tranches == transaction.tranches.len();
+        let target = if all_matured {
+            // The last tranche also picks up any rounding remainder
+            transaction.holdback_amount
+        } else {
+            let matured_percentage: u32 = transaction
+                .tranches
+                .iter()
+                .take(transaction.released_tranches as usize)
+                .map(|tranche| tranche.percentage)
+                .sum();
+            (transaction.holdback_amount * matured_percentage as u128) / 100
+        };
+        let available =
+            transaction.holdback_amount - transaction.claimed_amount - transaction.reserved_amount;
+        let payout = target
+            .min(available)
+            .saturating_sub(transaction.released_amount);
+
+        if payout > 0 {
+            let token_client = token::Client::new(env, &transaction.token);
             token_client.transfer(
                 &env.current_contract_address(),
                 &transaction.seller,
-                &(transaction.holdback_amount as i128),
+                &(payout as i128),
             );
-            transaction.status = TransactionStatus::Completed;
-            env.storage()
-                .persistent()
-                .set(&DataKey::Transaction(transaction_id), &transaction);
-
+            transaction.released_amount += payout;
             env.events().publish(
                 ("holdback_released",),
-                (
-                    transaction_id,
-                    transaction.seller,
-                    transaction.holdback_amount,
-                ),
+                (transaction_id, transaction.seller.clone(), payout),
+            );
+        }
+        if all_matured && transaction.reserved_amount == 0 {
+            transaction.status = TransactionStatus::Completed;
+        }
+    }
+
+    /// Buyer files a warranty claim for part of the amount still held back.
+    /// Returns the claim id.
+    pub fn file_claim(
+        env: Env,
+        transaction_id: u128,
+        buyer: Address,
+        amount: u128,
+        reason_hash: BytesN<32>,
+    ) -> Result<u32, Error> {
+        buyer.require_auth();
+        let mut transaction: Transaction = Self::load_transaction(&env, transaction_id)?;
+        if transaction.buyer != buyer {
+            return Err(Error::Unauthorized);
+        }
+        if transaction.status != TransactionStatus::Held
+            && transaction.status != TransactionStatus::HoldbackPending
+        {
+            return Err(Error::InvalidStatus);
+        }
+
+        let unclaimed = transaction.holdback_amount
+            - transaction.released_amount
+            - transaction.claimed_amount
+            - transaction.reserved_amount;
+        if amount == 0 || amount > unclaimed {
+            return Err(Error::InvalidClaim);
+        }
+
+        let mut claims: Vec<WarrantyClaim> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Claims(transaction_id))
+            .unwrap_or(Vec::new(&env));
+        let open_claims = claims
+            .iter()
+            .filter(|claim| claim.status == ClaimStatus::Pending)
+            .count() as u32;
+        if open_claims >= MAX_OPEN_CLAIMS {
+            return Err(Error::TooManyClaims);
+        }
+        let claim_id = claims.len();
+        claims.push_back(WarrantyClaim {
+            amount,
+            reason_hash: reason_hash.clone(),
+            status: ClaimStatus::Pending,
+            filed_at: env.ledger().timestamp(),
+        });
+        transaction.reserved_amount += amount;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Claims(transaction_id), &claims);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Transaction(transaction_id), &transaction);
+
+        env.events().publish(
+            ("claim_filed",),
+            (transaction_id, claim_id, amount, reason_hash),
+        );
+        Ok(claim_id)
+    }
+
+    /// Admin approves a claim, paying its amount to the buyer, or rejects it,
+    /// returning the amount to the seller's share.
+    pub fn resolve_claim(
+        env: Env,
+        transaction_id: u128,
+        claim_id: u32,
+        approve: bool,
+        admin: Address,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let stored_admin: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        if admin != stored_admin {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut transaction: Transaction = Self::load_transaction(&env, transaction_id)?;
+        if transaction.status != TransactionStatus::Held
+            && transaction.status != TransactionStatus::HoldbackPending
+        {
+            return Err(Error::InvalidStatus);
+        }
+
+        let mut claims: Vec<WarrantyClaim> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Claims(transaction_id))
+            .unwrap_or(Vec::new(&env));
+        let mut claim = claims.get(claim_id).ok_or(Error::ClaimNotFound)?;
+        if claim.status != ClaimStatus::Pending {
+            return Err(Error::InvalidStatus);
+        }
+
+        transaction.reserved_amount -= claim.amount;
+        if approve {
+            let token_client = token::Client::new(&env, &transaction.token);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &transaction.buyer,
+                &(claim.amount as i128),
             );
+            transaction.claimed_amount += claim.amount;
+            claim.status = ClaimStatus::Approved;
+        } else {
+            claim.status = ClaimStatus::Rejected;
         }
+        claims.set(claim_id, claim.clone());
+
+        // A rejected claim may free up a share the seller is already owed
+        Self::pay_seller_share(&env, transaction_id, &mut transaction);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Claims(transaction_id), &claims);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Transaction(transaction_id), &transaction);
+
+        env.events().publish(
+            ("claim_resolved",),
+            (transaction_id, claim_id, approve, claim.amount),
+        );
         Ok(())
     }
 
+    pub fn get_claims(env: Env, transaction_id: u128) -> Vec<WarrantyClaim> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Claims(transaction_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
     pub fn get_transaction(env: Env, transaction_id: u128) -> Result<Transaction, Error> {
+        Self::load_transaction(&env, transaction_id)
+    }
+
+    /// Rewrites a transaction stored in the legacy layout in the current one.
+    /// Reads upgrade legacy transactions on the fly, so this only saves the
+    /// conversion on each later read. Callable by anyone.
+    pub fn migrate_transaction(env: Env, transaction_id: u128) -> Result<(), Error> {
+        let transaction = Self::load_transaction(&env, transaction_id)?;
         env.storage()
+            .persistent()
+            .set(&DataKey::Transaction(transaction_id), &transaction);
+        Ok(())
+    }
+
+    /// Reads a transaction, upgrading one stored in the legacy layout.
+    fn load_transaction(env: &Env, transaction_id: u128) -> Result<Transaction, Error> {
+        let stored: Map<Symbol, Val> = env
+            .storage()
             .persistent()
             .get(&DataKey::Transaction(transaction_id))
-            .ok_or(Error::TransactionNotFound)
+            .ok_or(Error::TransactionNotFound)?;
+        // Only the current layout records a release schedule
+        if stored.contains_key(symbol_short!("tranches")) {
+            return Transaction::try_from_val(env, &stored.to_val())
+                .map_err(|_| Error::TransactionNotFound);
+        }
+        let legacy = LegacyTransaction::try_from_val(env, &stored.to_val())
+            .map_err(|_| Error::TransactionNotFound)?;
+        Ok(legacy.upgrade(env))
     }
 
     pub fn get_admin(env: Env) -> Result<Address, Error> {
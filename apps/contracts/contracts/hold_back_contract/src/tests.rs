@@ -1,12 +1,12 @@
 #[cfg(test)]
 mod test {
 
-    use crate::{entities::*, hold_back_contract::*};
+    use crate::{entities::*, errors::*, hold_back_contract::*};
 
     use soroban_sdk::{
         testutils::{Address as _, Ledger},
-        token::{self, StellarAssetClient},
-        Address, Env,
+        token::{self, StellarAssetClient, TokenClient},
+        vec, Address, BytesN, Env, Vec,
     };
 
     fn create_variables() -> (Env, Address, Address) {
@@ -46,6 +46,24 @@ mod test {
             Address::generate(env),
         )
     }
+
+    fn thirds_every_30_days(env: &Env) -> Vec<ReleaseTranche> {
+        vec![
+            env,
+            ReleaseTranche {
+                offset: 30 * DAY_IN_SECONDS,
+                percentage: 34,
+            },
+            ReleaseTranche {
+                offset: 60 * DAY_IN_SECONDS,
+                percentage: 33,
+            },
+            ReleaseTranche {
+                offset: 90 * DAY_IN_SECONDS,
+                percentage: 33,
+            },
+        ]
+    }
     //"initialize the contract"
     #[test]
     fn test_initialize_contract() {
@@ -68,13 +86,13 @@ mod test {
         token_client.mint(&buyer, &4000);
 
         let transaction_id =
-            contract_instance.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+            contract_instance.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
         let transaction = contract_instance.get_transaction(&transaction_id);
 
         assert_eq!(transaction.buyer, buyer);
         assert_eq!(transaction.seller, seller);
         assert_eq!(transaction.amount, 1000);
-        assert_eq!(transaction.holdback_rate, 20);
+        assert_eq!(transaction.holdback_rate, 2000);
         assert_eq!(transaction.holdback_amount, 200);
         assert_eq!(transaction.final_amount, 800);
         assert_eq!(transaction.status, TransactionStatus::Held);
@@ -91,7 +109,7 @@ mod test {
         token_client.mint(&buyer, &4000);
 
         let transaction_id =
-            contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
 
         contract.approve_release(&transaction_id, &buyer);
         let transaction = contract.get_transaction(&transaction_id);
@@ -107,7 +125,7 @@ mod test {
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
         let transaction_id =
-            contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &1);
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &1);
 
         env.ledger().set_timestamp(DAY_IN_SECONDS + 1);
 
@@ -126,7 +144,7 @@ mod test {
         token_client.mint(&buyer, &4000);
 
         let transaction_id =
-            contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
 
         contract.initiate_dispute(&transaction_id, &buyer);
         contract.resolve_dispute(&transaction_id, &true, &admin);
@@ -153,7 +171,7 @@ mod test {
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
         let transaction_id =
-            contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
 
         contract.approve_release(&transaction_id, &seller);
     }
@@ -166,7 +184,7 @@ mod test {
         let (buyer, seller, _, _) = generate_addresses(&env);
 
         let (token_address, _) = create_token(&env, &admin);
-        contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+        contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
     }
     #[test]
     #[should_panic]
@@ -190,7 +208,7 @@ mod test {
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
         let transaction_id =
-            contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
 
         contract.initiate_dispute(&transaction_id, &seller);
     }
@@ -205,7 +223,7 @@ mod test {
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
         let transaction_id =
-            contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
 
         contract.initiate_dispute(&transaction_id, &buyer);
         contract.resolve_dispute(&transaction_id, &true, &buyer);
@@ -221,7 +239,7 @@ mod test {
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
         let transaction_id =
-            contract.create_payment(&buyer, &seller, &1000, &token_address, &20, &7);
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
 
         contract.initiate_dispute(&transaction_id, &buyer);
         contract.approve_release(&transaction_id, &buyer);
@@ -236,7 +254,7 @@ mod test {
 
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
-        contract.create_payment(&buyer, &buyer, &1000, &token_address, &20, &7);
+        contract.create_payment(&buyer, &buyer, &1000, &token_address, &2000, &7);
     }
 
     #[test]
@@ -248,7 +266,7 @@ mod test {
 
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
-        contract.create_payment(&admin, &seller, &1000, &token_address, &20, &7);
+        contract.create_payment(&admin, &seller, &1000, &token_address, &2000, &7);
     }
 
     #[test]
@@ -260,6 +278,333 @@ mod test {
 
         let (token_address, token_client) = create_token(&env, &admin);
         token_client.mint(&buyer, &4000);
-        contract.create_payment(&buyer, &token_address, &1000, &token_address, &20, &7);
+        contract.create_payment(&buyer, &token_address, &1000, &token_address, &2000, &7);
+    }
+
+    #[test]
+    fn test_graduated_release() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &4000);
+        let token = TokenClient::new(&env, &token_address);
+
+        let transaction_id = contract.create_scheduled_payment(
+            &buyer,
+            &seller,
+            &1000,
+            &token_address,
+            &2500,
+            &thirds_every_30_days(&env),
+        );
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(transaction.release_time, 90 * DAY_IN_SECONDS);
+        assert_eq!(token.balance(&seller), 750);
+
+        // Nothing has matured yet
+        contract.check_and_release(&transaction_id);
+        assert_eq!(token.balance(&seller), 750);
+
+        env.ledger().set_timestamp(30 * DAY_IN_SECONDS);
+        contract.check_and_release(&transaction_id);
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(token.balance(&seller), 835);
+        assert_eq!(transaction.released_tranches, 1);
+        assert_eq!(transaction.released_amount, 85);
+        assert_eq!(transaction.status, TransactionStatus::Held);
+
+        // Both remaining tranches matured; the last one takes the rounding remainder
+        env.ledger().set_timestamp(90 * DAY_IN_SECONDS);
+        contract.check_and_release(&transaction_id);
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(token.balance(&seller), 1000);
+        assert_eq!(transaction.released_amount, 250);
+        assert_eq!(transaction.status, TransactionStatus::Completed);
+    }
+
+    #[test]
+    fn test_dispute_after_partial_release_refunds_remainder() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &1000);
+        let token = TokenClient::new(&env, &token_address);
+
+        let transaction_id = contract.create_scheduled_payment(
+            &buyer,
+            &seller,
+            &1000,
+            &token_address,
+            &3000,
+            &thirds_every_30_days(&env),
+        );
+
+        env.ledger().set_timestamp(30 * DAY_IN_SECONDS);
+        contract.check_and_release(&transaction_id);
+        contract.initiate_dispute(&transaction_id, &buyer);
+        contract.resolve_dispute(&transaction_id, &true, &admin);
+
+        assert_eq!(token.balance(&seller), 802);
+        assert_eq!(token.balance(&buyer), 198);
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(transaction.status, TransactionStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_invalid_release_schedule() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &4000);
+
+        let short = vec![
+            &env,
+            ReleaseTranche {
+                offset: DAY_IN_SECONDS,
+                percentage: 60,
+            },
+        ];
+        let unordered = vec![
+            &env,
+            ReleaseTranche {
+                offset: 2 * DAY_IN_SECONDS,
+                percentage: 50,
+            },
+            ReleaseTranche {
+                offset: DAY_IN_SECONDS,
+                percentage: 50,
+            },
+        ];
+        // Matures past the end of time
+        env.ledger().set_timestamp(DAY_IN_SECONDS);
+        let overflowing = vec![
+            &env,
+            ReleaseTranche {
+                offset: u64::MAX,
+                percentage: 100,
+            },
+        ];
+        for tranches in [Vec::new(&env), short, unordered, overflowing] {
+            let result = contract.try_create_scheduled_payment(
+                &buyer,
+                &seller,
+                &1000,
+                &token_address,
+                &2000,
+                &tranches,
+            );
+            assert_eq!(result, Err(Ok(Error::InvalidReleaseSchedule)));
+        }
+    }
+
+    #[test]
+    fn test_legacy_transaction_upgraded_on_read() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&contract.address, &200);
+        let token = TokenClient::new(&env, &token_address);
+
+        // Stored before tranches and claims, with the rate in whole percent
+        let legacy = LegacyTransaction {
+            buyer,
+            seller: seller.clone(),
+            amount: 1000,
+            token: token_address,
+            holdback_rate: 20,
+            holdback_amount: 200,
+            final_amount: 800,
+            release_time: 7 * DAY_IN_SECONDS,
+            status: TransactionStatus::Held,
+        };
+        env.as_contract(&contract.address, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Transaction(1), &legacy);
+        });
+
+        let transaction = contract.get_transaction(&1);
+        assert_eq!(transaction.holdback_rate, 2000);
+        assert_eq!(transaction.released_tranches, 0);
+        assert_eq!(transaction.tranches.len(), 1);
+
+        contract.migrate_transaction(&1);
+        env.as_contract(&contract.address, || {
+            let stored: Option<Transaction> =
+                env.storage().persistent().get(&DataKey::Transaction(1));
+            assert_eq!(stored, Some(transaction));
+        });
+
+        // The single tranche matures at the original release time
+        env.ledger().set_timestamp(7 * DAY_IN_SECONDS - 1);
+        contract.check_and_release(&1);
+        assert_eq!(token.balance(&seller), 0);
+
+        env.ledger().set_timestamp(7 * DAY_IN_SECONDS);
+        contract.check_and_release(&1);
+        assert_eq!(token.balance(&seller), 200);
+        assert_eq!(
+            contract.get_transaction(&1).status,
+            TransactionStatus::Completed
+        );
+    }
+
+    #[test]
+    fn test_holdback_rate_in_basis_points() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &4000);
+
+        let transaction_id =
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &250, &7);
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(transaction.holdback_amount, 25);
+        assert_eq!(transaction.final_amount, 975);
+
+        let result =
+            contract.try_create_payment(&buyer, &seller, &1000, &token_address, &10_001, &7);
+        assert_eq!(result, Err(Ok(Error::InvalidHoldbackRate)));
+    }
+
+    #[test]
+    fn test_warranty_claims() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &4000);
+        let token = TokenClient::new(&env, &token_address);
+
+        let transaction_id =
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
+        let reason = BytesN::from_array(&env, &[1; 32]);
+
+        let result = contract.try_file_claim(&transaction_id, &seller, &50, &reason);
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+        let scratch = contract.file_claim(&transaction_id, &buyer, &50, &reason);
+        let dent = contract.file_claim(&transaction_id, &buyer, &30, &reason);
+        assert_eq!((scratch, dent), (0, 1));
+
+        // Only 120 of the 200 holdback is left unclaimed
+        let result = contract.try_file_claim(&transaction_id, &buyer, &121, &reason);
+        assert_eq!(result, Err(Ok(Error::InvalidClaim)));
+
+        let result = contract.try_resolve_claim(&transaction_id, &scratch, &true, &buyer);
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+        contract.resolve_claim(&transaction_id, &scratch, &true, &admin);
+        contract.resolve_claim(&transaction_id, &dent, &false, &admin);
+        assert_eq!(token.balance(&buyer), 3050);
+
+        let result = contract.try_resolve_claim(&transaction_id, &dent, &true, &admin);
+        assert_eq!(result, Err(Ok(Error::InvalidStatus)));
+        let result = contract.try_resolve_claim(&transaction_id, &2, &true, &admin);
+        assert_eq!(result, Err(Ok(Error::ClaimNotFound)));
+
+        let claims = contract.get_claims(&transaction_id);
+        assert_eq!(claims.get(0).unwrap().status, ClaimStatus::Approved);
+        assert_eq!(claims.get(1).unwrap().status, ClaimStatus::Rejected);
+
+        // The unclaimed remainder goes to the seller
+        env.ledger().set_timestamp(7 * DAY_IN_SECONDS);
+        contract.check_and_release(&transaction_id);
+        assert_eq!(token.balance(&seller), 950);
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(transaction.claimed_amount, 50);
+        assert_eq!(transaction.status, TransactionStatus::Completed);
+    }
+
+    #[test]
+    fn test_pending_claim_delays_completion() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &4000);
+        let token = TokenClient::new(&env, &token_address);
+
+        let transaction_id =
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
+        let reason = BytesN::from_array(&env, &[2; 32]);
+        let claim_id = contract.file_claim(&transaction_id, &buyer, &80, &reason);
+
+        env.ledger().set_timestamp(7 * DAY_IN_SECONDS);
+        contract.check_and_release(&transaction_id);
+        assert_eq!(token.balance(&seller), 920);
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(transaction.reserved_amount, 80);
+        assert_eq!(transaction.status, TransactionStatus::Held);
+
+        contract.resolve_claim(&transaction_id, &claim_id, &false, &admin);
+        assert_eq!(token.balance(&seller), 1000);
+        let transaction = contract.get_transaction(&transaction_id);
+        assert_eq!(transaction.reserved_amount, 0);
+        assert_eq!(transaction.status, TransactionStatus::Completed);
+    }
+
+    #[test]
+    fn test_dispute_settles_pending_claims() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &4000);
+        let token = TokenClient::new(&env, &token_address);
+
+        let transaction_id =
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
+        let reason = BytesN::from_array(&env, &[3; 32]);
+        let approved = contract.file_claim(&transaction_id, &buyer, &20, &reason);
+        let pending = contract.file_claim(&transaction_id, &buyer, &40, &reason);
+        contract.resolve_claim(&transaction_id, &approved, &true, &admin);
+
+        contract.initiate_dispute(&transaction_id, &buyer);
+        contract.resolve_dispute(&transaction_id, &true, &admin);
+        assert_eq!(token.balance(&buyer), 3200);
+
+        // The pending claim was decided by the dispute, not left open
+        let claims = contract.get_claims(&transaction_id);
+        assert_eq!(claims.get(approved).unwrap().status, ClaimStatus::Approved);
+        assert_eq!(claims.get(pending).unwrap().status, ClaimStatus::Settled);
+        let result = contract.try_resolve_claim(&transaction_id, &pending, &true, &admin);
+        assert_eq!(result, Err(Ok(Error::InvalidStatus)));
+    }
+
+    #[test]
+    fn test_open_claims_capped() {
+        let (env, contract, admin) = create_variables_and_initialize_contract();
+
+        let (buyer, seller, _, _) = generate_addresses(&env);
+
+        let (token_address, token_client) = create_token(&env, &admin);
+        token_client.mint(&buyer, &4000);
+
+        let transaction_id =
+            contract.create_payment(&buyer, &seller, &1000, &token_address, &2000, &7);
+        let reason = BytesN::from_array(&env, &[4; 32]);
+        for _ in 0..MAX_OPEN_CLAIMS {
+            contract.file_claim(&transaction_id, &buyer, &1, &reason);
+        }
+        let result = contract.try_file_claim(&transaction_id, &buyer, &1, &reason);
+        assert_eq!(result, Err(Ok(Error::TooManyClaims)));
+
+        // Deciding a claim makes room for another
+        contract.resolve_claim(&transaction_id, &0, &false, &admin);
+        contract.file_claim(&transaction_id, &buyer, &1, &reason);
     }
 }